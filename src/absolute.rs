@@ -0,0 +1,126 @@
+use core::fmt;
+use crate::button::Polarity;
+use crate::ButtonInput;
+
+/// Binary value of a Gray code.
+pub const fn gray_to_binary(gray: u16) -> u16 {
+    let mut binary = gray;
+    let mut shift = 1;
+    while shift < 16 {
+        binary ^= binary >> shift;
+        shift <<= 1;
+    }
+    binary
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GrayAction {
+    None,
+    PositionChanged { from: u16, to: u16 },
+}
+
+/// Pin of an [`AbsoluteGray`] that failed to read, `bit` counted from the least significant.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct GrayError<E> {
+    pub bit: usize,
+    pub error: E,
+}
+
+impl<E> fmt::Display for GrayError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gray code bit {} pin error", self.bit)
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for GrayError<E> {}
+
+/// Absolute selector switch with a Gray coded output of `BITS` lines, least significant
+/// first, at most 16. A code is accepted after `stable_samples` identical readings, so the
+/// transient codes seen while the contacts switch are dropped. The first accepted code sets
+/// the position without reporting a change.
+pub struct AbsoluteGray<P, const BITS: usize> {
+    pins: [P; BITS],
+    active_low: bool,
+    stable_samples: u8,
+    candidate: u16,
+    count: u8,
+    position: Option<u16>,
+}
+
+impl<P, const BITS: usize> AbsoluteGray<P, BITS>
+where
+    P: ButtonInput,
+{
+    const VALID_BITS: () = assert!(BITS >= 1 && BITS <= 16, "BITS must be 1 to 16");
+
+    pub fn new(pins: [P; BITS], stable_samples: u8) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_BITS;
+        Self {
+            pins,
+            active_low: false,
+            stable_samples: stable_samples.max(1),
+            candidate: 0,
+            count: 0,
+            position: None,
+        }
+    }
+
+    /// Active low lines read a set bit while low, e.g. contacts pulling pulled-up pins to ground.
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        self.active_low = polarity == Polarity::ActiveLow;
+    }
+
+    /// Zero is treated as one.
+    pub fn set_stable_samples(&mut self, samples: u8) {
+        self.stable_samples = samples.max(1);
+    }
+
+    /// Accepted position, `None` until the first code is stable.
+    pub fn position(&self) -> Option<u16> {
+        self.position
+    }
+
+    pub fn pins(&mut self) -> &mut [P; BITS] {
+        &mut self.pins
+    }
+
+    pub fn into_pins(self) -> [P; BITS] {
+        self.pins
+    }
+
+    /// Forgets the position, the next stable code sets it again without a change.
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.position = None;
+    }
+
+    pub fn update(&mut self) -> Result<GrayAction, GrayError<P::Error>> {
+        let mut code = 0;
+        for (bit, pin) in self.pins.iter_mut().enumerate() {
+            let high = pin.is_high().map_err(|error| GrayError { bit, error })?;
+            code |= ((high != self.active_low) as u16) << bit;
+        }
+        Ok(self.update_with_code(code))
+    }
+
+    /// Updates from a Gray code read elsewhere, e.g. a whole port at once. Polarity is not
+    /// applied, bits above `BITS` are ignored.
+    pub fn update_with_code(&mut self, code: u16) -> GrayAction {
+        let code = code & (u16::MAX >> (16 - BITS));
+        if code == self.candidate && self.count != 0 {
+            self.count = self.count.saturating_add(1);
+        } else {
+            self.candidate = code;
+            self.count = 1;
+        }
+        if self.count < self.stable_samples {
+            return GrayAction::None;
+        }
+        let to = gray_to_binary(code);
+        match self.position.replace(to) {
+            Some(from) if from != to => GrayAction::PositionChanged { from, to },
+            _ => GrayAction::None,
+        }
+    }
+}
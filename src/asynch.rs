@@ -0,0 +1,200 @@
+use crate::button::{Error, TimeButtonAction};
+use crate::rotary::{FullStep, QuadratureDecoder, RotaryError, Rotation, Steps};
+use crate::{Clock, Instant};
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+use embedded_hal_1::digital::InputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use fugit::{ExtU32, MillisDurationU32};
+
+enum Either<A, B> {
+    First(A),
+    Second(B),
+}
+
+async fn select<F1: Future, F2: Future>(f1: F1, f2: F2) -> Either<F1::Output, F2::Output> {
+    let mut f1 = pin!(f1);
+    let mut f2 = pin!(f2);
+    poll_fn(|cx| {
+        if let Poll::Ready(r) = f1.as_mut().poll(cx) {
+            return Poll::Ready(Either::First(r));
+        }
+        if let Poll::Ready(r) = f2.as_mut().poll(cx) {
+            return Poll::Ready(Either::Second(r));
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+async fn wait_for<K: Wait>(k_pin: &mut K, high: bool) -> Result<(), K::Error> {
+    match high {
+        true => k_pin.wait_for_high().await,
+        false => k_pin.wait_for_low().await,
+    }
+}
+
+/// Button awaiting pin edges instead of polling. After an edge the pin is sampled again
+/// after the debounce delay, while held `Held` is reported every held interval.
+pub struct AsyncButton<K, D, T, const INVERTED: bool = false>
+where
+    K: Wait + InputPin,
+    D: DelayNs,
+    T: Instant,
+{
+    k_pin: K,
+    delay: D,
+    debounce: MillisDurationU32,
+    held_interval: MillisDurationU32,
+    pressed: bool,
+    press_at: Option<T>,
+    handle_press: bool,
+}
+
+impl<K, D, T, const INVERTED: bool> AsyncButton<K, D, T, INVERTED>
+where
+    K: Wait + InputPin,
+    D: DelayNs,
+    T: Instant,
+{
+    pub fn new(k_pin: K, delay: D) -> Self {
+        Self {
+            k_pin,
+            delay,
+            debounce: 10.millis(),
+            held_interval: 100.millis(),
+            pressed: false,
+            press_at: None,
+            handle_press: false,
+        }
+    }
+
+    /// Delay between an edge and the confirming sample. Default is 10 ms.
+    pub fn set_debounce(&mut self, debounce: MillisDurationU32) {
+        self.debounce = debounce;
+    }
+
+    /// Period of `Held` reports while pressed. Default is 100 ms.
+    pub fn set_held_interval(&mut self, interval: MillisDurationU32) {
+        self.held_interval = interval;
+    }
+
+    /// Suppresses `Held` and `Click` of the current press, its release is `Release`.
+    pub fn handle_press(&mut self) -> bool {
+        if self.pressed && !self.handle_press {
+            self.handle_press = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_pressed(&mut self) -> Result<bool, Error<K::Error>> {
+        Ok(self.k_pin.is_high().map_err(Error::KPin)? ^ INVERTED)
+    }
+
+    fn held_for(&self, now: T) -> MillisDurationU32 {
+        self.press_at.map_or(MillisDurationU32::from_ticks(0), |at| now.duration_since(at))
+    }
+
+    pub async fn next<C>(&mut self, clock: &mut C) -> Result<TimeButtonAction, Error<K::Error>>
+    where
+        C: Clock<Instant = T>,
+    {
+        loop {
+            if !self.pressed {
+                wait_for(&mut self.k_pin, !INVERTED).await.map_err(Error::KPin)?;
+                self.delay.delay_ms(self.debounce.ticks()).await;
+                if self.is_pressed()? {
+                    self.pressed = true;
+                    self.handle_press = false;
+                    self.press_at = Some(clock.now());
+                    return Ok(TimeButtonAction::Press);
+                }
+            } else {
+                let released = wait_for(&mut self.k_pin, INVERTED);
+                let timeout = self.delay.delay_ms(self.held_interval.ticks());
+                match select(released, timeout).await {
+                    Either::First(r) => {
+                        r.map_err(Error::KPin)?;
+                        self.delay.delay_ms(self.debounce.ticks()).await;
+                        if !self.is_pressed()? {
+                            self.pressed = false;
+                            let t = self.held_for(clock.now());
+                            self.press_at = None;
+                            return Ok(match self.handle_press {
+                                true => TimeButtonAction::Release(t),
+                                false => TimeButtonAction::Click(t),
+                            });
+                        }
+                    }
+                    Either::Second(()) if !self.handle_press => {
+                        return Ok(TimeButtonAction::Held(self.held_for(clock.now())));
+                    }
+                    Either::Second(()) => {}
+                }
+            }
+        }
+    }
+}
+
+/// Rotary awaiting pin edges instead of polling. Each wake samples both lines and runs
+/// the usual decoder, so a glitch shorter than the wake up is missed like between polls.
+pub struct AsyncRotary<A, B, M = FullStep>
+where
+    A: Wait + InputPin,
+    B: Wait + InputPin,
+{
+    a_pin: A,
+    b_pin: B,
+    decoder: QuadratureDecoder<M>,
+}
+
+impl<A, B, M: Steps> AsyncRotary<A, B, M>
+where
+    A: Wait + InputPin,
+    B: Wait + InputPin,
+{
+    pub fn new(a_pin: A, b_pin: B) -> Self {
+        Self::from_decoder(a_pin, b_pin, QuadratureDecoder::new())
+    }
+
+    pub fn from_decoder(a_pin: A, b_pin: B, decoder: QuadratureDecoder<M>) -> Self {
+        Self { a_pin, b_pin, decoder }
+    }
+
+    pub fn decoder(&mut self) -> &mut QuadratureDecoder<M> {
+        &mut self.decoder
+    }
+
+    pub fn into_parts(self) -> (A, B) {
+        (self.a_pin, self.b_pin)
+    }
+
+    fn read_levels(&mut self) -> Result<(bool, bool), RotaryError<A::Error, B::Error>> {
+        let a_low = self.a_pin.is_low().map_err(RotaryError::APin)?;
+        let b_low = self.b_pin.is_low().map_err(RotaryError::BPin)?;
+        Ok((a_low, b_low))
+    }
+
+    /// Waits for the next detent. Cancel safe: samples are decoded between the waits, so a
+    /// dropped future keeps the partial detent for the next call.
+    pub async fn next_rotation(&mut self) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
+        loop {
+            let (a_low, b_low) = self.read_levels()?;
+            let rot = self.decoder.step(a_low, b_low);
+            if !rot.is_zero() {
+                return Ok(rot);
+            }
+            // Waiting for the opposite of the sampled levels catches a change made since
+            let a_changed = wait_for(&mut self.a_pin, a_low);
+            let b_changed = wait_for(&mut self.b_pin, b_low);
+            match select(a_changed, b_changed).await {
+                Either::First(r) => r.map_err(RotaryError::APin)?,
+                Either::Second(r) => r.map_err(RotaryError::BPin)?,
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use portable_atomic::{AtomicI16, Ordering};
+use crate::rotary::{FullStep, QuadratureDecoder, Rotation, Steps};
+
+// Pending detents, added to by compare and swap
+struct Count {
+    pending: AtomicI16,
+}
+
+impl Count {
+    const fn new() -> Self {
+        Self { pending: AtomicI16::new(0) }
+    }
+
+    fn add(&self, steps: i16) {
+        let _ = self.pending.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+            Some(count.saturating_add(steps))
+        });
+    }
+
+    fn take(&self) -> i16 {
+        self.pending.swap(0, Ordering::AcqRel)
+    }
+}
+
+/// Detent counter shared between a GPIO interrupt decoding the lines and the main loop
+/// consuming the rotation, without a critical section. Meant for a `static`:
+///
+/// ```
+/// # use simple_encoder::{atomic::AtomicRotary, rotary::FullStep};
+/// static ROTARY: AtomicRotary = AtomicRotary::new();
+/// let (mut feed, take) = ROTARY.split::<FullStep>();
+/// for (a_low, b_low) in [(false, true), (true, true), (true, false), (false, false)] {
+///     feed.feed(a_low, b_low); // in the interrupt
+/// }
+/// assert_eq!(take.take().angle(), 1);
+/// ```
+///
+/// The count saturates at the `i16` range when the main loop falls behind. The atomics come
+/// from `portable-atomic`. On cores without 16 bit compare and swap, like Cortex-M0 or MSP430,
+/// the module needs the `critical-section` feature, or the
+/// `portable_atomic_unsafe_assume_single_core` cfg on single core chips.
+pub struct AtomicRotary {
+    count: Count,
+}
+
+impl AtomicRotary {
+    pub const fn new() -> Self {
+        Self { count: Count::new() }
+    }
+
+    /// Handles for the interrupt and the main loop. Splitting again makes another decoder
+    /// feeding the same count.
+    pub fn split<M: Steps>(&self) -> (RotaryFeed<'_, M>, RotaryTake<'_>) {
+        self.split_with(QuadratureDecoder::new())
+    }
+
+    /// Like [`split`](Self::split) with a configured decoder.
+    pub fn split_with<M: Steps>(
+        &self,
+        decoder: QuadratureDecoder<M>,
+    ) -> (RotaryFeed<'_, M>, RotaryTake<'_>) {
+        (RotaryFeed { decoder, count: &self.count }, RotaryTake { count: &self.count })
+    }
+
+    /// Net rotation since the previous call.
+    pub fn take(&self) -> Rotation {
+        Rotation::new(self.count.take() as i32)
+    }
+}
+
+impl Default for AtomicRotary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interrupt side of an [`AtomicRotary`], owning the decoder.
+pub struct RotaryFeed<'a, M = FullStep> {
+    decoder: QuadratureDecoder<M>,
+    count: &'a Count,
+}
+
+impl<M: Steps> RotaryFeed<'_, M> {
+    pub fn decoder(&mut self) -> &mut QuadratureDecoder<M> {
+        &mut self.decoder
+    }
+
+    /// Decodes one sample, `true` meaning the line is low, and adds a completed detent
+    /// to the count. Lock free: the compare and swap is retried only when another writer
+    /// changed the count in between, e.g. a second feed in a higher priority interrupt.
+    pub fn feed(&mut self, a_low: bool, b_low: bool) {
+        let steps = self.decoder.step(a_low, b_low).angle();
+        if steps != 0 {
+            self.count.add(steps.clamp(i16::MIN as i32, i16::MAX as i32) as i16);
+        }
+    }
+}
+
+/// Main loop side of an [`AtomicRotary`].
+#[derive(Copy, Clone)]
+pub struct RotaryTake<'a> {
+    count: &'a Count,
+}
+
+impl RotaryTake<'_> {
+    /// Net rotation since the previous call.
+    pub fn take(&self) -> Rotation {
+        Rotation::new(self.count.take() as i32)
+    }
+}
@@ -1,125 +1,1406 @@
-use crate::{Clock, Instant};
-use embedded_hal::digital::v2::InputPin;
-use fugit::MillisDurationU32;
+use crate::debounce::Debounced;
+use crate::time::{saturate, StartupGrace};
+use crate::input::PinKind;
+use crate::{ButtonInput, Clock, Instant, Stamped};
+use core::fmt;
+#[cfg(feature = "switch-hal")]
+use crate::input::SwitchInput;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use fugit::{MillisDurationU32, MillisDurationU64};
 
-#[derive(Copy, Clone, Debug)]
+pub use crate::debounce::Edge;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum ButtonAction {
     None,
     Press,
     Held,
     Click,
+    /// Release of a press suppressed by `handle_press`.
+    Release,
 }
 
-#[derive(Copy, Clone, Debug)]
-pub enum TimeButtonAction {
+impl ButtonAction {
+    pub fn is_none(&self) -> bool {
+        matches!(self, ButtonAction::None)
+    }
+
+    pub fn is_press(&self) -> bool {
+        matches!(self, ButtonAction::Press)
+    }
+
+    pub fn is_held(&self) -> bool {
+        matches!(self, ButtonAction::Held)
+    }
+
+    pub fn is_click(&self) -> bool {
+        matches!(self, ButtonAction::Click)
+    }
+}
+
+/// Action of a [`TimeButton`]. Durations are [`MillisDurationU32`], saturating at about 49.7
+/// days of a `u64` instant, unless taken from [`TimeButton::update_wide`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum TimeButtonAction<D = MillisDurationU32> {
     None,
     Press,
-    Held(MillisDurationU32),
-    Click(MillisDurationU32),
+    Held(D),
+    Click(D),
+    /// Release not reported as a click (suppressed, long or first of a possible double click).
+    /// Carries the total hold duration.
+    Release(D),
+    /// Second click within the double click window. Carries the duration of the second press.
+    DoubleClick(D),
+    /// Reported once when the press lasts longer than the long press threshold.
+    /// The following release is reported as `Release`, not `Click`.
+    LongPress(D),
+    /// Auto-repeat while held. Carries the number of repeat steps due since the previous report,
+    /// which is more than one when updates are late.
+    Repeat(u16),
+    /// Reported when the multi click window expires after the last of `count` clicks.
+    MultiClick { count: u8, last_duration: D },
+    /// `Click` shorter than the click threshold, reported instead of `Click` when it is set.
+    ShortClick(D),
+    /// `Click` not shorter than the click threshold, reported instead of `Click` when it is set.
+    LongClick(D),
+    /// Reported once when the press crosses the hold milestone with this index.
+    HoldMilestone(u8, D),
+    /// Reported once when the press lasts longer than the stuck timeout. Until the release,
+    /// which is reported as `Release`, the press reports nothing else.
+    Stuck(D),
+}
+
+/// [`TimeButtonAction`] with unsaturated durations.
+pub type WideTimeButtonAction = TimeButtonAction<MillisDurationU64>;
+
+impl<D: Copy> TimeButtonAction<D> {
+    pub fn is_none(&self) -> bool {
+        matches!(self, TimeButtonAction::None)
+    }
+
+    pub fn is_press(&self) -> bool {
+        matches!(self, TimeButtonAction::Press)
+    }
+
+    /// `true` for `Click`, `ShortClick` and `LongClick`.
+    pub fn is_click(&self) -> bool {
+        self.click_duration().is_some()
+    }
+
+    pub fn held_duration(&self) -> Option<D> {
+        match *self {
+            TimeButtonAction::Held(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Press duration of `Click`, `ShortClick` or `LongClick`.
+    pub fn click_duration(&self) -> Option<D> {
+        match *self {
+            TimeButtonAction::Click(t)
+            | TimeButtonAction::ShortClick(t)
+            | TimeButtonAction::LongClick(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl WideTimeButtonAction {
+    /// Saturates the durations at `u32::MAX` ms.
+    pub fn narrow(self) -> TimeButtonAction {
+        use TimeButtonAction::*;
+        match self {
+            None => None,
+            Press => Press,
+            Held(d) => Held(saturate(d)),
+            Click(d) => Click(saturate(d)),
+            Release(d) => Release(saturate(d)),
+            DoubleClick(d) => DoubleClick(saturate(d)),
+            LongPress(d) => LongPress(saturate(d)),
+            Repeat(n) => Repeat(n),
+            MultiClick { count, last_duration } => MultiClick { count, last_duration: saturate(last_duration) },
+            ShortClick(d) => ShortClick(saturate(d)),
+            LongClick(d) => LongClick(saturate(d)),
+            HoldMilestone(i, d) => HoldMilestone(i, saturate(d)),
+            Stuck(d) => Stuck(saturate(d)),
+        }
+    }
+}
+
+/// Maximal count of hold milestones of a [`TimeButton`].
+pub const MAX_HOLD_MILESTONES: usize = 4;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ToggleAction {
+    None,
+    On,
+    Off,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ChordAction {
+    None,
+    /// Combination with this index became fully pressed.
+    Chord(usize),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Polarity {
+    ActiveHigh,
+    ActiveLow,
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Error<K> {
     KPin(K),
 }
 
+impl<K> Error<K> {
+    pub fn into_kind(self) -> PinKind {
+        PinKind::Key
+    }
+}
+
+impl<K> fmt::Display for Error<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key pin error")
+    }
+}
+
+impl<K: fmt::Debug> core::error::Error for Error<K> {}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MatrixError<R, C> {
+    RowPin(R),
+    ColPin(C),
+}
+
+type MatrixPinsError<R, C> = MatrixError<<R as OutputPin>::Error, <C as InputPin>::Error>;
+
 #[inline]
-fn update_state(state: &mut u8, pressed: bool) -> u8 {
-    let s = match pressed {
-        true => (*state >> 1) | 0b10,
-        false => *state >> 1,
-    };
-    *state = s;
-    s
+fn state_action(s: u8) -> ButtonAction {
+    match s {
+        0b00 => ButtonAction::None,
+        0b01 => ButtonAction::Click,
+        0b10 => ButtonAction::Press,
+        0b11 => ButtonAction::Held,
+        _ => unreachable!(),
+    }
 }
 
-pub struct Button<K, const INVERTED: bool = false>
+/// Debounced level edges of a key, without click interpretation.
+pub struct EdgeButton<K, const INVERTED: bool = false>
 where
-    K: InputPin,
+    K: ButtonInput,
 {
     k_pin: K,
-    state: u8,
+    active_low: bool,
+    level: Debounced,
+}
+
+impl<K, const INVERTED: bool> EdgeButton<K, INVERTED>
+where
+    K: ButtonInput,
+{
+    pub fn new(k_pin: K) -> Self {
+        Self {
+            k_pin,
+            active_low: INVERTED,
+            level: Debounced::new(),
+        }
+    }
+
+    /// Creates a button with polarity chosen at runtime, overriding `INVERTED`.
+    pub fn with_polarity(k_pin: K, polarity: Polarity) -> Self {
+        let mut button = Self::new(k_pin);
+        button.active_low = polarity == Polarity::ActiveLow;
+        button
+    }
+
+    /// Creates a button seeded from the current pin level. A button already pressed at
+    /// creation reports no `Pressed` for that press.
+    pub fn new_from_pin(k_pin: K) -> Result<Self, Error<K::Error>> {
+        let mut button = Self::new(k_pin);
+        button.reset_from_pin()?;
+        Ok(button)
+    }
+
+    /// Pin access for reads outside the button. Reconfiguring the pin invalidates the
+    /// sampled history, call [`reset_from_pin`](Self::reset_from_pin) afterwards.
+    pub fn k_pin(&mut self) -> &mut K {
+        &mut self.k_pin
+    }
+
+    /// Gives the pin back. The sampled history and settings are discarded.
+    pub fn into_pin(self) -> K {
+        self.k_pin
+    }
+
+    /// Forgets the sampled history, as if the button was released.
+    pub fn reset(&mut self) {
+        self.level.reset(false);
+    }
+
+    /// Resets the history to the current pin level, so a held button reports no `Pressed`.
+    pub fn reset_from_pin(&mut self) -> Result<(), Error<K::Error>> {
+        let pressed = self.read_pin()?;
+        self.reset_to(pressed);
+        Ok(())
+    }
+
+    pub(crate) fn reset_to(&mut self, pressed: bool) {
+        self.level.reset(pressed);
+    }
+
+    pub fn polarity(&self) -> Polarity {
+        match self.active_low {
+            true => Polarity::ActiveLow,
+            false => Polarity::ActiveHigh,
+        }
+    }
+
+    /// Changes polarity in place. The current level is reinterpreted without reporting an edge.
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        if polarity != self.polarity() {
+            self.active_low = !self.active_low;
+            self.level.invert();
+        }
+    }
+
+    /// Sets how many consecutive identical samples are needed to accept a level change.
+    /// Default is 1, i.e. every sample is accepted immediately. Zero is treated as 1.
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.level.set_samples(samples);
+    }
+
+    /// Whether the button was down at the last `update`. Does not read the pin.
+    pub fn is_down(&self) -> bool {
+        self.level.history() & 0b10 != 0
+    }
+
+    /// Debounced level seen by the last `update`, `true` meaning pressed.
+    pub fn last_level(&self) -> bool {
+        self.level.level()
+    }
+
+    pub fn update(&mut self) -> Result<Edge, Error<K::Error>> {
+        let pressed = self.read_pin()?;
+        Ok(Edge::from_state(self.sample(pressed)))
+    }
+
+    #[inline]
+    pub(crate) fn read_pin(&mut self) -> Result<bool, Error<K::Error>> {
+        Ok(self.k_pin.is_high().map_err(Error::KPin)? ^ self.active_low)
+    }
+
+    // Returns the two last debounced levels, current one in the high bit
+    #[inline]
+    pub(crate) fn sample(&mut self, pressed: bool) -> u8 {
+        self.level.sample(pressed)
+    }
+
+    #[inline]
+    pub(crate) fn history(&self) -> u8 {
+        self.level.history()
+    }
+}
+
+pub struct Button<K, const INVERTED: bool = false>
+where
+    K: ButtonInput,
+{
+    edge: EdgeButton<K, INVERTED>,
     handle_press: bool,
+    hold_delay: u16,
+    held_samples: u16,
 }
 
 impl<K, const INVERTED: bool> Button<K, INVERTED>
 where
-    K: InputPin,
+    K: ButtonInput,
 {
     pub fn new(k_pin: K) -> Self {
         Self {
-            k_pin,
-            state: 0u8,
+            edge: EdgeButton::new(k_pin),
             handle_press: false,
+            hold_delay: 0,
+            held_samples: 0,
+        }
+    }
+
+    /// Creates a button with polarity chosen at runtime, overriding `INVERTED`.
+    pub fn with_polarity(k_pin: K, polarity: Polarity) -> Self {
+        Self {
+            edge: EdgeButton::with_polarity(k_pin, polarity),
+            handle_press: false,
+            hold_delay: 0,
+            held_samples: 0,
+        }
+    }
+
+    pub fn polarity(&self) -> Polarity {
+        self.edge.polarity()
+    }
+
+    /// Changes polarity in place. The current level is reinterpreted without reporting
+    /// an edge, a press in progress is dropped silently.
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        if polarity != self.polarity() {
+            self.edge.set_polarity(polarity);
+            self.handle_press = false;
+        }
+    }
+
+    /// Creates a button seeded from the current pin level. A button already pressed at
+    /// creation reports no `Press`, `Held` or `Click` for that press; its release is `Release`.
+    pub fn new_from_pin(k_pin: K) -> Result<Self, Error<K::Error>> {
+        let edge = EdgeButton::new_from_pin(k_pin)?;
+        Ok(Self {
+            handle_press: edge.is_down(),
+            hold_delay: 0,
+            held_samples: 0,
+            edge,
+        })
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.edge.set_debounce_samples(samples)
+    }
+
+    /// Reports `None` instead of `Held` for the first `samples` held updates after `Press`.
+    pub fn set_hold_delay(&mut self, samples: u16) {
+        self.hold_delay = samples;
+    }
+
+    pub fn k_pin(&mut self) -> &mut K {
+        self.edge.k_pin()
+    }
+
+    pub fn into_pin(self) -> K {
+        self.edge.into_pin()
+    }
+
+    /// Forgets the sampled history and suppression. The first `update` after it reports
+    /// nothing unless it sees a fresh press.
+    pub fn reset(&mut self) {
+        self.edge.reset();
+        self.handle_press = false;
+    }
+
+    /// Like [`reset`](Self::reset), but a button held at this moment reports nothing
+    /// until its release, which is `Release`.
+    pub fn reset_from_pin(&mut self) -> Result<(), Error<K::Error>> {
+        let pressed = self.read_pin()?;
+        self.reset_to(pressed);
+        Ok(())
+    }
+
+    pub(crate) fn reset_to(&mut self, pressed: bool) {
+        self.edge.reset_to(pressed);
+        self.handle_press = pressed;
+    }
+
+    /// Suppresses the `Held`/`Click` of the press just reported. Returns whether
+    /// a press was pending and is now suppressed.
+    pub fn handle_press(&mut self) -> bool {
+        if matches!(self.edge.history(), 0b10) && !self.handle_press {
+            self.handle_press = true;
+            true
+        } else {
+            false
         }
     }
 
-    pub fn handle_press(&mut self) {
-        if matches!(self.state, 0b10) {
+    pub fn is_down(&self) -> bool {
+        self.edge.is_down()
+    }
+
+    pub fn last_level(&self) -> bool {
+        self.edge.last_level()
+    }
+
+    pub(crate) fn is_suppressed(&self) -> bool {
+        self.handle_press
+    }
+
+    // Suppresses the current press at any point, not only right after `Press`
+    pub(crate) fn suppress(&mut self) -> bool {
+        if self.edge.is_down() && !self.handle_press {
             self.handle_press = true;
+            true
+        } else {
+            false
         }
     }
 
     pub fn update(&mut self) -> Result<ButtonAction, Error<K::Error>> {
-        let pressed = self.k_pin.is_high().map_err(Error::KPin)? ^ INVERTED;
-        let s = update_state(&mut self.state, pressed);
+        let pressed = self.read_pin()?;
+        Ok(self.update_with_level(pressed))
+    }
+
+    #[inline]
+    pub(crate) fn read_pin(&mut self) -> Result<bool, Error<K::Error>> {
+        self.edge.read_pin()
+    }
+
+    /// Updates from a level sampled elsewhere, e.g. a shift register read for the whole panel.
+    pub fn update_with_level(&mut self, pressed: bool) -> ButtonAction {
+        let s = self.edge.sample(pressed);
         let r = match s {
-            0b01 if self.handle_press => {
-                self.handle_press = false;
+            0b01 if self.handle_press => ButtonAction::Release,
+            0b11 if self.handle_press => ButtonAction::None,
+            0b11 if self.held_samples < self.hold_delay => {
+                self.held_samples += 1;
                 ButtonAction::None
             }
-            0b11 if self.handle_press => ButtonAction::None,
-            0b00 => ButtonAction::None,
-            0b01 => ButtonAction::Click,
-            0b10 => ButtonAction::Press,
-            0b11 => ButtonAction::Held,
-            _ => unreachable!(),
+            s => state_action(s),
+        };
+        if s == 0b10 {
+            self.held_samples = 0;
+        }
+        if s & 0b10 == 0 {
+            // Suppression belongs to a single press and always ends with its release
+            self.handle_press = false;
+        }
+        r
+    }
+}
+
+#[cfg(feature = "switch-hal")]
+impl<S, const INVERTED: bool> Button<SwitchInput<S>, INVERTED>
+where
+    S: switch_hal::InputSwitch,
+{
+    /// Creates a button from a `switch-hal` input switch, pressed while the switch is active.
+    ///
+    /// ```
+    /// # use simple_encoder::button::{Button, ButtonAction};
+    /// use switch_hal::mock::{Pin, State};
+    /// use switch_hal::IntoSwitch;
+    /// // Pulled-up key, low while pressed
+    /// let key = Pin::with_state(State::Low).into_active_low_switch();
+    /// let mut button = Button::<_>::from_switch(key);
+    /// assert_eq!(button.update(), Ok(ButtonAction::Press));
+    /// ```
+    pub fn from_switch(switch: S) -> Self {
+        Self::with_polarity(SwitchInput(switch), Polarity::ActiveHigh)
+    }
+}
+
+/// Push-on push-off button: every click flips the latched state.
+pub struct ToggleButton<K, const INVERTED: bool = false>
+where
+    K: ButtonInput,
+{
+    button: Button<K, INVERTED>,
+    on: bool,
+}
+
+impl<K, const INVERTED: bool> ToggleButton<K, INVERTED>
+where
+    K: ButtonInput,
+{
+    pub fn new(k_pin: K) -> Self {
+        Self {
+            button: Button::new(k_pin),
+            on: false,
+        }
+    }
+
+    pub fn state(&self) -> bool {
+        self.on
+    }
+
+    pub fn set_state(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    /// Keeps the current press from flipping the state.
+    pub fn handle_press(&mut self) -> bool {
+        self.button.handle_press()
+    }
+
+    pub fn update(&mut self) -> Result<ToggleAction, Error<K::Error>> {
+        let act = match self.button.update()? {
+            ButtonAction::Click => {
+                self.on = !self.on;
+                match self.on {
+                    true => ToggleAction::On,
+                    false => ToggleAction::Off,
+                }
+            }
+            _ => ToggleAction::None,
+        };
+        Ok(act)
+    }
+}
+
+/// Key matrix scanner. A row is selected by driving it low, a key is pressed when
+/// its column reads low (pull-ups on columns). Each key runs the same state machine
+/// as [`Button`], without `handle_press`.
+pub struct ButtonMatrix<R, C, const ROWS: usize, const COLS: usize>
+where
+    R: OutputPin,
+    C: InputPin,
+{
+    rows: [R; ROWS],
+    cols: [C; COLS],
+    levels: [[Debounced; COLS]; ROWS],
+    actions: [[ButtonAction; COLS]; ROWS],
+    scan_all: bool,
+    next_row: usize,
+    ghosting: bool,
+}
+
+impl<R, C, const ROWS: usize, const COLS: usize> ButtonMatrix<R, C, ROWS, COLS>
+where
+    R: OutputPin,
+    C: InputPin,
+{
+    /// Creates a matrix scanning every row on each `update`.
+    pub fn new(rows: [R; ROWS], cols: [C; COLS]) -> Self {
+        Self {
+            rows,
+            cols,
+            levels: [[Debounced::new(); COLS]; ROWS],
+            actions: [[ButtonAction::None; COLS]; ROWS],
+            scan_all: true,
+            next_row: 0,
+            ghosting: false,
+        }
+    }
+
+    /// Scans every row per `update` when `true`, a single row in turn otherwise.
+    pub fn set_scan_all(&mut self, scan_all: bool) {
+        self.scan_all = scan_all;
+    }
+
+    /// Whether the pressed keys form a rectangle, so a diode-less matrix can
+    /// report a phantom key. Updated on every scan.
+    pub fn is_ghosting(&self) -> bool {
+        self.ghosting
+    }
+
+    pub fn is_down(&self, row: usize, col: usize) -> bool {
+        self.levels[row][col].history() & 0b10 != 0
+    }
+
+    /// Scans and returns the `(row, col, action)` of every key with a non `None` action.
+    pub fn update(
+        &mut self,
+    ) -> Result<impl Iterator<Item = (usize, usize, ButtonAction)> + '_, MatrixPinsError<R, C>> {
+        self.actions = [[ButtonAction::None; COLS]; ROWS];
+        if self.scan_all {
+            for row in 0..ROWS {
+                self.scan_row(row)?;
+            }
+        } else if ROWS != 0 {
+            self.scan_row(self.next_row)?;
+            self.next_row = (self.next_row + 1) % ROWS;
+        }
+        self.ghosting = self.detect_ghosting();
+        let events = self.actions.iter().enumerate().flat_map(|(row, actions)| {
+            actions
+                .iter()
+                .enumerate()
+                .filter(|(_, act)| !matches!(act, ButtonAction::None))
+                .map(move |(col, act)| (row, col, *act))
+        });
+        Ok(events)
+    }
+
+    fn scan_row(&mut self, row: usize) -> Result<(), MatrixPinsError<R, C>> {
+        self.rows[row].set_low().map_err(MatrixError::RowPin)?;
+        let mut read = || -> Result<(), MatrixPinsError<R, C>> {
+            for col in 0..COLS {
+                let pressed = self.cols[col].is_low().map_err(MatrixError::ColPin)?;
+                let s = self.levels[row][col].sample(pressed);
+                self.actions[row][col] = state_action(s);
+            }
+            Ok(())
         };
-        Ok(r)
+        let r = read();
+        self.rows[row].set_high().map_err(MatrixError::RowPin)?;
+        r
+    }
+
+    fn detect_ghosting(&self) -> bool {
+        for r1 in 0..ROWS {
+            for r2 in r1 + 1..ROWS {
+                let shared = (0..COLS)
+                    .filter(|&c| self.levels[r1][c].history() & self.levels[r2][c].history() & 0b10 != 0)
+                    .count();
+                if shared >= 2 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Buttons sharing one ADC input through a resistor ladder. The caller supplies samples,
+/// button `i` is pressed when the sample falls into `windows[i]` (inclusive). Samples matching
+/// no window mean no button, overlapping windows resolve to the first match.
+pub struct LadderButtons<const N: usize> {
+    windows: [(u16, u16); N],
+    levels: [Debounced; N],
+}
+
+impl<const N: usize> LadderButtons<N> {
+    pub fn new(windows: [(u16, u16); N]) -> Self {
+        Self {
+            windows,
+            levels: [Debounced::new(); N],
+        }
+    }
+
+    /// See [`EdgeButton::set_debounce_samples`].
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        for level in self.levels.iter_mut() {
+            level.set_samples(samples);
+        }
+    }
+
+    pub fn is_down(&self, button: usize) -> bool {
+        self.levels[button].history() & 0b10 != 0
+    }
+
+    pub fn update_with_sample(&mut self, sample: u16) -> [ButtonAction; N] {
+        let matched = self
+            .windows
+            .iter()
+            .position(|&(low, high)| (low..=high).contains(&sample));
+        let mut actions = [ButtonAction::None; N];
+        for (i, act) in actions.iter_mut().enumerate() {
+            *act = state_action(self.levels[i].sample(matched == Some(i)));
+        }
+        actions
+    }
+}
+
+/// Detects combinations of up to 32 buttons pressed together. Combinations are bitmasks over
+/// button indices. Once a combination fires, the clicks of its buttons are reported as
+/// `Release`. An abandoned partial combination leaves the clicks untouched.
+pub struct Chord<const M: usize> {
+    combos: [u32; M],
+    down: u32,
+    fired: u32,
+}
+
+impl<const M: usize> Chord<M> {
+    pub fn new(combos: [u32; M]) -> Self {
+        Self {
+            combos,
+            down: 0,
+            fired: 0,
+        }
+    }
+
+    /// Bitmask of buttons currently down.
+    pub fn down(&self) -> u32 {
+        self.down
+    }
+
+    /// Consumes this update's actions of all buttons, index `i` for bit `i`,
+    /// and rewrites the clicks it suppresses.
+    pub fn update(&mut self, actions: &mut [ButtonAction]) -> ChordAction {
+        for (i, act) in actions.iter_mut().enumerate().take(32) {
+            let bit = 1u32 << i;
+            match act {
+                ButtonAction::Press | ButtonAction::Held => self.down |= bit,
+                ButtonAction::Click if self.fired & bit != 0 => {
+                    *act = ButtonAction::Release;
+                    self.down &= !bit;
+                    self.fired &= !bit;
+                }
+                ButtonAction::Click | ButtonAction::Release => {
+                    self.down &= !bit;
+                    self.fired &= !bit;
+                }
+                ButtonAction::None => {}
+            }
+        }
+        for (i, &combo) in self.combos.iter().enumerate() {
+            if combo != 0 && self.down & combo == combo && self.fired & combo != combo {
+                self.fired |= combo;
+                return ChordAction::Chord(i);
+            }
+        }
+        ChordAction::None
     }
 }
 
 pub struct TimeButton<K, T, const INVERTED: bool = false>
 where
-    K: InputPin,
+    K: ButtonInput,
     T: Instant,
 {
     button: Button<K, INVERTED>,
-    press_at: T, // none when press handled
+    press_at: Option<T>, // none when press handled
+    double_click_window: MillisDurationU32,
+    pending_click: Option<(T, MillisDurationU64)>, // release instant and duration of the first click
+    second_click: bool,
+    deferred_press: bool,
+    long_press: MillisDurationU32,
+    long_pressed: bool,
+    debounce: MillisDurationU32,
+    stable_level: bool,
+    change_at: Option<T>,
+    repeat_initial: MillisDurationU32,
+    repeat_rate: MillisDurationU32,
+    repeats: u64,
+    held_interval: MillisDurationU32,
+    held_reported: Option<MillisDurationU64>,
+    multi_click_window: MillisDurationU32,
+    multi_click_max: MillisDurationU32,
+    click_count: u8,
+    last_click: Option<(T, MillisDurationU64)>,
+    min_press: MillisDurationU32,
+    press_pending: bool,
+    press_dropped: bool,
+    click_threshold: MillisDurationU32,
+    prev_click_at: Option<T>,
+    last_press_at: Option<T>,
+    milestones: [MillisDurationU32; MAX_HOLD_MILESTONES],
+    milestones_len: u8,
+    milestones_passed: u8,
+    grace: StartupGrace<T>,
+    hold_delay: MillisDurationU32,
+    stuck_timeout: MillisDurationU32,
+    down_at: Option<T>,
+    stuck: bool,
 }
 
 impl<K, T: Instant, const INVERTED: bool> TimeButton<K, T, INVERTED>
 where
-    K: InputPin,
+    K: ButtonInput,
 {
     pub fn new(k_pin: K) -> Self {
+        Self::with_double_click_window(k_pin, MillisDurationU32::from_ticks(0))
+    }
+
+    /// Creates a button reporting `DoubleClick` when a second press starts within `window`
+    /// after the first release.
+    ///
+    /// A single `Click` is delayed until the window expires, so every click is reported
+    /// `window` later than without double click detection. Zero window disables detection.
+    pub fn with_double_click_window(k_pin: K, window: MillisDurationU32) -> Self {
+        let mut button = Self::from_button(Button::new(k_pin));
+        button.double_click_window = window;
+        button
+    }
+
+    /// Creates a button seeded from the current pin level, see [`Button::new_from_pin`].
+    pub fn new_from_pin(k_pin: K) -> Result<Self, Error<K::Error>> {
+        Ok(Self::from_button(Button::new_from_pin(k_pin)?))
+    }
+
+    pub fn with_polarity(k_pin: K, polarity: Polarity) -> Self {
+        Self::from_button(Button::with_polarity(k_pin, polarity))
+    }
+
+    /// Creates a button reporting nothing during `grace` since the first `update`, while pull-ups
+    /// settle. Afterwards it behaves as if created from the settled level: a press held at
+    /// that moment reports nothing until released.
+    pub fn with_startup_grace(k_pin: K, grace: MillisDurationU32) -> Self {
+        let mut button = Self::new(k_pin);
+        button.grace = StartupGrace::new(grace);
+        button
+    }
+
+    fn from_button(button: Button<K, INVERTED>) -> Self {
         Self {
-            button: Button::new(k_pin),
-            press_at: T::zero(),
+            stable_level: button.last_level(),
+            button,
+            press_at: None,
+            double_click_window: MillisDurationU32::from_ticks(0),
+            pending_click: None,
+            second_click: false,
+            deferred_press: false,
+            long_press: MillisDurationU32::from_ticks(0),
+            long_pressed: false,
+            debounce: MillisDurationU32::from_ticks(0),
+            change_at: None,
+            repeat_initial: MillisDurationU32::from_ticks(0),
+            repeat_rate: MillisDurationU32::from_ticks(0),
+            repeats: 0,
+            held_interval: MillisDurationU32::from_ticks(0),
+            held_reported: None,
+            multi_click_window: MillisDurationU32::from_ticks(0),
+            multi_click_max: MillisDurationU32::from_ticks(0),
+            click_count: 0,
+            last_click: None,
+            min_press: MillisDurationU32::from_ticks(0),
+            press_pending: false,
+            press_dropped: false,
+            click_threshold: MillisDurationU32::from_ticks(0),
+            prev_click_at: None,
+            last_press_at: None,
+            milestones: [MillisDurationU32::from_ticks(0); MAX_HOLD_MILESTONES],
+            milestones_len: 0,
+            milestones_passed: 0,
+            grace: StartupGrace::new(MillisDurationU32::from_ticks(0)),
+            hold_delay: MillisDurationU32::from_ticks(0),
+            stuck_timeout: MillisDurationU32::from_ticks(0),
+            down_at: None,
+            stuck: false,
         }
     }
 
-    pub fn handle_press(&mut self) {
-        self.button.handle_press()
+    /// Sets ascending hold durations reported once per press as `HoldMilestone`.
+    /// Only the first [`MAX_HOLD_MILESTONES`] are used, empty slice disables them.
+    pub fn set_hold_milestones(&mut self, milestones: &[MillisDurationU32]) {
+        let len = milestones.len().min(MAX_HOLD_MILESTONES);
+        self.milestones[..len].copy_from_slice(&milestones[..len]);
+        self.milestones_len = len as u8;
+        self.milestones_passed = 0;
+    }
+
+    /// Splits `Click` into `ShortClick` and `LongClick` by `threshold`. Zero keeps `Click`.
+    pub fn set_click_threshold(&mut self, threshold: MillisDurationU32) {
+        self.click_threshold = threshold;
+    }
+
+    /// Ignores presses shorter than `min`. `Press` is reported once the press has lasted `min`
+    /// (durations are still counted from the real press), `Held` and `Click` only after it.
+    /// A press released before an update observes the threshold reports only `Click`.
+    /// `handle_press` during the deferred period drops the press silently. Zero disables it.
+    pub fn set_min_press(&mut self, min: MillisDurationU32) {
+        self.min_press = min;
+    }
+
+    /// Enables click counting: clicks separated by less than `window` are reported together
+    /// as one `MultiClick` after the window expires, each click release itself is reported
+    /// as `Release`. A press longer than `max_click` resets the count (zero means no limit).
+    /// Zero window disables counting. Takes precedence over double click detection.
+    pub fn set_multi_click(&mut self, window: MillisDurationU32, max_click: MillisDurationU32) {
+        self.multi_click_window = window;
+        self.multi_click_max = max_click;
+    }
+
+    /// Treats a press longer than `timeout` as a jammed switch, see [`TimeButtonAction::Stuck`].
+    /// Counted from the physical press, also for presses consumed by `handle_press`.
+    /// Zero disables it.
+    pub fn set_stuck_timeout(&mut self, timeout: MillisDurationU32) {
+        self.stuck_timeout = timeout;
+    }
+
+    /// `true` from `Stuck` until the release of the stuck press.
+    pub fn is_stuck(&self) -> bool {
+        self.stuck
+    }
+
+    /// Reports `None` instead of `Held` until the press has lasted `delay`.
+    /// Long press, repeat and milestones keep counting from the real press. Zero disables it.
+    pub fn set_hold_delay(&mut self, delay: MillisDurationU32) {
+        self.hold_delay = delay;
+    }
+
+    /// Limits `Held` to one report per `interval`; updates in between return `None`.
+    /// The first `Held` after `Press` is reported immediately. Zero disables the limit.
+    pub fn set_held_interval(&mut self, interval: MillisDurationU32) {
+        self.held_interval = interval;
+    }
+
+    /// Enables auto-repeat: `Repeat` is reported `initial` after the press and then every `rate`.
+    /// The release after a repeat is reported as `Release`, not `Click`. Zero rate disables it.
+    pub fn set_repeat(&mut self, initial: MillisDurationU32, rate: MillisDurationU32) {
+        self.repeat_initial = initial;
+        self.repeat_rate = rate;
+    }
+
+    /// Sets how long a new level must persist before it is accepted. Shorter presses
+    /// are ignored and short releases are merged into the surrounding press. Zero disables it.
+    pub fn set_debounce(&mut self, window: MillisDurationU32) {
+        self.debounce = window;
+    }
+
+    pub fn set_double_click_window(&mut self, window: MillisDurationU32) {
+        self.double_click_window = window;
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.button.set_debounce_samples(samples)
+    }
+
+    /// Sets the hold duration after which `LongPress` is reported. Zero disables it.
+    pub fn set_long_press(&mut self, threshold: MillisDurationU32) {
+        self.long_press = threshold;
+    }
+
+    pub fn handle_press(&mut self) -> bool {
+        let handled = if self.min_press.ticks() == 0 {
+            self.button.handle_press()
+        } else if self.press_pending {
+            self.press_pending = false;
+            self.press_dropped = true;
+            true
+        } else if self.press_dropped {
+            false
+        } else {
+            // `Press` is reported late, so the inner button is already past its press state
+            self.button.suppress()
+        };
+        if handled {
+            self.press_at = None;
+            self.second_click = false;
+            self.deferred_press = false;
+            self.click_count = 0;
+            self.last_click = None;
+        }
+        handled
+    }
+
+    pub fn polarity(&self) -> Polarity {
+        self.button.polarity()
+    }
+
+    /// Changes polarity in place without reporting an edge, see [`Button::set_polarity`].
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        if polarity != self.button.polarity() {
+            self.button.set_polarity(polarity);
+            self.stable_level = !self.stable_level;
+            self.change_at = None;
+            self.press_at = None;
+            self.press_pending = false;
+        }
+    }
+
+    pub fn k_pin(&mut self) -> &mut K {
+        self.button.k_pin()
+    }
+
+    pub fn into_pin(self) -> K {
+        self.button.into_pin()
+    }
+
+    /// Forgets the sampled history and every press or click in progress.
+    /// The first `update` after it reports nothing unless it sees a fresh press.
+    pub fn reset(&mut self) {
+        self.button.reset();
+        self.clear_progress();
+    }
+
+    /// Like [`reset`](Self::reset), but a button held at this moment reports nothing
+    /// until its release.
+    pub fn reset_from_pin(&mut self) -> Result<(), Error<K::Error>> {
+        self.button.reset_from_pin()?;
+        self.clear_progress();
+        Ok(())
+    }
+
+    fn clear_progress(&mut self) {
+        self.press_at = None;
+        self.pending_click = None;
+        self.second_click = false;
+        self.deferred_press = false;
+        self.long_pressed = false;
+        self.stable_level = self.button.last_level();
+        self.change_at = None;
+        self.repeats = 0;
+        self.held_reported = None;
+        self.click_count = 0;
+        self.last_click = None;
+        self.press_pending = false;
+        self.press_dropped = false;
+        self.down_at = None;
+        self.stuck = false;
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.button.is_down()
+    }
+
+    pub fn last_level(&self) -> bool {
+        self.button.last_level()
+    }
+
+    /// Time since the current press started, `None` when the button is up
+    /// or the press was consumed by `handle_press`.
+    pub fn press_duration(&self, now: T) -> Option<MillisDurationU32> {
+        if !self.button.is_down() || self.button.is_suppressed() || self.press_dropped {
+            return None;
+        }
+        self.press_at.map(|at| now.duration_since(at))
+    }
+
+    /// `press_duration` without saturation at `u32::MAX` ms (about 49.7 days), see
+    /// [`update_wide`](Self::update_wide) for the durations carried by actions.
+    pub fn wide_press_duration(&self, now: T) -> Option<MillisDurationU64> {
+        if !self.button.is_down() || self.button.is_suppressed() || self.press_dropped {
+            return None;
+        }
+        self.press_at.map(|at| now.wide_duration_since(at))
+    }
+
+    /// Time since the release of the last click, `None` before the first one.
+    /// Releases of presses suppressed by `handle_press` are not clicks.
+    pub fn time_since_last_click(&self, now: T) -> Option<MillisDurationU32> {
+        self.prev_click_at.map(|at| now.duration_since(at))
+    }
+
+    /// Time since the start of the last press, `None` before the first one.
+    pub fn time_since_last_press(&self, now: T) -> Option<MillisDurationU32> {
+        self.last_press_at.map(|at| now.duration_since(at))
     }
 
     pub fn update(&mut self, now: T) -> Result<TimeButtonAction, Error<K::Error>> {
-        let act = self.button.update()?;
-        let act = match act {
+        let pressed = self.button.read_pin()?;
+        Ok(self.update_with_level(now, pressed))
+    }
+
+    /// Updates from a level sampled elsewhere, see [`Button::update_with_level`].
+    pub fn update_with_level(&mut self, now: T, pressed: bool) -> TimeButtonAction {
+        self.update_wide_with_level(now, pressed).narrow()
+    }
+
+    /// `update` with durations past `u32::MAX` ms, for presses held longer than about 49.7
+    /// days on a `u64` instant. Thresholds and timeouts still compare as usual.
+    pub fn update_wide(&mut self, now: T) -> Result<WideTimeButtonAction, Error<K::Error>> {
+        let pressed = self.button.read_pin()?;
+        Ok(self.update_wide_with_level(now, pressed))
+    }
+
+    pub fn update_wide_with_level(&mut self, now: T, pressed: bool) -> WideTimeButtonAction {
+        match self.poll_level(now, pressed) {
+            TimeButtonAction::Held(t) if !self.report_held(t) => TimeButtonAction::None,
+            act => act,
+        }
+    }
+
+    pub fn update_stamped(&mut self, now: T) -> Result<Stamped<TimeButtonAction, T>, Error<K::Error>> {
+        Ok(Stamped { at: now, action: self.update(now)? })
+    }
+
+    // Update without `Held` rate limit, so the caller still sees every held poll
+    pub(crate) fn poll(&mut self, now: T) -> Result<TimeButtonAction, Error<K::Error>> {
+        let pressed = self.button.read_pin()?;
+        Ok(self.poll_level(now, pressed).narrow())
+    }
+
+    fn poll_level(&mut self, now: T, pressed: bool) -> WideTimeButtonAction {
+        let pressed = self.debounce(now, pressed);
+        if self.grace.is_active() {
+            self.button.update_with_level(pressed);
+            if self.grace.update(now) {
+                let settled = self.button.last_level();
+                self.button.reset_to(settled);
+                self.clear_progress();
+            }
+            return TimeButtonAction::None;
+        }
+        let act = match self.button.update_with_level(pressed) {
             ButtonAction::None => TimeButtonAction::None,
             ButtonAction::Press => {
-                self.press_at = now;
+                self.press_at = Some(now);
+                self.last_press_at = Some(now);
+                self.held_reported = None;
                 TimeButtonAction::Press
             }
-            ButtonAction::Held => TimeButtonAction::Held(now.duration_since(self.press_at)),
-            ButtonAction::Click => TimeButtonAction::Click(now.duration_since(self.press_at)),
+            ButtonAction::Held => TimeButtonAction::Held(self.held_for(now)),
+            ButtonAction::Click => TimeButtonAction::Click(self.take_held_for(now)),
+            ButtonAction::Release => TimeButtonAction::Release(self.take_release_for(now)),
         };
-        Ok(act)
+        let act = self.filter_short_press(act);
+        if let TimeButtonAction::Click(_) = act {
+            self.prev_click_at = Some(now);
+        }
+        let act = self.detect_long_press(act);
+        let act = self.detect_milestone(act);
+        let act = self.detect_repeat(act);
+        let act = self.detect_stuck(now, act);
+        let act = self.count_clicks(now, act);
+        let act = self.detect_double_click(now, act);
+        match self.classify_click(act) {
+            TimeButtonAction::Held(t) if t < self.hold_delay => TimeButtonAction::None,
+            act => act,
+        }
+    }
+
+    // Zero when the press start is unknown, e.g. its `Press` sample was missed
+    #[inline]
+    fn held_for(&self, now: T) -> MillisDurationU64 {
+        self.press_at.map_or(MillisDurationU64::from_ticks(0), |at| now.wide_duration_since(at))
+    }
+
+    #[inline]
+    fn take_held_for(&mut self, now: T) -> MillisDurationU64 {
+        let t = self.held_for(now);
+        self.press_at = None;
+        t
+    }
+
+    // A suppressed press has no `press_at`, its release is timed from the physical press
+    #[inline]
+    fn take_release_for(&mut self, now: T) -> MillisDurationU64 {
+        self.press_at = None;
+        self.down_at.map_or(MillisDurationU64::from_ticks(0), |at| now.wide_duration_since(at))
+    }
+
+    pub(crate) fn report_held(&mut self, t: MillisDurationU64) -> bool {
+        if self.held_interval.ticks() == 0 {
+            return true;
+        }
+        match self.held_reported {
+            Some(last) if t - last < self.held_interval => false,
+            _ => {
+                self.held_reported = Some(t);
+                true
+            }
+        }
+    }
+
+    fn debounce(&mut self, now: T, pressed: bool) -> bool {
+        if self.debounce.ticks() == 0 {
+            return pressed;
+        }
+        if pressed == self.stable_level {
+            self.change_at = None;
+        } else {
+            match self.change_at {
+                None => self.change_at = Some(now),
+                Some(at) if now.duration_since(at) >= self.debounce => {
+                    self.change_at = None;
+                    self.stable_level = pressed;
+                }
+                Some(_) => {}
+            }
+        }
+        self.stable_level
+    }
+
+    fn filter_short_press(&mut self, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        if self.min_press.ticks() == 0 {
+            return act;
+        }
+        match act {
+            TimeButtonAction::Press => {
+                self.press_pending = true;
+                self.press_dropped = false;
+                TimeButtonAction::None
+            }
+            TimeButtonAction::Held(_) | TimeButtonAction::Click(_) | TimeButtonAction::Release(_)
+                if self.press_dropped =>
+            {
+                TimeButtonAction::None
+            }
+            TimeButtonAction::Held(t) if self.press_pending => {
+                if t >= self.min_press {
+                    self.press_pending = false;
+                    TimeButtonAction::Press
+                } else {
+                    TimeButtonAction::None
+                }
+            }
+            TimeButtonAction::Click(t) | TimeButtonAction::Release(t) if self.press_pending => {
+                self.press_pending = false;
+                if t >= self.min_press {
+                    act
+                } else {
+                    TimeButtonAction::None
+                }
+            }
+            act => act,
+        }
+    }
+
+    fn detect_long_press(&mut self, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        match act {
+            TimeButtonAction::Press => {
+                self.long_pressed = false;
+                act
+            }
+            TimeButtonAction::Held(t) if self.long_press.ticks() != 0 && !self.long_pressed && t >= self.long_press => {
+                self.long_pressed = true;
+                TimeButtonAction::LongPress(t)
+            }
+            TimeButtonAction::Click(t) if self.long_pressed => {
+                self.long_pressed = false;
+                TimeButtonAction::Release(t)
+            }
+            act => act,
+        }
+    }
+
+    fn detect_milestone(&mut self, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        match act {
+            TimeButtonAction::Press => {
+                self.milestones_passed = 0;
+                act
+            }
+            TimeButtonAction::Held(t)
+                if self.milestones_passed < self.milestones_len
+                    && t >= self.milestones[self.milestones_passed as usize] =>
+            {
+                self.milestones_passed += 1;
+                TimeButtonAction::HoldMilestone(self.milestones_passed - 1, t)
+            }
+            act => act,
+        }
+    }
+
+    fn detect_repeat(&mut self, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        match act {
+            TimeButtonAction::Press => {
+                self.repeats = 0;
+                act
+            }
+            TimeButtonAction::Held(t) if self.repeat_rate.ticks() != 0 && t >= self.repeat_initial => {
+                let due = 1 + (t - self.repeat_initial).ticks() / self.repeat_rate.ticks() as u64;
+                if due > self.repeats {
+                    let n = due - self.repeats;
+                    self.repeats = due;
+                    TimeButtonAction::Repeat(n.min(u16::MAX as u64) as u16)
+                } else {
+                    act
+                }
+            }
+            TimeButtonAction::Click(t) if self.repeats != 0 => {
+                self.repeats = 0;
+                TimeButtonAction::Release(t)
+            }
+            act => act,
+        }
+    }
+
+    fn count_clicks(&mut self, now: T, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        if self.multi_click_window.ticks() == 0 {
+            return act;
+        }
+        let flush = |count: &mut u8, last_duration| {
+            let count = core::mem::take(count);
+            TimeButtonAction::MultiClick { count, last_duration }
+        };
+        match act {
+            TimeButtonAction::Press => match self.last_click {
+                Some((at, t)) if now.duration_since(at) > self.multi_click_window => {
+                    // Window expired between two updates: flush the clicks, report press later
+                    self.last_click = None;
+                    self.deferred_press = true;
+                    flush(&mut self.click_count, t)
+                }
+                _ => act,
+            },
+            TimeButtonAction::Held(_) if self.deferred_press => {
+                self.deferred_press = false;
+                TimeButtonAction::Press
+            }
+            TimeButtonAction::Click(t) if self.multi_click_max.ticks() != 0 && t > self.multi_click_max => {
+                self.click_count = 0;
+                self.last_click = None;
+                act
+            }
+            TimeButtonAction::Click(t) => {
+                self.deferred_press = false;
+                self.click_count = self.click_count.saturating_add(1);
+                self.last_click = Some((now, t));
+                TimeButtonAction::Release(t)
+            }
+            TimeButtonAction::None => match self.last_click {
+                Some((at, t)) if now.duration_since(at) > self.multi_click_window => {
+                    self.last_click = None;
+                    flush(&mut self.click_count, t)
+                }
+                _ => act,
+            },
+            act => act,
+        }
+    }
+
+    fn detect_stuck(&mut self, now: T, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        if !self.button.is_down() {
+            self.down_at = None;
+        } else if self.down_at.is_none() {
+            self.down_at = Some(now);
+        }
+        if self.stuck {
+            return match act {
+                TimeButtonAction::Click(t) | TimeButtonAction::Release(t) => {
+                    self.stuck = false;
+                    TimeButtonAction::Release(t)
+                }
+                _ => TimeButtonAction::None,
+            };
+        }
+        match (act, self.down_at) {
+            (TimeButtonAction::None | TimeButtonAction::Held(_), Some(at)) if self.stuck_timeout.ticks() != 0 => {
+                let t = now.wide_duration_since(at);
+                if t >= self.stuck_timeout {
+                    self.stuck = true;
+                    TimeButtonAction::Stuck(t)
+                } else {
+                    act
+                }
+            }
+            _ => act,
+        }
+    }
+
+    fn classify_click(&self, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        match act {
+            TimeButtonAction::Click(t) if self.click_threshold.ticks() != 0 => {
+                if t < self.click_threshold {
+                    TimeButtonAction::ShortClick(t)
+                } else {
+                    TimeButtonAction::LongClick(t)
+                }
+            }
+            act => act,
+        }
+    }
+
+    fn detect_double_click(&mut self, now: T, act: WideTimeButtonAction) -> WideTimeButtonAction {
+        if self.double_click_window.ticks() == 0 {
+            return act;
+        }
+        match act {
+            TimeButtonAction::Press => match self.pending_click.take() {
+                Some((at, _)) if now.duration_since(at) <= self.double_click_window => {
+                    self.second_click = true;
+                    act
+                }
+                Some((_, t)) => {
+                    // Window expired between two updates: flush the click, report press later
+                    self.deferred_press = true;
+                    TimeButtonAction::Click(t)
+                }
+                None => act,
+            },
+            TimeButtonAction::Held(_) if self.deferred_press => {
+                self.deferred_press = false;
+                TimeButtonAction::Press
+            }
+            TimeButtonAction::LongPress(_) => {
+                self.second_click = false;
+                act
+            }
+            TimeButtonAction::Click(t) if self.second_click => {
+                self.second_click = false;
+                TimeButtonAction::DoubleClick(t)
+            }
+            TimeButtonAction::Click(t) => {
+                self.deferred_press = false;
+                self.pending_click = Some((now, t));
+                TimeButtonAction::Release(t)
+            }
+            TimeButtonAction::None => match self.pending_click {
+                Some((at, t)) if now.duration_since(at) > self.double_click_window => {
+                    self.pending_click = None;
+                    TimeButtonAction::Click(t)
+                }
+                _ => TimeButtonAction::None,
+            },
+            act => act,
+        }
     }
 }
 
 pub struct ClockButton<K, C, const INVERTED: bool = false>
 where
-    K: InputPin,
+    K: ButtonInput,
     C: Clock,
 {
     button: TimeButton<K, C::Instant, INVERTED>,
@@ -128,7 +1409,7 @@ where
 
 impl<K, C, const INVERTED: bool> ClockButton<K, C, INVERTED>
 where
-    K: InputPin,
+    K: ButtonInput,
     C: Clock,
 {
     pub fn new(k_pin: K, clock: C) -> Self {
@@ -138,11 +1419,158 @@ where
         }
     }
 
-    pub fn handle_press(&mut self) {
+    pub fn new_from_pin(k_pin: K, clock: C) -> Result<Self, Error<K::Error>> {
+        Ok(Self {
+            button: TimeButton::new_from_pin(k_pin)?,
+            clock,
+        })
+    }
+
+    pub fn with_polarity(k_pin: K, clock: C, polarity: Polarity) -> Self {
+        Self {
+            button: TimeButton::with_polarity(k_pin, polarity),
+            clock,
+        }
+    }
+
+    pub fn from_parts(button: TimeButton<K, C::Instant, INVERTED>, clock: C) -> Self {
+        Self { button, clock }
+    }
+
+    pub fn into_parts(self) -> (TimeButton<K, C::Instant, INVERTED>, C) {
+        (self.button, self.clock)
+    }
+
+    pub fn button(&mut self) -> &mut TimeButton<K, C::Instant, INVERTED> {
+        &mut self.button
+    }
+
+    /// Same as [`button`](Self::button), named alike on every clock wrapper.
+    pub fn inner(&mut self) -> &mut TimeButton<K, C::Instant, INVERTED> {
+        &mut self.button
+    }
+
+    pub fn clock(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    pub fn with_double_click_window(k_pin: K, clock: C, window: MillisDurationU32) -> Self {
+        Self {
+            button: TimeButton::with_double_click_window(k_pin, window),
+            clock,
+        }
+    }
+
+    pub fn set_double_click_window(&mut self, window: MillisDurationU32) {
+        self.button.set_double_click_window(window)
+    }
+
+    pub fn set_long_press(&mut self, threshold: MillisDurationU32) {
+        self.button.set_long_press(threshold)
+    }
+
+    pub fn set_repeat(&mut self, initial: MillisDurationU32, rate: MillisDurationU32) {
+        self.button.set_repeat(initial, rate)
+    }
+
+    pub fn set_held_interval(&mut self, interval: MillisDurationU32) {
+        self.button.set_held_interval(interval)
+    }
+
+    pub fn set_hold_delay(&mut self, delay: MillisDurationU32) {
+        self.button.set_hold_delay(delay)
+    }
+
+    pub fn set_stuck_timeout(&mut self, timeout: MillisDurationU32) {
+        self.button.set_stuck_timeout(timeout)
+    }
+
+    pub fn is_stuck(&self) -> bool {
+        self.button.is_stuck()
+    }
+
+    pub fn set_multi_click(&mut self, window: MillisDurationU32, max_click: MillisDurationU32) {
+        self.button.set_multi_click(window, max_click)
+    }
+
+    pub fn set_min_press(&mut self, min: MillisDurationU32) {
+        self.button.set_min_press(min)
+    }
+
+    pub fn set_click_threshold(&mut self, threshold: MillisDurationU32) {
+        self.button.set_click_threshold(threshold)
+    }
+
+    pub fn set_hold_milestones(&mut self, milestones: &[MillisDurationU32]) {
+        self.button.set_hold_milestones(milestones)
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.button.set_debounce_samples(samples)
+    }
+
+    pub fn set_debounce(&mut self, window: MillisDurationU32) {
+        self.button.set_debounce(window)
+    }
+
+    pub fn handle_press(&mut self) -> bool {
         self.button.handle_press()
     }
 
+    pub fn polarity(&self) -> Polarity {
+        self.button.polarity()
+    }
+
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        self.button.set_polarity(polarity)
+    }
+
+    pub fn reset(&mut self) {
+        self.button.reset()
+    }
+
+    pub fn reset_from_pin(&mut self) -> Result<(), Error<K::Error>> {
+        self.button.reset_from_pin()
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.button.is_down()
+    }
+
+    pub fn last_level(&self) -> bool {
+        self.button.last_level()
+    }
+
+    pub fn press_duration(&mut self) -> Option<MillisDurationU32> {
+        self.button.press_duration(self.clock.now())
+    }
+
+    pub fn wide_press_duration(&mut self) -> Option<MillisDurationU64> {
+        self.button.wide_press_duration(self.clock.now())
+    }
+
+    pub fn time_since_last_click(&mut self) -> Option<MillisDurationU32> {
+        self.button.time_since_last_click(self.clock.now())
+    }
+
+    pub fn time_since_last_press(&mut self) -> Option<MillisDurationU32> {
+        self.button.time_since_last_press(self.clock.now())
+    }
+
     pub fn update(&mut self) -> Result<TimeButtonAction, Error<K::Error>> {
         self.button.update(self.clock.now())
     }
+
+    pub fn update_with_level(&mut self, pressed: bool) -> TimeButtonAction {
+        self.button.update_with_level(self.clock.now(), pressed)
+    }
+
+    /// See [`TimeButton::update_wide`].
+    pub fn update_wide(&mut self) -> Result<WideTimeButtonAction, Error<K::Error>> {
+        self.button.update_wide(self.clock.now())
+    }
+
+    pub fn update_stamped(&mut self) -> Result<Stamped<TimeButtonAction, C::Instant>, Error<K::Error>> {
+        self.button.update_stamped(self.clock.now())
+    }
 }
@@ -0,0 +1,90 @@
+use crate::rotary::StepMode;
+
+/// Recommended step mode and the share of the measured detents agreeing with it, in percent.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Calibration {
+    pub step_mode: StepMode,
+    pub confidence: u8,
+}
+
+/// Finds the transitions per detent of an unknown encoder. Feed every sample while the
+/// user turns the knob and call [`mark_detent`](Self::mark_detent) each time they report
+/// one detent turned. Only valid transitions count, signed by direction, so bounce cancels
+/// out and skipped states are ignored. Apply the result with `set_step_mode`.
+#[derive(Clone, Debug, Default)]
+pub struct DividerCalibrator {
+    last: Option<u8>,
+    transitions: i16,
+    // Detents measured as 4, 2 and 1 transitions
+    votes: [u8; 3],
+    rejected: u8,
+}
+
+impl DividerCalibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops every measurement.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// `true` means the line is low. Polarity does not matter, only transitions are counted.
+    pub fn sample(&mut self, a_low: bool, b_low: bool) {
+        // Position of the state within the quadrature cycle
+        let state = match (a_low, b_low) {
+            (false, false) => 0,
+            (false, true) => 1,
+            (true, true) => 2,
+            (true, false) => 3,
+        };
+        if let Some(last) = self.last.replace(state) {
+            match (state + 4 - last) % 4 {
+                1 => self.transitions = self.transitions.saturating_add(1),
+                3 => self.transitions = self.transitions.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    /// Ends the measurement of one detent and returns its step mode, `None` when the count
+    /// of transitions matches no mode. Either way the detent counts for the confidence.
+    pub fn mark_detent(&mut self) -> Option<StepMode> {
+        let vote = match core::mem::take(&mut self.transitions).unsigned_abs() {
+            4 => Some(0),
+            2 => Some(1),
+            1 => Some(2),
+            _ => None,
+        };
+        match vote {
+            Some(i) => self.votes[i] = self.votes[i].saturating_add(1),
+            None => self.rejected = self.rejected.saturating_add(1),
+        }
+        vote.map(Self::mode)
+    }
+
+    /// Count of detents marked so far.
+    pub fn detents(&self) -> u16 {
+        self.votes.iter().map(|&v| v as u16).sum::<u16>() + self.rejected as u16
+    }
+
+    /// Most common step mode, `None` before a detent matched one. Ties go to fewer
+    /// detents per cycle.
+    pub fn recommendation(&self) -> Option<Calibration> {
+        let (i, &votes) = self.votes.iter().enumerate().rev().max_by_key(|&(_, &v)| v)?;
+        if votes == 0 {
+            return None;
+        }
+        let confidence = (votes as u32 * 100 / self.detents() as u32) as u8;
+        Some(Calibration { step_mode: Self::mode(i), confidence })
+    }
+
+    fn mode(vote: usize) -> StepMode {
+        match vote {
+            0 => StepMode::Full,
+            1 => StepMode::Half,
+            _ => StepMode::Quarter,
+        }
+    }
+}
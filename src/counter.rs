@@ -0,0 +1,226 @@
+use crate::rotary::{Accelerator, AccelCurve, Linear, RateWindow, Rotation};
+use crate::Instant;
+use fugit::MillisDurationU32;
+
+/// Free running quadrature count of a hardware decoder, such as a timer in encoder mode.
+pub trait CountSource {
+    type Error;
+
+    /// Width of the counter, the count wraps around at `2^BITS`. At most 32.
+    const BITS: u32 = 16;
+
+    /// Current count, only the low `BITS` bits are used.
+    fn read(&mut self) -> Result<u32, Self::Error>;
+}
+
+/// Counter kept in software, e.g. incremented from pin interrupts.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct SoftCounter<const BITS: u32 = 16> {
+    count: u32,
+}
+
+impl<const BITS: u32> SoftCounter<BITS> {
+    pub fn new(count: u32) -> Self {
+        let mut counter = Self::default();
+        counter.set(count);
+        counter
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn set(&mut self, count: u32) {
+        self.count = count & mask(BITS);
+    }
+
+    /// Adds `delta` counts, wrapping around at `2^BITS`.
+    pub fn add(&mut self, delta: i32) {
+        self.set(self.count.wrapping_add(delta as u32));
+    }
+}
+
+impl<const BITS: u32> CountSource for SoftCounter<BITS> {
+    type Error = core::convert::Infallible;
+
+    const BITS: u32 = BITS;
+
+    fn read(&mut self) -> Result<u32, Self::Error> {
+        Ok(self.count)
+    }
+}
+
+#[inline]
+fn mask(bits: u32) -> u32 {
+    u32::MAX >> (32 - bits.clamp(1, 32))
+}
+
+/// Rotary decoded from a [`CountSource`] instead of pins. Reports detents the same way as
+/// [`Rotary`](crate::rotary::Rotary), but a single update can report several of them.
+/// The source has to be read at least once per half of its range.
+pub struct CounterRotary<S> {
+    source: S,
+    last: Option<u32>,
+    counts_per_detent: u16,
+    counts: i32,
+    reversed: bool,
+}
+
+impl<S: CountSource> CounterRotary<S> {
+    /// Zero `counts_per_detent` is treated as one.
+    pub fn new(source: S, counts_per_detent: u16) -> Self {
+        Self {
+            source,
+            last: None,
+            counts_per_detent: counts_per_detent.max(1),
+            counts: 0,
+            reversed: false,
+        }
+    }
+
+    pub fn source(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    pub fn set_counts_per_detent(&mut self, counts_per_detent: u16) {
+        self.counts_per_detent = counts_per_detent.max(1);
+        self.counts = 0;
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    /// Counts towards the next detent.
+    pub fn sub_steps(&self) -> i32 {
+        self.counts
+    }
+
+    /// Forgets the partial detent, the next update takes the count as its starting point.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.counts = 0;
+    }
+
+    pub fn update(&mut self) -> Result<Rotation, S::Error> {
+        let count = self.source.read()? & mask(S::BITS);
+        let delta = match self.last.replace(count) {
+            None => return Ok(Rotation::new(0)),
+            Some(last) => {
+                // Sign extends the wrapped difference from `BITS` bits
+                let shift = 32 - S::BITS.clamp(1, 32);
+                ((count.wrapping_sub(last) << shift) as i32) >> shift
+            }
+        };
+        self.counts = self.counts.saturating_add(delta);
+        let per_detent = self.counts_per_detent as i32;
+        let detents = self.counts / per_detent;
+        self.counts -= detents * per_detent;
+        Ok(Rotation::new(match self.reversed {
+            true => -detents,
+            false => detents,
+        }))
+    }
+}
+
+/// [`CounterRotary`] with the acceleration of [`TimeRotary`](crate::rotary::TimeRotary).
+/// Several detents read by one update are accelerated as one.
+pub struct TimeCounterRotary<S, T, G = Linear> {
+    rotary: CounterRotary<S>,
+    accel: Accelerator<T, G>,
+}
+
+impl<S, T> TimeCounterRotary<S, T>
+where
+    S: CountSource,
+    T: Instant,
+{
+    pub fn new(rotary: CounterRotary<S>) -> Self {
+        Self::with_acceleration(rotary, 1)
+    }
+
+    /// `acceleration` is the maximal multiplier of a detent, zero disables acceleration.
+    pub fn with_acceleration(rotary: CounterRotary<S>, acceleration: u16) -> Self {
+        Self::with_curve(rotary, acceleration, Linear::default())
+    }
+
+    /// See [`TimeRotary::set_accel_window`](crate::rotary::TimeRotary::set_accel_window).
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.accel.curve.set_window(slow, fast)
+    }
+}
+
+impl<S, T, G> TimeCounterRotary<S, T, G>
+where
+    S: CountSource,
+    T: Instant,
+    G: AccelCurve,
+{
+    pub fn with_curve(rotary: CounterRotary<S>, acceleration: u16, curve: G) -> Self {
+        Self { rotary, accel: Accelerator::new(acceleration, curve) }
+    }
+
+    pub fn rotary(&mut self) -> &mut CounterRotary<S> {
+        &mut self.rotary
+    }
+
+    pub fn set_acceleration(&mut self, acceleration: u16) {
+        self.accel.acceleration = acceleration;
+    }
+
+    pub fn acceleration(&self) -> u16 {
+        self.accel.acceleration
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.accel.curve = curve;
+    }
+
+    pub fn curve(&self) -> &G {
+        &self.accel.curve
+    }
+
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.accel.rate = RateWindow::new(window, table);
+    }
+
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.accel.max_step = max_step;
+    }
+
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.accel.reversal_lockout = lockout;
+    }
+
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.accel.min_interval = interval;
+    }
+
+    pub fn velocity(&self, now: T) -> u16 {
+        self.accel.velocity(now)
+    }
+
+    /// See [`TimeRotary::set_velocity_smoothing`](crate::rotary::TimeRotary::set_velocity_smoothing).
+    pub fn set_velocity_smoothing(&mut self, shift: u8) {
+        self.accel.smoothing = shift;
+    }
+
+    pub fn smoothed_velocity(&self) -> u16 {
+        self.accel.smoothed_velocity()
+    }
+
+    /// Forgets the partial detent and the acceleration history.
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.accel.clear();
+    }
+
+    pub fn update(&mut self, now: T) -> Result<Rotation, S::Error> {
+        let rot = self.rotary.update()?;
+        Ok(self.accel.apply(now, rot))
+    }
+}
@@ -0,0 +1,141 @@
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Edge {
+    None,
+    Pressed,
+    Released,
+}
+
+impl Edge {
+    #[inline]
+    pub(crate) fn from_state(s: u8) -> Self {
+        match s {
+            0b10 => Edge::Pressed,
+            0b01 => Edge::Released,
+            _ => Edge::None,
+        }
+    }
+}
+
+// Shifts the new level into the high bit of the two sample history
+#[inline]
+fn update_state(state: &mut u8, pressed: bool) -> u8 {
+    let s = match pressed {
+        true => (*state >> 1) | 0b10,
+        false => *state >> 1,
+    };
+    *state = s;
+    s
+}
+
+// Accepts a level change after `samples` consecutive samples of the new level
+#[derive(Copy, Clone)]
+struct Debouncer {
+    level: bool,
+    samples: u8,
+    count: u8,
+}
+
+impl Debouncer {
+    const fn with_samples(samples: u8) -> Self {
+        Self {
+            level: false,
+            samples: if samples == 0 { 1 } else { samples },
+            count: 0,
+        }
+    }
+
+    fn set_samples(&mut self, samples: u8) {
+        self.samples = samples.max(1);
+    }
+
+    fn reset(&mut self, level: bool) {
+        self.level = level;
+        self.count = 0;
+    }
+
+    #[inline]
+    fn update(&mut self, level: bool) -> bool {
+        if level == self.level {
+            self.count = 0;
+        } else {
+            self.count += 1;
+            if self.count >= self.samples {
+                self.count = 0;
+                self.level = level;
+            }
+        }
+        self.level
+    }
+}
+
+/// Debounced boolean signal. A level change is accepted after `SAMPLES` consecutive
+/// samples of the new level, zero acts as one. `true` is treated as the pressed level.
+/// Buttons and rotaries debounce their lines with it, `SAMPLES` only sets the initial
+/// depth for [`set_samples`](Self::set_samples).
+#[derive(Copy, Clone)]
+pub struct Debounced<const SAMPLES: u8 = 1> {
+    debouncer: Debouncer,
+    state: u8,
+}
+
+impl<const SAMPLES: u8> Debounced<SAMPLES> {
+    pub const fn new() -> Self {
+        Self::with_level(false)
+    }
+
+    /// Creates a signal already settled at `level`, so it reports no edge for it.
+    pub const fn with_level(level: bool) -> Self {
+        let mut debouncer = Debouncer::with_samples(SAMPLES);
+        debouncer.level = level;
+        Self {
+            debouncer,
+            state: if level { 0b11 } else { 0b00 },
+        }
+    }
+
+    /// Current debounced level.
+    pub fn level(&self) -> bool {
+        self.debouncer.level
+    }
+
+    /// Changes the depth at runtime, zero acts as one. A change in progress restarts.
+    pub fn set_samples(&mut self, samples: u8) {
+        self.debouncer.set_samples(samples);
+        self.debouncer.count = 0;
+    }
+
+    /// Settles at `level` without reporting an edge, dropping a change in progress.
+    pub fn reset(&mut self, level: bool) {
+        self.debouncer.reset(level);
+        self.state = if level { 0b11 } else { 0b00 };
+    }
+
+    pub fn update(&mut self, level: bool) -> Edge {
+        Edge::from_state(self.sample(level))
+    }
+
+    // Debounces a sample, returns the two last debounced levels with the current one
+    // in the high bit
+    #[inline]
+    pub(crate) fn sample(&mut self, level: bool) -> u8 {
+        let level = self.debouncer.update(level);
+        update_state(&mut self.state, level)
+    }
+
+    #[inline]
+    pub(crate) fn history(&self) -> u8 {
+        self.state
+    }
+
+    // Swaps the meaning of both levels in place, reporting no edge
+    pub(crate) fn invert(&mut self) {
+        self.state ^= 0b11;
+        self.debouncer.reset(!self.debouncer.level);
+    }
+}
+
+impl<const SAMPLES: u8> Default for Debounced<SAMPLES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
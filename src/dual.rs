@@ -0,0 +1,171 @@
+use crate::input::PinKind;
+use crate::rotary::{AccelCurve, FullStep, Linear, PinRotary, RotaryError, Rotation, Steps, TimeRotary};
+use crate::{ButtonInput, Instant};
+use core::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DualRotaryError<A1, B1, A2, B2> {
+    OuterA(A1),
+    OuterB(B1),
+    InnerA(A2),
+    InnerB(B2),
+}
+
+impl<A1, B1, A2, B2> DualRotaryError<A1, B1, A2, B2> {
+    pub fn is_outer(&self) -> bool {
+        matches!(self, DualRotaryError::OuterA(_) | DualRotaryError::OuterB(_))
+    }
+
+    /// Failed line of the encoder told by [`is_outer`](Self::is_outer).
+    pub fn into_kind(self) -> PinKind {
+        match self {
+            DualRotaryError::OuterA(_) | DualRotaryError::InnerA(_) => PinKind::A,
+            DualRotaryError::OuterB(_) | DualRotaryError::InnerB(_) => PinKind::B,
+        }
+    }
+
+    fn outer(err: RotaryError<A1, B1>) -> Self {
+        match err {
+            RotaryError::APin(e) => DualRotaryError::OuterA(e),
+            RotaryError::BPin(e) => DualRotaryError::OuterB(e),
+        }
+    }
+
+    fn inner(err: RotaryError<A2, B2>) -> Self {
+        match err {
+            RotaryError::APin(e) => DualRotaryError::InnerA(e),
+            RotaryError::BPin(e) => DualRotaryError::InnerB(e),
+        }
+    }
+}
+
+impl<A1, B1, A2, B2> fmt::Display for DualRotaryError<A1, B1, A2, B2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DualRotaryError::OuterA(_) => f.write_str("outer rotary A pin error"),
+            DualRotaryError::OuterB(_) => f.write_str("outer rotary B pin error"),
+            DualRotaryError::InnerA(_) => f.write_str("inner rotary A pin error"),
+            DualRotaryError::InnerB(_) => f.write_str("inner rotary B pin error"),
+        }
+    }
+}
+
+impl<A1, B1, A2, B2> core::error::Error for DualRotaryError<A1, B1, A2, B2>
+where
+    A1: fmt::Debug,
+    B1: fmt::Debug,
+    A2: fmt::Debug,
+    B2: fmt::Debug,
+{
+}
+
+pub(crate) type DualError<A1, B1, A2, B2> = DualRotaryError<
+    <A1 as ButtonInput>::Error,
+    <B1 as ButtonInput>::Error,
+    <A2 as ButtonInput>::Error,
+    <B2 as ButtonInput>::Error,
+>;
+
+/// Rotations of both encoders from one update.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct DualRotation {
+    pub outer: Rotation,
+    pub inner: Rotation,
+}
+
+impl DualRotation {
+    pub fn is_zero(&self) -> bool {
+        self.outer.is_zero() && self.inner.is_zero()
+    }
+}
+
+/// Two concentric encoders, e.g. coarse and fine tuning, updated together. Each one is
+/// configured through [`outer`](Self::outer) and [`inner`](Self::inner).
+pub struct DualRotary<A1, B1, A2, B2, OM = FullStep, IM = FullStep> {
+    outer: PinRotary<A1, B1, OM>,
+    inner: PinRotary<A2, B2, IM>,
+}
+
+impl<A1, B1, A2, B2, OM: Steps, IM: Steps> DualRotary<A1, B1, A2, B2, OM, IM>
+where
+    A1: ButtonInput,
+    B1: ButtonInput,
+    A2: ButtonInput,
+    B2: ButtonInput,
+{
+    pub fn new(outer: PinRotary<A1, B1, OM>, inner: PinRotary<A2, B2, IM>) -> Self {
+        Self { outer, inner }
+    }
+
+    pub fn outer(&mut self) -> &mut PinRotary<A1, B1, OM> {
+        &mut self.outer
+    }
+
+    pub fn inner(&mut self) -> &mut PinRotary<A2, B2, IM> {
+        &mut self.inner
+    }
+
+    pub fn into_parts(self) -> (PinRotary<A1, B1, OM>, PinRotary<A2, B2, IM>) {
+        (self.outer, self.inner)
+    }
+
+    pub fn reset(&mut self) {
+        self.outer.reset();
+        self.inner.reset();
+    }
+
+    /// Updates the outer encoder, then the inner one. A failing outer pin leaves the inner
+    /// encoder unread.
+    pub fn update(&mut self) -> Result<DualRotation, DualError<A1, B1, A2, B2>> {
+        let outer = self.outer.update().map_err(DualRotaryError::outer)?;
+        let inner = self.inner.update().map_err(DualRotaryError::inner)?;
+        Ok(DualRotation { outer, inner })
+    }
+}
+
+/// [`DualRotary`] of two [`TimeRotary`]s accelerated from the same instant.
+pub struct TimeDualRotary<A1, B1, A2, B2, T, OM = FullStep, IM = FullStep, G = Linear>
+where
+    T: Instant,
+{
+    outer: TimeRotary<A1, B1, T, OM, G>,
+    inner: TimeRotary<A2, B2, T, IM, G>,
+}
+
+impl<A1, B1, A2, B2, T, OM: Steps, IM: Steps, G> TimeDualRotary<A1, B1, A2, B2, T, OM, IM, G>
+where
+    A1: ButtonInput,
+    B1: ButtonInput,
+    A2: ButtonInput,
+    B2: ButtonInput,
+    T: Instant,
+    G: AccelCurve,
+{
+    pub fn new(outer: TimeRotary<A1, B1, T, OM, G>, inner: TimeRotary<A2, B2, T, IM, G>) -> Self {
+        Self { outer, inner }
+    }
+
+    pub fn outer(&mut self) -> &mut TimeRotary<A1, B1, T, OM, G> {
+        &mut self.outer
+    }
+
+    pub fn inner(&mut self) -> &mut TimeRotary<A2, B2, T, IM, G> {
+        &mut self.inner
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(self) -> (TimeRotary<A1, B1, T, OM, G>, TimeRotary<A2, B2, T, IM, G>) {
+        (self.outer, self.inner)
+    }
+
+    pub fn reset(&mut self) {
+        self.outer.reset();
+        self.inner.reset();
+    }
+
+    pub fn update(&mut self, now: T) -> Result<DualRotation, DualError<A1, B1, A2, B2>> {
+        let outer = self.outer.update(now).map_err(DualRotaryError::outer)?;
+        let inner = self.inner.update(now).map_err(DualRotaryError::inner)?;
+        Ok(DualRotation { outer, inner })
+    }
+}
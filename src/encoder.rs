@@ -1,33 +1,127 @@
-use embedded_hal::digital::v2::InputPin;
 use fugit::MillisDurationU32;
-use crate::rotary::{Rotary, RotaryError, Rotation, TimeRotary};
-use crate::button::{Button, TimeButton};
-use crate::{button, Clock, Instant};
+use crate::rotary::{
+    AccelCurve, DoubleTransition, Flick, Linear, PinRotary, RestState, Rotary, RotaryError, RotaryHealth, Rotation, StepMode, Steps,
+    TimeRotary,
+};
+use crate::button::{Button, Polarity, TimeButton};
+use crate::input::PinKind;
+use crate::{button, ButtonInput, Clock, Instant, Stamped};
+use core::fmt;
+#[cfg(feature = "switch-hal")]
+use crate::input::SwitchInput;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum EncoderAction {
     None,
     Press,
     Held,
     Click,
+    Release,
     Rotate(Rotation),
     RotatePressed(Rotation),
 }
 
+impl EncoderAction {
+    pub fn is_none(&self) -> bool {
+        matches!(self, EncoderAction::None)
+    }
+
+    pub fn is_press(&self) -> bool {
+        matches!(self, EncoderAction::Press)
+    }
+
+    pub fn is_click(&self) -> bool {
+        matches!(self, EncoderAction::Click)
+    }
+
+    /// Rotation of `Rotate` or `RotatePressed`.
+    pub fn rotation(&self) -> Option<Rotation> {
+        match *self {
+            EncoderAction::Rotate(r) | EncoderAction::RotatePressed(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn is_rotate_pressed(&self) -> bool {
+        matches!(self, EncoderAction::RotatePressed(_))
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum TimeEncoderAction {
     None,
     Press,
     Held(MillisDurationU32),
     Click(MillisDurationU32),
+    Release(MillisDurationU32),
+    DoubleClick(MillisDurationU32),
+    LongPress(MillisDurationU32),
+    Repeat(u16),
+    MultiClick { count: u8, last_duration: MillisDurationU32 },
+    ShortClick(MillisDurationU32),
+    LongClick(MillisDurationU32),
+    HoldMilestone(u8, MillisDurationU32),
+    /// Rotation while the key is stuck is reported as `Rotate`.
+    Stuck(MillisDurationU32),
     Rotate(Rotation),
     RotatePressed(Rotation),
+    /// Reported on the first update without another action once the flick is detected.
+    Flick(Flick),
+}
+
+impl TimeEncoderAction {
+    pub fn is_none(&self) -> bool {
+        matches!(self, TimeEncoderAction::None)
+    }
+
+    pub fn is_press(&self) -> bool {
+        matches!(self, TimeEncoderAction::Press)
+    }
+
+    /// `true` for `Click`, `ShortClick` and `LongClick`.
+    pub fn is_click(&self) -> bool {
+        self.click_duration().is_some()
+    }
+
+    pub fn held_duration(&self) -> Option<MillisDurationU32> {
+        match *self {
+            TimeEncoderAction::Held(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Press duration of `Click`, `ShortClick` or `LongClick`.
+    pub fn click_duration(&self) -> Option<MillisDurationU32> {
+        match *self {
+            TimeEncoderAction::Click(t)
+            | TimeEncoderAction::ShortClick(t)
+            | TimeEncoderAction::LongClick(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Rotation of `Rotate` or `RotatePressed`.
+    pub fn rotation(&self) -> Option<Rotation> {
+        match *self {
+            TimeEncoderAction::Rotate(r) | TimeEncoderAction::RotatePressed(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn is_rotate_pressed(&self) -> bool {
+        matches!(self, TimeEncoderAction::RotatePressed(_))
+    }
 }
 
 // ----------------
 // # EncoderError #
 // ----------------
 
+pub(crate) type PinsError<A, B, K> = EncoderError<<A as ButtonInput>::Error, <B as ButtonInput>::Error, <K as ButtonInput>::Error>;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum EncoderError<A, B, K>
 {
     APin(A),
@@ -35,6 +129,61 @@ pub enum EncoderError<A, B, K>
     KPin(K),
 }
 
+impl<A, B, K> EncoderError<A, B, K> {
+    pub fn into_kind(self) -> PinKind {
+        match self {
+            EncoderError::APin(_) => PinKind::A,
+            EncoderError::BPin(_) => PinKind::B,
+            EncoderError::KPin(_) => PinKind::Key,
+        }
+    }
+
+    pub fn map_a<E>(self, f: impl FnOnce(A) -> E) -> EncoderError<E, B, K> {
+        match self {
+            EncoderError::APin(e) => EncoderError::APin(f(e)),
+            EncoderError::BPin(e) => EncoderError::BPin(e),
+            EncoderError::KPin(e) => EncoderError::KPin(e),
+        }
+    }
+
+    pub fn map_b<E>(self, f: impl FnOnce(B) -> E) -> EncoderError<A, E, K> {
+        match self {
+            EncoderError::APin(e) => EncoderError::APin(e),
+            EncoderError::BPin(e) => EncoderError::BPin(f(e)),
+            EncoderError::KPin(e) => EncoderError::KPin(e),
+        }
+    }
+
+    pub fn map_k<E>(self, f: impl FnOnce(K) -> E) -> EncoderError<A, B, E> {
+        match self {
+            EncoderError::APin(e) => EncoderError::APin(e),
+            EncoderError::BPin(e) => EncoderError::BPin(e),
+            EncoderError::KPin(e) => EncoderError::KPin(f(e)),
+        }
+    }
+}
+
+impl<E> EncoderError<E, E, E> {
+    /// Error of whichever pin failed, for pins sharing an error type.
+    pub fn into_inner(self) -> E {
+        match self {
+            EncoderError::APin(e) | EncoderError::BPin(e) | EncoderError::KPin(e) => e,
+        }
+    }
+}
+
+impl<A, B, K> fmt::Display for EncoderError<A, B, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncoderError::APin(_) => f.write_str("encoder A pin error"),
+            EncoderError::BPin(_) => f.write_str("encoder B pin error"),
+            EncoderError::KPin(_) => f.write_str("encoder key pin error"),
+        }
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug, K: fmt::Debug> core::error::Error for EncoderError<A, B, K> {}
+
 impl<A, B, K> From<RotaryError<A, B>> for EncoderError<A, B, K>
 {
     fn from(re: RotaryError<A, B>) -> Self {
@@ -59,17 +208,17 @@ impl<A, B, K> From<button::Error<K>> for EncoderError<A, B, K>
 // -----------
 
 // Энкодер с кнопкой
-pub struct Encoder<A, B, K, const ROTATION_DIVIDER: i8> where A: InputPin, B: InputPin, K: InputPin {
-    rotary: Rotary<A, B>,
+pub struct Encoder<A, B, K, M> where A: ButtonInput, B: ButtonInput, K: ButtonInput {
+    rotary: PinRotary<A, B, M>,
     button: Button<K, true>,
     rotated_on_hold: bool,
 }
 
-impl<A, B, K, const ROTATION_DIVIDER: i8> Encoder<A, B, K, ROTATION_DIVIDER>
+impl<A, B, K, M: Steps> Encoder<A, B, K, M>
     where
-        A: InputPin,
-        B: InputPin,
-        K: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
+        K: ButtonInput,
 {
     pub fn new(a_pin: A, b_pin: B, k_pin: K) -> Self {
         let rotary = Rotary::new(a_pin, b_pin);
@@ -81,77 +230,222 @@ impl<A, B, K, const ROTATION_DIVIDER: i8> Encoder<A, B, K, ROTATION_DIVIDER>
         }
     }
 
-    pub fn handle_press(&mut self) {
+    /// Creates an encoder with the given active levels of A and B, see [`Rotary::with_polarity`].
+    pub fn with_rotary_polarity(a_pin: A, b_pin: B, k_pin: K, a: Polarity, b: Polarity) -> Self {
+        Self {
+            rotary: Rotary::with_polarity(a_pin, b_pin, a, b),
+            button: Button::new(k_pin),
+            rotated_on_hold: false,
+        }
+    }
+
+    /// Creates an encoder with the key pressed at the given level, active low by default.
+    pub fn with_key_polarity(a_pin: A, b_pin: B, k_pin: K, polarity: Polarity) -> Self {
+        Self {
+            rotary: Rotary::new(a_pin, b_pin),
+            button: Button::with_polarity(k_pin, polarity),
+            rotated_on_hold: false,
+        }
+    }
+
+    /// Creates an encoder whose button is seeded from the current key level,
+    /// see [`Button::new_from_pin`].
+    pub fn new_from_pin(a_pin: A, b_pin: B, k_pin: K) -> Result<Self, PinsError<A, B, K>> {
+        let rotary = Rotary::new(a_pin, b_pin);
+        let button = Button::new_from_pin(k_pin)?;
+        Ok(Self {
+            rotary,
+            button,
+            rotated_on_hold: false,
+        })
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.button.set_debounce_samples(samples)
+    }
+
+    pub fn key_polarity(&self) -> Polarity {
+        self.button.polarity()
+    }
+
+    pub fn set_key_polarity(&mut self, polarity: Polarity) {
+        self.button.set_polarity(polarity)
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.rotary.set_step_mode(step_mode)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.rotary.set_reversed(reversed)
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.rotary.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.rotary.set_double_transition(double)
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.rotary.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.rotary.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.rotary.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.rotary.reset_odometer()
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.rotary.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.rotary.health()
+    }
+
+    pub fn set_rotary_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.rotary.set_polarity(a, b)
+    }
+
+    pub fn handle_press(&mut self) -> bool {
         self.rotated_on_hold = false;
         self.button.handle_press()
     }
 
-    pub fn update(&mut self) -> Result<EncoderAction, EncoderError<A::Error, B::Error, K::Error>> {
+    pub fn a_pin(&mut self) -> &mut A {
+        self.rotary.a_pin()
+    }
+
+    pub fn b_pin(&mut self) -> &mut B {
+        self.rotary.b_pin()
+    }
+
+    pub fn k_pin(&mut self) -> &mut K {
+        self.button.k_pin()
+    }
+
+    /// Gives the pins back. Decoding state, press tracking and settings are discarded.
+    pub fn into_parts(self) -> (A, B, K) {
+        let (a_pin, b_pin) = self.rotary.into_parts();
+        (a_pin, b_pin, self.button.into_pin())
+    }
+
+    /// Resets the rotary and the button, see [`Button::reset`].
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.button.reset();
+        self.rotated_on_hold = false;
+    }
+
+    pub fn reset_from_pins(&mut self) -> Result<(), PinsError<A, B, K>> {
+        self.rotary.reset_from_pins()?;
+        self.button.reset_from_pin()?;
+        self.rotated_on_hold = false;
+        Ok(())
+    }
+
+    pub fn update(&mut self) -> Result<EncoderAction, PinsError<A, B, K>> {
         let rotation = self.rotary.update()?;
         let btn_action = self.button.update()?;
 
-        let act = match (self.rotated_on_hold, rotation.is_zero(), btn_action) {
-            (false, false, button::ButtonAction::None) => EncoderAction::Rotate(rotation),
-            (false, true, button::ButtonAction::None) => EncoderAction::None,
-            (true, false, button::ButtonAction::None) => {
-                self.rotated_on_hold = false;
-                EncoderAction::None
-            },
-            (true, true, button::ButtonAction::None) => {
-                self.rotated_on_hold = false;
-                EncoderAction::None
-            },
+        Ok(combine(&mut self.rotated_on_hold, rotation, btn_action))
+    }
+}
 
-            (false, false, button::ButtonAction::Press) => {
-                self.rotated_on_hold = true;
-                EncoderAction::RotatePressed(rotation)
-            },
-            (false, true, button::ButtonAction::Press) => EncoderAction::Press,
-            (true, false, button::ButtonAction::Press) => {
-                EncoderAction::RotatePressed(rotation)
-            },
-            (true, true, button::ButtonAction::Press) =>
-                EncoderAction::None,
+// Merges a rotation with the key action of the same update, tracking rotation while pressed
+pub(crate) fn combine(rotated_on_hold: &mut bool, rotation: Rotation, btn_action: button::ButtonAction) -> EncoderAction {
+    match (*rotated_on_hold, rotation.is_zero(), btn_action) {
+        (false, false, button::ButtonAction::None) => EncoderAction::Rotate(rotation),
+        (false, true, button::ButtonAction::None) => EncoderAction::None,
+        (true, false, button::ButtonAction::None) => {
+            *rotated_on_hold = false;
+            EncoderAction::None
+        },
+        (true, true, button::ButtonAction::None) => {
+            *rotated_on_hold = false;
+            EncoderAction::None
+        },
 
-            (false, false, button::ButtonAction::Held) => {
-                self.rotated_on_hold = true;
-                EncoderAction::RotatePressed(rotation)
-            },
-            (false, true, button::ButtonAction::Held) => EncoderAction::Held,
-            (true, false, button::ButtonAction::Held) => {
-                EncoderAction::RotatePressed(rotation)
-            },
-            (true, true, button::ButtonAction::Held) => EncoderAction::None,
+        (false, false, button::ButtonAction::Press) => {
+            *rotated_on_hold = true;
+            EncoderAction::RotatePressed(rotation)
+        },
+        (false, true, button::ButtonAction::Press) => EncoderAction::Press,
+        (true, false, button::ButtonAction::Press) => {
+            EncoderAction::RotatePressed(rotation)
+        },
+        (true, true, button::ButtonAction::Press) =>
+            EncoderAction::None,
 
-            (false, false, button::ButtonAction::Click) => EncoderAction::Click,
-            (false, true, button::ButtonAction::Click) => EncoderAction::Click,
-            (true, false, button::ButtonAction::Click) => {
-                self.rotated_on_hold = false;
-                EncoderAction::None
-            },
-            (true, true, button::ButtonAction::Click) => {
-                self.rotated_on_hold = false;
-                EncoderAction::None
-            },
-        };
+        (false, false, button::ButtonAction::Held) => {
+            *rotated_on_hold = true;
+            EncoderAction::RotatePressed(rotation)
+        },
+        (false, true, button::ButtonAction::Held) => EncoderAction::Held,
+        (true, false, button::ButtonAction::Held) => {
+            EncoderAction::RotatePressed(rotation)
+        },
+        (true, true, button::ButtonAction::Held) => EncoderAction::None,
 
-        Ok(act)
+        (false, false, button::ButtonAction::Click) => EncoderAction::Click,
+        (false, true, button::ButtonAction::Click) => EncoderAction::Click,
+        (true, false, button::ButtonAction::Click) => {
+            *rotated_on_hold = false;
+            EncoderAction::Release
+        },
+        (true, true, button::ButtonAction::Click) => {
+            *rotated_on_hold = false;
+            EncoderAction::Release
+        },
+
+        (_, _, button::ButtonAction::Release) => {
+            *rotated_on_hold = false;
+            EncoderAction::Release
+        },
     }
 }
 
 
+#[cfg(feature = "switch-hal")]
+impl<A, B, S, M: Steps> Encoder<A, B, SwitchInput<S>, M>
+    where
+        A: ButtonInput,
+        B: ButtonInput,
+        S: switch_hal::InputSwitch,
+{
+    /// Creates an encoder with a `switch-hal` input switch as the key, pressed while active.
+    pub fn with_key_switch(a_pin: A, b_pin: B, switch: S) -> Self {
+        Self {
+            rotary: Rotary::new(a_pin, b_pin),
+            button: Button::with_polarity(SwitchInput(switch), Polarity::ActiveHigh),
+            rotated_on_hold: false,
+        }
+    }
+}
+
 // Энкодер с кнопкой
-pub struct TimeEncoder<A, B, K, T, const ROTATION_DIVIDER: i8> where A: InputPin, B: InputPin, K: InputPin, T: Instant {
-    rotary: TimeRotary<A, B, T, ROTATION_DIVIDER>,
+pub struct TimeEncoder<A, B, K, T, M, G = Linear> where A: ButtonInput, B: ButtonInput, K: ButtonInput, T: Instant {
+    rotary: TimeRotary<A, B, T, M, G>,
     button: TimeButton<K, T, true>,
     rotated_on_hold: bool,
+    // Rotation sampled in the update reporting `Stuck`, reported on the next one
+    stuck_rotation: Rotation,
 }
 
-impl<A, B, K, T, const ROTATION_DIVIDER: i8> TimeEncoder<A, B, K, T, ROTATION_DIVIDER>
+impl<A, B, K, T, M: Steps> TimeEncoder<A, B, K, T, M>
     where
-        A: InputPin,
-        B: InputPin,
-        K: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
+        K: ButtonInput,
         T: Instant,
 {
     pub fn new(a_pin: A, b_pin: B, k_pin: K) -> Self {
@@ -161,20 +455,278 @@ impl<A, B, K, T, const ROTATION_DIVIDER: i8> TimeEncoder<A, B, K, T, ROTATION_DI
             rotary,
             button,
             rotated_on_hold: false,
+            stuck_rotation: Rotation::ZERO,
+        }
+    }
+
+    /// Creates an encoder reporting nothing during `grace` since the first `update`,
+    /// see [`TimeButton::with_startup_grace`] and [`TimeRotary::with_startup_grace`].
+    pub fn with_startup_grace(a_pin: A, b_pin: B, k_pin: K, grace: MillisDurationU32) -> Self {
+        Self {
+            rotary: TimeRotary::with_startup_grace(a_pin, b_pin, grace),
+            button: TimeButton::with_startup_grace(k_pin, grace),
+            rotated_on_hold: false,
+            stuck_rotation: Rotation::ZERO,
+        }
+    }
+
+    pub fn with_rotary_polarity(a_pin: A, b_pin: B, k_pin: K, a: Polarity, b: Polarity) -> Self {
+        let mut encoder = Self::new(a_pin, b_pin, k_pin);
+        encoder.set_rotary_polarity(a, b);
+        // Setting reinterprets the default history, start from rest instead
+        encoder.rotary.reset();
+        encoder
+    }
+
+    /// Creates an encoder with the key pressed at the given level, active low by default.
+    pub fn with_key_polarity(a_pin: A, b_pin: B, k_pin: K, polarity: Polarity) -> Self {
+        Self {
+            rotary: TimeRotary::new(a_pin, b_pin),
+            button: TimeButton::with_polarity(k_pin, polarity),
+            rotated_on_hold: false,
+            stuck_rotation: Rotation::ZERO,
+        }
+    }
+
+    pub fn new_from_pin(a_pin: A, b_pin: B, k_pin: K) -> Result<Self, PinsError<A, B, K>> {
+        let rotary = TimeRotary::new(a_pin, b_pin);
+        let button = TimeButton::new_from_pin(k_pin)?;
+        Ok(Self {
+            rotary,
+            button,
+            rotated_on_hold: false,
+            stuck_rotation: Rotation::ZERO,
+        })
+    }
+
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.rotary.set_accel_window(slow, fast)
+    }
+}
+
+impl<A, B, K, T, M: Steps, G> TimeEncoder<A, B, K, T, M, G>
+    where
+        A: ButtonInput,
+        B: ButtonInput,
+        K: ButtonInput,
+        T: Instant,
+        G: AccelCurve,
+{
+    pub fn with_curve(a_pin: A, b_pin: B, k_pin: K, acceleration: u16, curve: G) -> Self {
+        Self {
+            rotary: TimeRotary::with_curve(a_pin, b_pin, acceleration, curve),
+            button: TimeButton::new(k_pin),
+            rotated_on_hold: false,
+            stuck_rotation: Rotation::ZERO,
         }
     }
+
 pub fn set_acceleration(&mut self, acc: u16) {
     self.rotary.set_acceleration(acc)
 }
 
-    pub fn handle_press(&mut self) {
+    pub fn acceleration(&self) -> u16 {
+        self.rotary.acceleration()
+    }
+
+    /// See [`TimeRotary::set_gain_decay`],
+    /// `shift` divides the gain by a power of two.
+    pub fn set_gain_decay(&mut self, shift: u8) {
+        self.rotary.set_gain_decay(shift)
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.rotary.set_curve(curve)
+    }
+
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.rotary.set_rate_acceleration(window, table)
+    }
+
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.rotary.set_max_step(max_step)
+    }
+
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.rotary.set_reversal_lockout(lockout)
+    }
+
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.rotary.set_min_detent_interval(interval)
+    }
+
+    /// See [`TimeRotary::set_flick`].
+    pub fn set_flick(&mut self, min_detents: u8, window: MillisDurationU32, quiet: MillisDurationU32) {
+        self.rotary.set_flick(min_detents, window, quiet)
+    }
+
+    pub fn set_double_click_window(&mut self, window: MillisDurationU32) {
+        self.button.set_double_click_window(window)
+    }
+
+    pub fn set_long_press(&mut self, threshold: MillisDurationU32) {
+        self.button.set_long_press(threshold)
+    }
+
+    pub fn set_repeat(&mut self, initial: MillisDurationU32, rate: MillisDurationU32) {
+        self.button.set_repeat(initial, rate)
+    }
+
+    pub fn set_held_interval(&mut self, interval: MillisDurationU32) {
+        self.button.set_held_interval(interval)
+    }
+
+    pub fn set_hold_delay(&mut self, delay: MillisDurationU32) {
+        self.button.set_hold_delay(delay)
+    }
+
+    pub fn set_stuck_timeout(&mut self, timeout: MillisDurationU32) {
+        self.button.set_stuck_timeout(timeout)
+    }
+
+    pub fn is_stuck(&self) -> bool {
+        self.button.is_stuck()
+    }
+
+    pub fn set_multi_click(&mut self, window: MillisDurationU32, max_click: MillisDurationU32) {
+        self.button.set_multi_click(window, max_click)
+    }
+
+    pub fn set_min_press(&mut self, min: MillisDurationU32) {
+        self.button.set_min_press(min)
+    }
+
+    pub fn set_click_threshold(&mut self, threshold: MillisDurationU32) {
+        self.button.set_click_threshold(threshold)
+    }
+
+    pub fn set_hold_milestones(&mut self, milestones: &[MillisDurationU32]) {
+        self.button.set_hold_milestones(milestones)
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.button.set_debounce_samples(samples)
+    }
+
+    pub fn key_polarity(&self) -> Polarity {
+        self.button.polarity()
+    }
+
+    pub fn set_key_polarity(&mut self, polarity: Polarity) {
+        self.button.set_polarity(polarity)
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.rotary.set_step_mode(step_mode)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.rotary.set_reversed(reversed)
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.rotary.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.rotary.set_double_transition(double)
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.rotary.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.rotary.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.rotary.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.rotary.reset_odometer()
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.rotary.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.rotary.health()
+    }
+
+    pub fn set_rotary_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.rotary.set_polarity(a, b)
+    }
+
+    pub fn handle_press(&mut self) -> bool {
         self.rotated_on_hold = false;
         self.button.handle_press()
     }
 
-    pub fn update(&mut self, now: T) -> Result<TimeEncoderAction, EncoderError<A::Error, B::Error, K::Error>> {
-        let rotation = self.rotary.update(now)?;
-        let btn_action = self.button.update(now)?;
+    pub fn a_pin(&mut self) -> &mut A {
+        self.rotary.a_pin()
+    }
+
+    pub fn b_pin(&mut self) -> &mut B {
+        self.rotary.b_pin()
+    }
+
+    pub fn k_pin(&mut self) -> &mut K {
+        self.button.k_pin()
+    }
+
+    pub fn into_parts(self) -> (A, B, K) {
+        let (a_pin, b_pin) = self.rotary.into_parts();
+        (a_pin, b_pin, self.button.into_pin())
+    }
+
+    /// Resets the rotary and the button, see [`Button::reset`].
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.button.reset();
+        self.rotated_on_hold = false;
+        self.stuck_rotation = Rotation::ZERO;
+    }
+
+    pub fn reset_from_pins(&mut self) -> Result<(), PinsError<A, B, K>> {
+        self.rotary.reset_from_pins()?;
+        self.button.reset_from_pin()?;
+        self.rotated_on_hold = false;
+        self.stuck_rotation = Rotation::ZERO;
+        Ok(())
+    }
+
+    /// Rotation velocity in detents per second, see [`TimeRotary::velocity`].
+    pub fn velocity(&self, now: T) -> u16 {
+        self.rotary.velocity(now)
+    }
+
+    /// Time since the last rotation or press, zero while the key is down.
+    /// `None` before any activity.
+    pub fn idle_for(&self, now: T) -> Option<MillisDurationU32> {
+        if self.button.is_down() {
+            return Some(MillisDurationU32::from_ticks(0));
+        }
+        match (self.rotary.idle_for(now), self.button.time_since_last_press(now)) {
+            (Some(rot), Some(press)) => Some(rot.min(press)),
+            (rot, press) => rot.or(press),
+        }
+    }
+
+    /// See [`TimeRotary::smoothed_velocity`].
+    pub fn smoothed_velocity(&self) -> u16 {
+        self.rotary.smoothed_velocity()
+    }
+
+    pub fn set_velocity_smoothing(&mut self, shift: u8) {
+        self.rotary.set_velocity_smoothing(shift)
+    }
+
+    pub fn update(&mut self, now: T) -> Result<TimeEncoderAction, PinsError<A, B, K>> {
+        let rotation = self.rotary.update(now)? + core::mem::take(&mut self.stuck_rotation);
+        let btn_action = self.button.poll(now)?;
 
         let act = match (self.rotated_on_hold, rotation.is_zero(), btn_action) {
             (false, false, button::TimeButtonAction::None) => TimeEncoderAction::Rotate(rotation),
@@ -201,49 +753,321 @@ pub fn set_acceleration(&mut self, acc: u16) {
                 TimeEncoderAction::RotatePressed(rotation)
             },
             (false, true, button::TimeButtonAction::Held(t)) => TimeEncoderAction::Held(t),
-            (true, false, button::TimeButtonAction::Held(t)) => {
+            (true, false, button::TimeButtonAction::Held(_)) => {
                 TimeEncoderAction::RotatePressed(rotation)
             },
             (true, true, button::TimeButtonAction::Held(_)) => TimeEncoderAction::None,
 
+            (false, false, button::TimeButtonAction::LongPress(_)) => {
+                self.rotated_on_hold = true;
+                TimeEncoderAction::RotatePressed(rotation)
+            },
+            (false, true, button::TimeButtonAction::LongPress(t)) => TimeEncoderAction::LongPress(t),
+            (true, false, button::TimeButtonAction::LongPress(_)) => TimeEncoderAction::RotatePressed(rotation),
+            (true, true, button::TimeButtonAction::LongPress(_)) => TimeEncoderAction::None,
+
+            (false, false, button::TimeButtonAction::Repeat(_)) => {
+                self.rotated_on_hold = true;
+                TimeEncoderAction::RotatePressed(rotation)
+            },
+            (false, true, button::TimeButtonAction::Repeat(n)) => TimeEncoderAction::Repeat(n),
+            (true, false, button::TimeButtonAction::Repeat(_)) => TimeEncoderAction::RotatePressed(rotation),
+            (true, true, button::TimeButtonAction::Repeat(_)) => TimeEncoderAction::None,
+
+            (false, false, button::TimeButtonAction::HoldMilestone(..)) => {
+                self.rotated_on_hold = true;
+                TimeEncoderAction::RotatePressed(rotation)
+            },
+            (false, true, button::TimeButtonAction::HoldMilestone(i, t)) => TimeEncoderAction::HoldMilestone(i, t),
+            (true, false, button::TimeButtonAction::HoldMilestone(..)) => TimeEncoderAction::RotatePressed(rotation),
+            (true, true, button::TimeButtonAction::HoldMilestone(..)) => TimeEncoderAction::None,
+
             (false, false, button::TimeButtonAction::Click(t)) => TimeEncoderAction::Click(t),
             (false, true, button::TimeButtonAction::Click(t)) => TimeEncoderAction::Click(t),
-            (true, false, button::TimeButtonAction::Click(_)) => {
+            (true, false, button::TimeButtonAction::Click(t)) => {
                 self.rotated_on_hold = false;
-                TimeEncoderAction::None
+                TimeEncoderAction::Release(t)
             },
-            (true, true, button::TimeButtonAction::Click(_)) => {
+            (true, true, button::TimeButtonAction::Click(t)) => {
                 self.rotated_on_hold = false;
-                TimeEncoderAction::None
+                TimeEncoderAction::Release(t)
+            },
+
+            (_, _, button::TimeButtonAction::Release(t)) => {
+                self.rotated_on_hold = false;
+                TimeEncoderAction::Release(t)
+            },
+
+            (false, _, button::TimeButtonAction::DoubleClick(t)) => TimeEncoderAction::DoubleClick(t),
+            (true, _, button::TimeButtonAction::DoubleClick(t)) => {
+                self.rotated_on_hold = false;
+                TimeEncoderAction::Release(t)
+            },
+
+            (_, _, button::TimeButtonAction::Stuck(t)) => {
+                self.rotated_on_hold = false;
+                self.stuck_rotation = rotation;
+                TimeEncoderAction::Stuck(t)
             },
+
+            (_, _, button::TimeButtonAction::MultiClick { count, last_duration }) =>
+                TimeEncoderAction::MultiClick { count, last_duration },
+
+            (false, _, button::TimeButtonAction::ShortClick(t)) => TimeEncoderAction::ShortClick(t),
+            (false, _, button::TimeButtonAction::LongClick(t)) => TimeEncoderAction::LongClick(t),
+            (true, _, button::TimeButtonAction::ShortClick(t) | button::TimeButtonAction::LongClick(t)) => {
+                self.rotated_on_hold = false;
+                TimeEncoderAction::Release(t)
+            },
+        };
+        let act = match act {
+            TimeEncoderAction::Held(t) if !self.button.report_held(t.into()) => TimeEncoderAction::None,
+            act => act,
+        };
+        let act = match act {
+            TimeEncoderAction::None => self.rotary.take_flick().map_or(act, TimeEncoderAction::Flick),
+            act => act,
         };
 
         Ok(act)
     }
+
+    pub fn update_stamped(&mut self, now: T) -> Result<Stamped<TimeEncoderAction, T>, PinsError<A, B, K>> {
+        Ok(Stamped { at: now, action: self.update(now)? })
+    }
 }
 
 // Энкодер с кнопкой
-pub struct ClockEncoder<A, B, K, C, const ROTATION_DIVIDER: i8> where A: InputPin, B: InputPin, K: InputPin, C: Clock {
-    encoder: TimeEncoder<A, B, K, C::Instant, ROTATION_DIVIDER>,
+pub struct ClockEncoder<A, B, K, C, M, G = Linear> where A: ButtonInput, B: ButtonInput, K: ButtonInput, C: Clock {
+    encoder: TimeEncoder<A, B, K, C::Instant, M, G>,
     clock: C,
 }
 
-impl<A, B, K, C, const ROTATION_DIVIDER: i8> ClockEncoder<A, B, K, C, ROTATION_DIVIDER>
+impl<A, B, K, C, M: Steps> ClockEncoder<A, B, K, C, M>
     where
-        A: InputPin,
-        B: InputPin,
-        K: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
+        K: ButtonInput,
         C: Clock,
 {
     pub fn new(a_pin: A, b_pin: B, k_pin: K, clock: C) -> Self {
         Self { encoder: TimeEncoder::new(a_pin, b_pin, k_pin), clock }
     }
 
-    pub fn handle_press(&mut self) {
+    pub fn new_from_pin(a_pin: A, b_pin: B, k_pin: K, clock: C) -> Result<Self, PinsError<A, B, K>> {
+        Ok(Self { encoder: TimeEncoder::new_from_pin(a_pin, b_pin, k_pin)?, clock })
+    }
+
+    pub fn with_key_polarity(a_pin: A, b_pin: B, k_pin: K, clock: C, polarity: Polarity) -> Self {
+        Self { encoder: TimeEncoder::with_key_polarity(a_pin, b_pin, k_pin, polarity), clock }
+    }
+
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.encoder.set_accel_window(slow, fast)
+    }
+}
+
+impl<A, B, K, C, M: Steps, G> ClockEncoder<A, B, K, C, M, G>
+    where
+        A: ButtonInput,
+        B: ButtonInput,
+        K: ButtonInput,
+        C: Clock,
+        G: AccelCurve,
+{
+    pub fn from_parts(encoder: TimeEncoder<A, B, K, C::Instant, M, G>, clock: C) -> Self {
+        Self { encoder, clock }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(self) -> (TimeEncoder<A, B, K, C::Instant, M, G>, C) {
+        (self.encoder, self.clock)
+    }
+
+    pub fn encoder(&mut self) -> &mut TimeEncoder<A, B, K, C::Instant, M, G> {
+        &mut self.encoder
+    }
+
+    /// Same as [`encoder`](Self::encoder), named alike on every clock wrapper.
+    pub fn inner(&mut self) -> &mut TimeEncoder<A, B, K, C::Instant, M, G> {
+        &mut self.encoder
+    }
+
+    pub fn set_acceleration(&mut self, acceleration: u16) {
+        self.encoder.set_acceleration(acceleration)
+    }
+
+    pub fn acceleration(&self) -> u16 {
+        self.encoder.acceleration()
+    }
+
+    /// See [`TimeRotary::set_gain_decay`],
+    /// `shift` divides the gain by a power of two.
+    pub fn set_gain_decay(&mut self, shift: u8) {
+        self.encoder.set_gain_decay(shift)
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.encoder.set_curve(curve)
+    }
+
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.encoder.set_rate_acceleration(window, table)
+    }
+
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.encoder.set_max_step(max_step)
+    }
+
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.encoder.set_reversal_lockout(lockout)
+    }
+
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.encoder.set_min_detent_interval(interval)
+    }
+
+    pub fn set_flick(&mut self, min_detents: u8, window: MillisDurationU32, quiet: MillisDurationU32) {
+        self.encoder.set_flick(min_detents, window, quiet)
+    }
+
+    pub fn clock(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    pub fn set_double_click_window(&mut self, window: MillisDurationU32) {
+        self.encoder.set_double_click_window(window)
+    }
+
+    pub fn set_long_press(&mut self, threshold: MillisDurationU32) {
+        self.encoder.set_long_press(threshold)
+    }
+
+    pub fn set_repeat(&mut self, initial: MillisDurationU32, rate: MillisDurationU32) {
+        self.encoder.set_repeat(initial, rate)
+    }
+
+    pub fn set_held_interval(&mut self, interval: MillisDurationU32) {
+        self.encoder.set_held_interval(interval)
+    }
+
+    pub fn set_hold_delay(&mut self, delay: MillisDurationU32) {
+        self.encoder.set_hold_delay(delay)
+    }
+
+    pub fn set_stuck_timeout(&mut self, timeout: MillisDurationU32) {
+        self.encoder.set_stuck_timeout(timeout)
+    }
+
+    pub fn is_stuck(&self) -> bool {
+        self.encoder.is_stuck()
+    }
+
+    pub fn set_multi_click(&mut self, window: MillisDurationU32, max_click: MillisDurationU32) {
+        self.encoder.set_multi_click(window, max_click)
+    }
+
+    pub fn set_min_press(&mut self, min: MillisDurationU32) {
+        self.encoder.set_min_press(min)
+    }
+
+    pub fn set_click_threshold(&mut self, threshold: MillisDurationU32) {
+        self.encoder.set_click_threshold(threshold)
+    }
+
+    pub fn set_hold_milestones(&mut self, milestones: &[MillisDurationU32]) {
+        self.encoder.set_hold_milestones(milestones)
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.encoder.set_debounce_samples(samples)
+    }
+
+    pub fn key_polarity(&self) -> Polarity {
+        self.encoder.key_polarity()
+    }
+
+    pub fn set_key_polarity(&mut self, polarity: Polarity) {
+        self.encoder.set_key_polarity(polarity)
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.encoder.set_step_mode(step_mode)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.encoder.set_reversed(reversed)
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.encoder.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.encoder.set_double_transition(double)
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.encoder.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.encoder.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.encoder.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.encoder.reset_odometer()
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.encoder.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.encoder.health()
+    }
+
+    pub fn set_rotary_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.encoder.set_rotary_polarity(a, b)
+    }
+
+    pub fn handle_press(&mut self) -> bool {
         self.encoder.handle_press()
     }
 
-    pub fn update(&mut self) -> Result<TimeEncoderAction, EncoderError<A::Error, B::Error, K::Error>> {
+    pub fn reset(&mut self) {
+        self.encoder.reset()
+    }
+
+    pub fn reset_from_pins(&mut self) -> Result<(), PinsError<A, B, K>> {
+        self.encoder.reset_from_pins()
+    }
+
+    pub fn velocity(&mut self) -> u16 {
+        self.encoder.velocity(self.clock.now())
+    }
+
+    pub fn idle_for(&mut self) -> Option<MillisDurationU32> {
+        self.encoder.idle_for(self.clock.now())
+    }
+
+    pub fn smoothed_velocity(&self) -> u16 {
+        self.encoder.smoothed_velocity()
+    }
+
+    pub fn set_velocity_smoothing(&mut self, shift: u8) {
+        self.encoder.set_velocity_smoothing(shift)
+    }
+
+    pub fn update(&mut self) -> Result<TimeEncoderAction, PinsError<A, B, K>> {
         self.encoder.update(self.clock.now())
     }
+
+    pub fn update_stamped(&mut self) -> Result<Stamped<TimeEncoderAction, C::Instant>, PinsError<A, B, K>> {
+        self.encoder.update_stamped(self.clock.now())
+    }
 }
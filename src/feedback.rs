@@ -0,0 +1,201 @@
+use crate::button::{self, Button, ButtonAction, TimeButton, TimeButtonAction};
+use crate::encoder::{Encoder, EncoderAction, EncoderError};
+use crate::rotary::Steps;
+use crate::{ButtonInput, Instant};
+use core::fmt;
+use embedded_hal::digital::v2::OutputPin;
+use fugit::MillisDurationU32;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FeedbackError<E, O> {
+    Device(E),
+    OutPin(O),
+}
+
+impl<E, O> fmt::Display for FeedbackError<E, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedbackError::Device(_) => f.write_str("device pin error"),
+            FeedbackError::OutPin(_) => f.write_str("feedback pin error"),
+        }
+    }
+}
+
+impl<E: fmt::Debug, O: fmt::Debug> core::error::Error for FeedbackError<E, O> {}
+
+type EncoderPinsError<A, B, K, O> = FeedbackError<
+    EncoderError<<A as ButtonInput>::Error, <B as ButtonInput>::Error, <K as ButtonInput>::Error>,
+    <O as OutputPin>::Error,
+>;
+
+// Output held high for `length` updates after the trigger
+struct Pulse<O> {
+    pin: O,
+    length: u16,
+    left: u16,
+}
+
+impl<O: OutputPin> Pulse<O> {
+    fn new(pin: O, length: u16) -> Self {
+        Self { pin, length, left: 0 }
+    }
+
+    fn update(&mut self, trigger: bool) -> Result<(), O::Error> {
+        if trigger && self.length != 0 {
+            if self.left == 0 {
+                self.pin.set_high()?;
+            }
+            self.left = self.length;
+        } else if self.left != 0 {
+            self.left -= 1;
+            if self.left == 0 {
+                self.pin.set_low()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Output held high for `length` after the last trigger
+struct TimePulse<O, T> {
+    pin: O,
+    length: MillisDurationU32,
+    started_at: Option<T>,
+}
+
+impl<O: OutputPin, T: Instant> TimePulse<O, T> {
+    fn update(&mut self, now: T, trigger: bool) -> Result<(), O::Error> {
+        if trigger && self.length.ticks() != 0 {
+            if self.started_at.is_none() {
+                self.pin.set_high()?;
+            }
+            self.started_at = Some(now);
+        } else if let Some(at) = self.started_at {
+            if now.duration_since(at) >= self.length {
+                self.started_at = None;
+                self.pin.set_low()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Button driving an output high for `pulse` updates after every `Click`.
+/// Actions are passed through unchanged.
+pub struct FeedbackButton<K, O, const INVERTED: bool = false>
+where
+    K: ButtonInput,
+    O: OutputPin,
+{
+    button: Button<K, INVERTED>,
+    pulse: Pulse<O>,
+}
+
+impl<K, O, const INVERTED: bool> FeedbackButton<K, O, INVERTED>
+where
+    K: ButtonInput,
+    O: OutputPin,
+{
+    /// The output is expected to be low. Zero `pulse` disables feedback.
+    pub fn new(button: Button<K, INVERTED>, out_pin: O, pulse: u16) -> Self {
+        Self { button, pulse: Pulse::new(out_pin, pulse) }
+    }
+
+    pub fn button(&mut self) -> &mut Button<K, INVERTED> {
+        &mut self.button
+    }
+
+    pub fn into_parts(self) -> (Button<K, INVERTED>, O) {
+        (self.button, self.pulse.pin)
+    }
+
+    pub fn update(&mut self) -> Result<ButtonAction, FeedbackError<button::Error<K::Error>, O::Error>> {
+        let act = self.button.update().map_err(FeedbackError::Device)?;
+        self.pulse.update(act.is_click()).map_err(FeedbackError::OutPin)?;
+        Ok(act)
+    }
+}
+
+/// Time button driving an output high for `pulse` after every click.
+/// Actions are passed through unchanged.
+pub struct TimeFeedbackButton<K, O, T, const INVERTED: bool = false>
+where
+    K: ButtonInput,
+    O: OutputPin,
+    T: Instant,
+{
+    button: TimeButton<K, T, INVERTED>,
+    pulse: TimePulse<O, T>,
+}
+
+impl<K, O, T, const INVERTED: bool> TimeFeedbackButton<K, O, T, INVERTED>
+where
+    K: ButtonInput,
+    O: OutputPin,
+    T: Instant,
+{
+    /// The output is expected to be low. Zero `pulse` disables feedback.
+    pub fn new(button: TimeButton<K, T, INVERTED>, out_pin: O, pulse: MillisDurationU32) -> Self {
+        Self {
+            button,
+            pulse: TimePulse { pin: out_pin, length: pulse, started_at: None },
+        }
+    }
+
+    pub fn button(&mut self) -> &mut TimeButton<K, T, INVERTED> {
+        &mut self.button
+    }
+
+    pub fn into_parts(self) -> (TimeButton<K, T, INVERTED>, O) {
+        (self.button, self.pulse.pin)
+    }
+
+    pub fn update(&mut self, now: T) -> Result<TimeButtonAction, FeedbackError<button::Error<K::Error>, O::Error>> {
+        let act = self.button.update(now).map_err(FeedbackError::Device)?;
+        let trigger = act.is_click()
+            || matches!(act, TimeButtonAction::DoubleClick(_) | TimeButtonAction::MultiClick { .. });
+        self.pulse.update(now, trigger).map_err(FeedbackError::OutPin)?;
+        Ok(act)
+    }
+}
+
+/// Encoder driving an output high for `pulse` updates after every `Click` and every detent.
+/// Actions are passed through unchanged.
+pub struct FeedbackEncoder<A, B, K, O, M>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    K: ButtonInput,
+    O: OutputPin,
+{
+    encoder: Encoder<A, B, K, M>,
+    pulse: Pulse<O>,
+}
+
+impl<A, B, K, O, M: Steps> FeedbackEncoder<A, B, K, O, M>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    K: ButtonInput,
+    O: OutputPin,
+{
+    /// The output is expected to be low. Zero `pulse` disables feedback.
+    pub fn new(encoder: Encoder<A, B, K, M>, out_pin: O, pulse: u16) -> Self {
+        Self { encoder, pulse: Pulse::new(out_pin, pulse) }
+    }
+
+    pub fn encoder(&mut self) -> &mut Encoder<A, B, K, M> {
+        &mut self.encoder
+    }
+
+    pub fn into_parts(self) -> (Encoder<A, B, K, M>, O) {
+        (self.encoder, self.pulse.pin)
+    }
+
+    pub fn update(&mut self) -> Result<EncoderAction, EncoderPinsError<A, B, K, O>> {
+        let act = self.encoder.update().map_err(FeedbackError::Device)?;
+        let trigger = act.is_click() || act.rotation().is_some();
+        self.pulse.update(trigger).map_err(FeedbackError::OutPin)?;
+        Ok(act)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::button::{self, EdgeButton, Edge};
+use crate::input::PinKind;
+use crate::rotary::{FullStep, PinRotary, RotaryError, Rotation, Steps};
+use crate::ButtonInput;
+use core::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RotaryIndexError<A, B, Z> {
+    APin(A),
+    BPin(B),
+    ZPin(Z),
+}
+
+impl<A, B, Z> RotaryIndexError<A, B, Z> {
+    pub fn into_kind(self) -> PinKind {
+        match self {
+            RotaryIndexError::APin(_) => PinKind::A,
+            RotaryIndexError::BPin(_) => PinKind::B,
+            RotaryIndexError::ZPin(_) => PinKind::Z,
+        }
+    }
+}
+
+impl<A, B, Z> From<RotaryError<A, B>> for RotaryIndexError<A, B, Z> {
+    fn from(err: RotaryError<A, B>) -> Self {
+        match err {
+            RotaryError::APin(e) => RotaryIndexError::APin(e),
+            RotaryError::BPin(e) => RotaryIndexError::BPin(e),
+        }
+    }
+}
+
+impl<A, B, Z> fmt::Display for RotaryIndexError<A, B, Z> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotaryIndexError::APin(_) => f.write_str("rotary A pin error"),
+            RotaryIndexError::BPin(_) => f.write_str("rotary B pin error"),
+            RotaryIndexError::ZPin(_) => f.write_str("rotary index pin error"),
+        }
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug, Z: fmt::Debug> core::error::Error for RotaryIndexError<A, B, Z> {}
+
+/// Rotation together with whether the index pulse started at the same update.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IndexedRotation {
+    pub rotation: Rotation,
+    pub index: bool,
+}
+
+/// [`Rotary`](crate::rotary::Rotary) with an index (Z) channel pulsing once per revolution.
+/// The pulse is read through an [`EdgeButton`], which sets its polarity and debouncing.
+pub struct RotaryWithIndex<A, B, Z, M = FullStep, const INVERTED: bool = false>
+where
+    Z: ButtonInput,
+{
+    rotary: PinRotary<A, B, M>,
+    index: EdgeButton<Z, INVERTED>,
+}
+
+pub(crate) type IndexError<A, B, Z> =
+    RotaryIndexError<<A as ButtonInput>::Error, <B as ButtonInput>::Error, <Z as ButtonInput>::Error>;
+
+impl<A, B, Z, M: Steps, const INVERTED: bool> RotaryWithIndex<A, B, Z, M, INVERTED>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    Z: ButtonInput,
+{
+    pub fn new(rotary: PinRotary<A, B, M>, index: EdgeButton<Z, INVERTED>) -> Self {
+        Self { rotary, index }
+    }
+
+    pub fn rotary(&mut self) -> &mut PinRotary<A, B, M> {
+        &mut self.rotary
+    }
+
+    pub fn index(&mut self) -> &mut EdgeButton<Z, INVERTED> {
+        &mut self.index
+    }
+
+    pub fn into_parts(self) -> (PinRotary<A, B, M>, EdgeButton<Z, INVERTED>) {
+        (self.rotary, self.index)
+    }
+
+    pub fn update(&mut self) -> Result<IndexedRotation, IndexError<A, B, Z>> {
+        let rotation = self.rotary.update()?;
+        let edge = self.index.update().map_err(|button::Error::KPin(e)| RotaryIndexError::ZPin(e))?;
+        Ok(IndexedRotation { rotation, index: edge == Edge::Pressed })
+    }
+}
@@ -0,0 +1,102 @@
+use crate::rotary::RotaryError;
+use embedded_hal::digital::v2::InputPin;
+
+/// Digital input read by buttons and rotaries. Unlike [`InputPin`] it takes `&mut self`,
+/// so it can be implemented for lines behind an I/O expander or a shift register.
+pub trait ButtonInput {
+    type Error;
+
+    /// `true` when the line is high. Polarity is applied by the reader.
+    fn is_high(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<P> ButtonInput for P
+where
+    P: InputPin,
+{
+    type Error = P::Error;
+
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        InputPin::is_high(self)
+    }
+}
+
+/// Adapts a `switch-hal` [`InputSwitch`](switch_hal::InputSwitch): active reads as high,
+/// wiring polarity is handled by the switch itself.
+#[cfg(feature = "switch-hal")]
+pub struct SwitchInput<S>(pub S);
+
+#[cfg(feature = "switch-hal")]
+impl<S> ButtonInput for SwitchInput<S>
+where
+    S: switch_hal::InputSwitch,
+{
+    type Error = S::Error;
+
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_active()
+    }
+}
+
+/// A and B lines of a rotary sampled together, e.g. by a single read of the GPIO port
+/// both are on, so a transition cannot fall between the two reads.
+pub trait QuadratureInput {
+    type Error;
+
+    /// Levels of A and B, `true` when the line is high. Polarity is applied by the reader.
+    fn read(&mut self) -> Result<(bool, bool), Self::Error>;
+}
+
+/// Two separately read lines, A first.
+impl<A, B> QuadratureInput for (A, B)
+where
+    A: ButtonInput,
+    B: ButtonInput,
+{
+    type Error = RotaryError<A::Error, B::Error>;
+
+    #[inline]
+    fn read(&mut self) -> Result<(bool, bool), Self::Error> {
+        let a = self.0.is_high().map_err(RotaryError::APin)?;
+        let b = self.1.is_high().map_err(RotaryError::BPin)?;
+        Ok((a, b))
+    }
+}
+
+/// Which line of a button or encoder failed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PinKind {
+    A,
+    B,
+    Key,
+    /// Index (Z) channel of a [`RotaryWithIndex`](crate::index::RotaryWithIndex).
+    Z,
+}
+
+/// Borrowed input, so a device can use a pin for a scope and give it back afterwards,
+/// e.g. `Rotary::new(PinRef(&mut a), PinRef(&mut b))`. A blanket impl for `&mut P`
+/// would conflict with the one for [`InputPin`].
+pub struct PinRef<'a, P: ?Sized>(pub &'a mut P);
+
+impl<P: ButtonInput + ?Sized> ButtonInput for PinRef<'_, P> {
+    type Error = P::Error;
+
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_high()
+    }
+}
+
+// Placeholder input of a button fed only through `update_with_level`
+pub(crate) struct NoPin;
+
+impl ButtonInput for NoPin {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
@@ -4,8 +4,29 @@ pub mod encoder;
 mod time;
 pub mod rotary;
 pub mod button;
-mod internal;
+pub mod input;
+pub mod debounce;
+pub mod feedback;
+pub mod position;
+pub mod index;
+pub mod counter;
+pub mod scale;
+pub mod shared;
+pub mod stepdir;
+pub mod absolute;
+pub mod calibrate;
+pub mod dual;
+// Native compare and swap, or the critical section or single core fallback of portable-atomic
+#[cfg(any(target_has_atomic = "16", feature = "critical-section", portable_atomic_unsafe_assume_single_core))]
+pub mod atomic;
+#[cfg(feature = "async")]
+pub mod asynch;
+#[cfg(feature = "critical-section")]
+pub mod sync;
+#[cfg(feature = "sim")]
+pub mod sim;
 
 pub use self::{
-    time::{Instant, Clock},
+    time::{Instant, Clock, ZeroInstant, ZeroClock, Stamped},
+    input::ButtonInput,
 };
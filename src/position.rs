@@ -0,0 +1,304 @@
+use crate::index::{IndexError, IndexedRotation, RotaryWithIndex};
+use crate::rotary::{AccelCurve, FullStep, Linear, PinRotary, RotaryError, Rotation, Steps, TimeRotary};
+use crate::{ButtonInput, Instant};
+
+/// Limit that absorbed (part of) a rotation.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum EndStop {
+    #[default]
+    None,
+    Min,
+    Max,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+enum Mode {
+    #[default]
+    Free,
+    Wrap(u16),
+    Clamp(i32, i32),
+}
+
+/// Absolute position summed from rotations. Updates take `&mut self`, so a position
+/// updated from an interrupt has to be shared through a critical section.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Position {
+    value: i32,
+    delta: i32,
+    mode: Mode,
+    wraps: i32,
+    end_stop: EndStop,
+}
+
+impl Position {
+    pub fn new(value: i32) -> Self {
+        Self { value, ..Self::default() }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    /// Moves to `value` without counting it as a change for [`take_delta`](Self::take_delta).
+    /// Wrapped into range when wrapping is enabled.
+    pub fn set(&mut self, value: i32) {
+        self.value = match self.mode {
+            Mode::Free => value,
+            Mode::Wrap(n) => value.rem_euclid(n as i32),
+            Mode::Clamp(min, max) => value.clamp(min, max),
+        };
+    }
+
+    /// Keeps the position in `[0, modulus)`, multi-step rotations wrap as often as needed.
+    /// The current position is re-mapped modulo the new value and the wrap count cleared.
+    /// Zero disables wrapping. Replaces any range set by [`set_range`](Self::set_range).
+    pub fn set_wrap(&mut self, modulus: u16) {
+        self.mode = match modulus {
+            0 => Mode::Free,
+            n => Mode::Wrap(n),
+        };
+        self.wraps = 0;
+        self.set(self.value);
+    }
+
+    /// Clamps the position to `[min, max]`, the current position included.
+    /// Replaces wrapping set by [`set_wrap`](Self::set_wrap).
+    pub fn set_range(&mut self, min: i32, max: i32) {
+        self.mode = Mode::Clamp(min.min(max), max.max(min));
+        self.set(self.value);
+    }
+
+    /// Removes the range or wrapping, the position is kept.
+    pub fn clear_range(&mut self) {
+        self.mode = Mode::Free;
+    }
+
+    /// End stop hit by the last update.
+    pub fn end_stop(&self) -> EndStop {
+        self.end_stop
+    }
+
+    /// Net count of wraps, positive past the top of the range, negative below zero.
+    pub fn wraps(&self) -> i32 {
+        self.wraps
+    }
+
+    /// Change since the previous call.
+    pub fn take_delta(&mut self) -> i32 {
+        core::mem::take(&mut self.delta)
+    }
+
+    /// Applies a rotation and returns the applied delta, truncated at an end stop when
+    /// clamped, along with the stop that absorbed the rest.
+    pub fn update(&mut self, rot: Rotation) -> (i32, EndStop) {
+        let angle = rot.angle();
+        let (value, applied, end_stop) = match self.mode {
+            Mode::Free => {
+                let value = self.value.saturating_add(angle);
+                (value, value - self.value, EndStop::None)
+            }
+            Mode::Wrap(n) => {
+                let total = self.value as i64 + angle as i64;
+                self.wraps = self.wraps.saturating_add(total.div_euclid(n as i64) as i32);
+                (total.rem_euclid(n as i64) as i32, angle, EndStop::None)
+            }
+            Mode::Clamp(min, max) => {
+                let value = self.value.saturating_add(angle);
+                let end_stop = if value < min {
+                    EndStop::Min
+                } else if value > max {
+                    EndStop::Max
+                } else {
+                    EndStop::None
+                };
+                let value = value.clamp(min, max);
+                (value, value - self.value, end_stop)
+            }
+        };
+        self.value = value;
+        self.delta = self.delta.saturating_add(applied);
+        self.end_stop = end_stop;
+        (applied, end_stop)
+    }
+}
+
+/// [`Rotary`](crate::rotary::Rotary) keeping an absolute position.
+pub struct PositionRotary<A, B, M = FullStep> {
+    rotary: PinRotary<A, B, M>,
+    position: Position,
+}
+
+impl<A, B, M: Steps> PositionRotary<A, B, M>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+{
+    pub fn new(rotary: PinRotary<A, B, M>) -> Self {
+        Self { rotary, position: Position::default() }
+    }
+
+    pub fn rotary(&mut self) -> &mut PinRotary<A, B, M> {
+        &mut self.rotary
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position.value()
+    }
+
+    pub fn set_position(&mut self, position: i32) {
+        self.position.set(position)
+    }
+
+    pub fn take_delta(&mut self) -> i32 {
+        self.position.take_delta()
+    }
+
+    pub fn set_wrap(&mut self, modulus: u16) {
+        self.position.set_wrap(modulus)
+    }
+
+    pub fn wraps(&self) -> i32 {
+        self.position.wraps()
+    }
+
+    pub fn set_range(&mut self, min: i32, max: i32) {
+        self.position.set_range(min, max)
+    }
+
+    pub fn clear_range(&mut self) {
+        self.position.clear_range()
+    }
+
+    pub fn end_stop(&self) -> EndStop {
+        self.position.end_stop()
+    }
+
+    pub fn update(&mut self) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
+        let rot = self.rotary.update()?;
+        self.position.update(rot);
+        Ok(rot)
+    }
+}
+
+/// [`TimeRotary`] keeping an absolute position, accelerated rotations included.
+pub struct TimePositionRotary<A, B, T, M = FullStep, G = Linear>
+where
+    T: Instant,
+{
+    rotary: TimeRotary<A, B, T, M, G>,
+    position: Position,
+}
+
+impl<A, B, T, M: Steps, G> TimePositionRotary<A, B, T, M, G>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    T: Instant,
+    G: AccelCurve,
+{
+    pub fn new(rotary: TimeRotary<A, B, T, M, G>) -> Self {
+        Self { rotary, position: Position::default() }
+    }
+
+    pub fn rotary(&mut self) -> &mut TimeRotary<A, B, T, M, G> {
+        &mut self.rotary
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position.value()
+    }
+
+    pub fn set_position(&mut self, position: i32) {
+        self.position.set(position)
+    }
+
+    pub fn take_delta(&mut self) -> i32 {
+        self.position.take_delta()
+    }
+
+    pub fn set_wrap(&mut self, modulus: u16) {
+        self.position.set_wrap(modulus)
+    }
+
+    pub fn wraps(&self) -> i32 {
+        self.position.wraps()
+    }
+
+    pub fn set_range(&mut self, min: i32, max: i32) {
+        self.position.set_range(min, max)
+    }
+
+    pub fn clear_range(&mut self) {
+        self.position.clear_range()
+    }
+
+    pub fn end_stop(&self) -> EndStop {
+        self.position.end_stop()
+    }
+
+    pub fn update(&mut self, now: T) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
+        let rot = self.rotary.update(now)?;
+        self.position.update(rot);
+        Ok(rot)
+    }
+}
+
+/// [`RotaryWithIndex`] keeping an absolute position, moved to `index_position` whenever
+/// the index pulse starts. Drift accumulated over the revolution is dropped, not counted
+/// in the delta.
+pub struct IndexPositionRotary<A, B, Z, M = FullStep, const INVERTED: bool = false>
+where
+    Z: ButtonInput,
+{
+    rotary: RotaryWithIndex<A, B, Z, M, INVERTED>,
+    position: Position,
+    index_position: i32,
+}
+
+impl<A, B, Z, M: Steps, const INVERTED: bool> IndexPositionRotary<A, B, Z, M, INVERTED>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    Z: ButtonInput,
+{
+    pub fn new(rotary: RotaryWithIndex<A, B, Z, M, INVERTED>, index_position: i32) -> Self {
+        Self { rotary, position: Position::default(), index_position }
+    }
+
+    pub fn rotary(&mut self) -> &mut RotaryWithIndex<A, B, Z, M, INVERTED> {
+        &mut self.rotary
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position.value()
+    }
+
+    pub fn set_position(&mut self, position: i32) {
+        self.position.set(position)
+    }
+
+    pub fn set_index_position(&mut self, position: i32) {
+        self.index_position = position;
+    }
+
+    pub fn take_delta(&mut self) -> i32 {
+        self.position.take_delta()
+    }
+
+    pub fn set_wrap(&mut self, modulus: u16) {
+        self.position.set_wrap(modulus)
+    }
+
+    pub fn wraps(&self) -> i32 {
+        self.position.wraps()
+    }
+
+    pub fn update(&mut self) -> Result<IndexedRotation, IndexError<A, B, Z>> {
+        let step = self.rotary.update()?;
+        self.position.update(step.rotation);
+        if step.index {
+            self.position.set(self.index_position);
+        }
+        Ok(step)
+    }
+}
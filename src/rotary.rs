@@ -1,197 +1,1733 @@
-use crate::time::{Clock, Instant};
-use embedded_hal::digital::v2::InputPin;
+use crate::time::{Clock, Instant, StartupGrace};
+use crate::input::{PinKind, QuadratureInput};
+use crate::button::Polarity;
+use crate::debounce::Debounced;
+use crate::ButtonInput;
+use core::{fmt, ops};
+use core::marker::PhantomData;
+use fugit::MillisDurationU32;
 
 const SINGLE_ROTATION_MS: u32 = 100;
 const LIMITED_ROTATION_MS: u32 = 20;
+// Pause ending a gain decay ramp, ten gaps slow enough for single steps on the default curve
+const DECAY_PAUSE_MS: u32 = 10 * SINGLE_ROTATION_MS;
+// Largest clockwise and counter-clockwise step counts of a rotation
+const MAX_STEPS: u32 = Angle::MAX as u32;
+const MIN_STEPS: u32 = MAX_STEPS + 1;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+// Gain or step limit as an angle, saturating at `i16::MAX` with `narrow`
+fn gain_angle(gain: u16) -> Angle {
+    #[cfg(feature = "narrow")]
+    let gain = gain.min(Angle::MAX as u16);
+    gain as Angle
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
 pub enum Direction {
+    #[default]
     None,
     Cw,
     Ccw,
 }
 
 impl Direction {
-    pub fn to_rotation(self) -> Rotation {
+    /// Direction of a signed count, positive is clockwise.
+    ///
+    /// ```
+    /// # use simple_encoder::rotary::Direction;
+    /// assert_eq!(Direction::from_sign(-7), Direction::Ccw);
+    /// assert_eq!(Direction::from_sign(0), Direction::None);
+    /// ```
+    pub const fn from_sign(sign: i32) -> Self {
+        match sign {
+            0 => Direction::None,
+            1..=i32::MAX => Direction::Cw,
+            i32::MIN..=-1 => Direction::Ccw,
+        }
+    }
+
+    pub const fn to_rotation(self) -> Rotation {
         match self {
             Direction::None => Rotation(0),
-            Direction::Cw => Rotation(1),
-            Direction::Ccw => Rotation(-1),
+            Direction::Cw => Rotation::CW_STEP,
+            Direction::Ccw => Rotation::CCW_STEP,
+        }
+    }
+
+    /// ```
+    /// # use simple_encoder::rotary::Direction;
+    /// assert!(Direction::Cw.is_cw());
+    /// assert!(!Direction::None.is_cw());
+    /// ```
+    pub const fn is_cw(self) -> bool {
+        matches!(self, Direction::Cw)
+    }
+
+    /// ```
+    /// # use simple_encoder::rotary::Direction;
+    /// assert!(Direction::Ccw.is_ccw());
+    /// assert!(!Direction::Cw.is_ccw());
+    /// ```
+    pub const fn is_ccw(self) -> bool {
+        matches!(self, Direction::Ccw)
+    }
+
+    /// ```
+    /// # use simple_encoder::rotary::Direction;
+    /// assert!(Direction::default().is_none());
+    /// assert!(!Direction::Ccw.is_none());
+    /// ```
+    pub const fn is_none(self) -> bool {
+        matches!(self, Direction::None)
+    }
+
+    /// ```
+    /// # use simple_encoder::rotary::Direction;
+    /// assert_eq!(Direction::Cw.reversed(), Direction::Ccw);
+    /// assert_eq!(Direction::None.reversed(), Direction::None);
+    /// ```
+    pub const fn reversed(self) -> Self {
+        match self {
+            Direction::None => Direction::None,
+            Direction::Cw => Direction::Ccw,
+            Direction::Ccw => Direction::Cw,
+        }
+    }
+}
+
+impl From<Direction> for Rotation {
+    fn from(dir: Direction) -> Self {
+        dir.to_rotation()
+    }
+}
+
+/// One for clockwise, minus one for counter-clockwise.
+///
+/// ```
+/// # use simple_encoder::rotary::Direction;
+/// assert_eq!(i32::from(Direction::Ccw), -1);
+/// assert_eq!(i8::from(Direction::Cw), 1);
+/// ```
+impl From<Direction> for i32 {
+    fn from(dir: Direction) -> Self {
+        dir.to_rotation().angle()
+    }
+}
+
+impl From<Direction> for i8 {
+    fn from(dir: Direction) -> Self {
+        dir.to_rotation().0 as i8
+    }
+}
+
+// Storage of a rotation. The `narrow` feature keeps rotations and acceleration in 16 bit
+// math for 8-bit cores, the API stays `i32`. Sizes to watch on AVR: with it, `Linear`,
+// the smoothed velocity and `velocity` divide in 16 bits, so the acceleration path no
+// longer links the 32 bit division, and detents are scaled by a 16 bit multiply. Instants
+// and the velocity filter stay 32 bit adds and shifts
+#[cfg(not(feature = "narrow"))]
+type Angle = i32;
+#[cfg(feature = "narrow")]
+type Angle = i16;
+
+/// Signed rotation in steps, positive is clockwise. Arithmetic saturates at the `i32` range,
+/// or the `i16` range with the `narrow` feature, ordering is by angle.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[repr(transparent)]
+pub struct Rotation(Angle);
+
+impl Rotation {
+    pub const ZERO: Rotation = Rotation(0);
+    pub const CW_STEP: Rotation = Rotation(1);
+    pub const CCW_STEP: Rotation = Rotation(-1);
+
+    /// Saturates at the `i16` range with the `narrow` feature.
+    #[cfg(not(feature = "narrow"))]
+    pub const fn new(angle: i32) -> Self {
+        Self(angle)
+    }
+
+    /// Saturates at the `i16` range with the `narrow` feature.
+    #[cfg(feature = "narrow")]
+    pub const fn new(angle: i32) -> Self {
+        match angle {
+            ..=-0x8000 => Self(i16::MIN),
+            0x7fff.. => Self(i16::MAX),
+            angle => Self(angle as i16),
+        }
+    }
+
+    /// `steps` clockwise, saturating at `i32::MAX`, or `i16::MAX` with `narrow`.
+    ///
+    /// ```
+    /// # use simple_encoder::rotary::{Direction, Rotation};
+    /// assert_eq!(Rotation::cw(3).direction(), Direction::Cw);
+    /// assert_eq!(Rotation::from(Direction::Cw), Rotation::cw(1));
+    /// ```
+    pub const fn cw(steps: u32) -> Self {
+        match steps {
+            0..=MAX_STEPS => Self(steps as Angle),
+            _ => Self(Angle::MAX),
+        }
+    }
+
+    /// `steps` counter-clockwise, saturating at `i32::MIN`, or `i16::MIN` with `narrow`.
+    ///
+    /// ```
+    /// # use simple_encoder::rotary::{Direction, Rotation};
+    /// assert_eq!(Rotation::ccw(3).direction(), Direction::Ccw);
+    /// assert_eq!(Rotation::ccw(2).direction().reversed().to_rotation(), Rotation::cw(1));
+    /// ```
+    pub const fn ccw(steps: u32) -> Self {
+        match steps {
+            0..=MIN_STEPS => Self((steps as Angle).wrapping_neg()),
+            _ => Self(Angle::MIN),
+        }
+    }
+
+    #[inline]
+    #[allow(clippy::unnecessary_cast)] // `Angle` is `i32` without `narrow`
+    pub const fn direction(self) -> Direction {
+        Direction::from_sign(self.0 as i32)
+    }
+
+    #[inline(always)]
+    #[allow(clippy::unnecessary_cast)]
+    pub const fn angle(self) -> i32 {
+        self.0 as i32
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+}
+
+impl ops::Add for Rotation {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl ops::AddAssign for Rotation {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::Sub for Rotation {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl ops::SubAssign for Rotation {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl ops::Neg for Rotation {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl ops::Mul<i32> for Rotation {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        let rhs = Angle::try_from(rhs).unwrap_or(if rhs < 0 { Angle::MIN } else { Angle::MAX });
+        Self(self.0.saturating_mul(rhs))
+    }
+}
+
+impl core::iter::Sum for Rotation {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Rotation::ZERO, |sum, rot| sum + rot)
+    }
+}
+
+impl<'a> core::iter::Sum<&'a Rotation> for Rotation {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RotaryError<A, B> {
+    APin(A),
+    BPin(B),
+}
+
+impl<A, B> RotaryError<A, B> {
+    pub fn into_kind(self) -> PinKind {
+        match self {
+            RotaryError::APin(_) => PinKind::A,
+            RotaryError::BPin(_) => PinKind::B,
+        }
+    }
+
+    pub fn map_a<E>(self, f: impl FnOnce(A) -> E) -> RotaryError<E, B> {
+        match self {
+            RotaryError::APin(e) => RotaryError::APin(f(e)),
+            RotaryError::BPin(e) => RotaryError::BPin(e),
+        }
+    }
+
+    pub fn map_b<E>(self, f: impl FnOnce(B) -> E) -> RotaryError<A, E> {
+        match self {
+            RotaryError::APin(e) => RotaryError::APin(e),
+            RotaryError::BPin(e) => RotaryError::BPin(f(e)),
+        }
+    }
+}
+
+impl<E> RotaryError<E, E> {
+    /// Error of whichever pin failed, for pins sharing an error type.
+    pub fn into_inner(self) -> E {
+        match self {
+            RotaryError::APin(e) | RotaryError::BPin(e) => e,
+        }
+    }
+}
+
+impl<A, B> fmt::Display for RotaryError<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotaryError::APin(_) => f.write_str("rotary A pin error"),
+            RotaryError::BPin(_) => f.write_str("rotary B pin error"),
+        }
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> core::error::Error for RotaryError<A, B> {}
+
+// Direction of every 4-bit pin history, current levels in the high bits. `REST` marks
+// staying at or jumping back to `00` or `11`, `DOUBLE` both lines changing between `01`
+// and `10`. Kept in flash, one load replaces the chain of compares the match over states
+// compiled to, which shows in fast interrupts on M0: by the instruction timings the lookup
+// is an address add and a 2 cycle `ldrb`, where the last arms took ten compare and branch
+// pairs, close to 20 cycles. `tests/decoder.rs` checks it against the match.
+const REST: i8 = i8::MIN;
+const DOUBLE: i8 = i8::MAX;
+static TRANSITIONS: [i8; 16] = [
+    REST, -1, 1, REST,
+    1, 0, DOUBLE, -1,
+    -1, DOUBLE, 0, 1,
+    REST, 1, -1, REST,
+];
+
+/// Handling of a sample where both lines changed between `01` and `10`, meaning the
+/// intermediate state fell between two samples. Either way it counts as an error.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum DoubleTransition {
+    /// Reports nothing. Sampling too slowly loses steps, but never reverses.
+    #[default]
+    Ignore,
+    /// Counts two transitions in the direction of the previous one. Keeps the count when
+    /// the encoder only outruns the sampling, but a reversal within the missed state is
+    /// counted the wrong way.
+    Infer,
+}
+
+/// Quadrature transitions per reported step.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StepMode {
+    /// One step per full cycle, detents at `00` only.
+    Full,
+    /// One step per half cycle, detents at `00` and `11`.
+    Half,
+    /// Raw mode for encoders without detents: every valid transition is a step reported
+    /// immediately, resting states report nothing. Other modes flush a partial detent
+    /// as one step when the encoder comes to rest.
+    Quarter,
+    /// Step per this many transitions, detents at `00` only.
+    Custom(i8),
+}
+
+impl StepMode {
+    pub fn transitions(self) -> i8 {
+        match self {
+            StepMode::Full => 4,
+            StepMode::Half => 2,
+            StepMode::Quarter => 1,
+            StepMode::Custom(n) => n.saturating_abs().max(1),
+        }
+    }
+
+    /// Mode for a legacy `ROTATION_DIVIDER` value.
+    pub const fn from_divider(divider: i8) -> Self {
+        match divider {
+            4 => StepMode::Full,
+            2 => StepMode::Half,
+            1 => StepMode::Quarter,
+            n => StepMode::Custom(n),
+        }
+    }
+}
+
+/// [`StepMode`] chosen by type, the `M` parameter of [`Rotary`] and the types built on it.
+/// It only sets the initial mode, `set_step_mode` changes it at runtime.
+pub trait Steps {
+    const MODE: StepMode;
+}
+
+/// [`StepMode::Full`], the default.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct FullStep;
+
+/// [`StepMode::Half`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct HalfStep;
+
+/// [`StepMode::Quarter`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct QuarterStep;
+
+impl Steps for FullStep {
+    const MODE: StepMode = StepMode::Full;
+}
+
+impl Steps for HalfStep {
+    const MODE: StepMode = StepMode::Half;
+}
+
+impl Steps for QuarterStep {
+    const MODE: StepMode = StepMode::Quarter;
+}
+
+/// The former `const ROTATION_DIVIDER: i8` parameter, kept for one release. `Divider<4>`,
+/// `Divider<2>` and `Divider<1>` become [`FullStep`], [`HalfStep`] and [`QuarterStep`], so
+/// `Rotary<A, B, 2>` is now `PinRotary<A, B, HalfStep>`. Other counts fail to build, they are
+/// set with [`StepMode::Custom`] at runtime:
+///
+/// ```compile_fail
+/// # #![allow(deprecated)]
+/// let decoder = simple_encoder::rotary::QuadratureDecoder::<simple_encoder::rotary::Divider<-4>>::new();
+/// ```
+///
+/// ```compile_fail
+/// # #![allow(deprecated)]
+/// let decoder = simple_encoder::rotary::QuadratureDecoder::<simple_encoder::rotary::Divider<0>>::new();
+/// ```
+///
+/// ```compile_fail
+/// # #![allow(deprecated)]
+/// let decoder = simple_encoder::rotary::QuadratureDecoder::<simple_encoder::rotary::Divider<3>>::new();
+/// ```
+#[deprecated(note = "use FullStep, HalfStep or QuarterStep")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Divider<const N: i8>;
+
+#[allow(deprecated)]
+impl<const N: i8> Steps for Divider<N> {
+    const MODE: StepMode = {
+        assert!(
+            N == 1 || N == 2 || N == 4,
+            "ROTATION_DIVIDER must be 1, 2 or 4, other counts are set with StepMode::Custom",
+        );
+        StepMode::from_divider(N)
+    };
+}
+
+/// Contact state an encoder rests in at a detent.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RestState {
+    /// Both contacts open, both lines high on a pulled-up encoder.
+    Open,
+    /// Both contacts closed, both lines low on a pulled-up encoder.
+    Closed,
+    /// Detents at both states, as in [`StepMode::Half`].
+    Either,
+}
+
+/// Channel fault seen by [`Rotary::health`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RotaryHealth {
+    Ok,
+    /// Only B toggled over the health window.
+    AStuck,
+    /// Only A toggled over the health window.
+    BStuck,
+}
+
+/// Quadrature decoding state machine fed with sampled levels, independent of any pins.
+/// `M` only sets the initial [`StepMode`], see [`Steps`].
+#[derive(Clone, Debug)]
+pub struct QuadratureDecoder<M = FullStep> {
+    state: u8,
+    switches: i8,
+    step_mode: StepMode,
+    reversed: bool,
+    polarity: (Polarity, Polarity),
+    accumulated: Angle,
+    rest: RestState,
+    errors: u16,
+    primed: bool, // pin history holds a sampled level
+    health_window: u8,
+    a_only: u8, // transitions of A alone since B last changed
+    b_only: u8,
+    chatter_filter: bool,
+    last_dir: Angle,
+    pending_reversal: Angle,
+    odometer: (u32, u32),
+    double: DoubleTransition,
+    trend: i8, // direction of the last single transition
+    mode: PhantomData<M>,
+}
+
+impl<M: Steps> Default for QuadratureDecoder<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Steps> QuadratureDecoder<M> {
+    pub fn new() -> Self {
+        Self {
+            state: 0,
+            switches: 0,
+            step_mode: M::MODE,
+            reversed: false,
+            polarity: (Polarity::ActiveLow, Polarity::ActiveLow),
+            accumulated: 0,
+            rest: RestState::Open,
+            errors: 0,
+            primed: false,
+            health_window: 0,
+            a_only: 0,
+            b_only: 0,
+            chatter_filter: false,
+            last_dir: 0,
+            pending_reversal: 0,
+            odometer: (0, 0),
+            double: DoubleTransition::Ignore,
+            trend: 0,
+            mode: PhantomData,
+        }
+    }
+
+    /// Holds back a step against the previous direction until the next step confirms it.
+    /// A step back in the old direction cancels it, so a single chattering blip at a detent
+    /// boundary reports nothing. Adds one step of latency to genuine reversals.
+    pub fn set_chatter_filter(&mut self, enabled: bool) {
+        self.chatter_filter = enabled;
+        self.pending_reversal = 0;
+    }
+
+    fn filter_chatter(&mut self, rot: Angle) -> Angle {
+        if !self.chatter_filter || rot == 0 {
+            return rot;
+        }
+        let pending = core::mem::take(&mut self.pending_reversal);
+        if pending != 0 {
+            return if rot.signum() == pending.signum() {
+                self.last_dir = rot.signum();
+                rot + pending
+            } else {
+                0
+            };
+        }
+        if self.last_dir != 0 && rot.signum() != self.last_dir {
+            self.pending_reversal = rot;
+            return 0;
+        }
+        self.last_dir = rot.signum();
+        rot
+    }
+
+    /// Reports a stuck channel once only the other one toggled `window` times in a row.
+    /// Zero disables the check.
+    pub fn set_health_window(&mut self, window: u8) {
+        self.health_window = window;
+    }
+
+    /// Clears itself as soon as the stuck channel toggles again.
+    pub fn health(&self) -> RotaryHealth {
+        match self.health_window {
+            0 => RotaryHealth::Ok,
+            n if self.a_only >= n => RotaryHealth::BStuck,
+            n if self.b_only >= n => RotaryHealth::AStuck,
+            _ => RotaryHealth::Ok,
+        }
+    }
+
+    /// Count of impossible transitions seen, where both channels changed between two
+    /// updates. Points at noise or too slow sampling. Saturates at `u16::MAX`.
+    pub fn error_count(&self) -> u16 {
+        self.errors
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.errors = 0;
+    }
+
+    /// Detents turned clockwise and counter-clockwise, before any acceleration.
+    /// Survives resets, saturates at `u32::MAX`.
+    pub fn odometer(&self) -> (u32, u32) {
+        self.odometer
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.odometer = (0, 0);
+    }
+
+    /// Creates a decoder completing partial detents when at rest in `rest`. Default is `Open`.
+    pub fn with_rest_state(rest: RestState) -> Self {
+        let mut decoder = Self::new();
+        decoder.rest = rest;
+        decoder
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.rest = rest;
+        self.switches = 0;
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.double = double;
+    }
+
+    // Whether a partial detent completes at rest with both contacts closed or both open
+    #[inline]
+    fn rests_at(&self, closed: bool) -> bool {
+        match (self.step_mode, self.rest) {
+            (StepMode::Quarter, _) => false,
+            (StepMode::Half, _) | (_, RestState::Either) => true,
+            (_, RestState::Open) => !closed,
+            (_, RestState::Closed) => closed,
+        }
+    }
+
+    /// Creates a decoder with the given active levels of A and B. Pulled-up mechanical
+    /// encoders, the default, are active low.
+    pub fn with_polarity(a: Polarity, b: Polarity) -> Self {
+        let mut decoder = Self::new();
+        decoder.polarity = (a, b);
+        decoder
+    }
+
+    pub fn polarity(&self) -> (Polarity, Polarity) {
+        self.polarity
+    }
+
+    /// Changes the active levels in place, reinterpreting the pin history without
+    /// reporting a rotation. Independent of [`set_reversed`](Self::set_reversed).
+    pub fn set_polarity(&mut self, a: Polarity, b: Polarity) {
+        if a != self.polarity.0 {
+            self.state ^= 0b1010;
+        }
+        if b != self.polarity.1 {
+            self.state ^= 0b0101;
+        }
+        self.polarity = (a, b);
+        self.switches = 0;
+    }
+
+    pub fn with_step_mode(step_mode: StepMode) -> Self {
+        let mut decoder = Self::new();
+        decoder.step_mode = step_mode;
+        decoder
+    }
+
+    pub fn step_mode(&self) -> StepMode {
+        self.step_mode
+    }
+
+    /// Changes the step mode, dropping any partial step.
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.step_mode = step_mode;
+        self.switches = 0;
+    }
+
+    /// Transitions counted towards the next detent, signed by direction. Transient: it returns
+    /// to zero when a detent completes or the encoder rests, see [`divider`](Self::divider).
+    pub fn sub_steps(&self) -> i8 {
+        match self.reversed {
+            true => -self.switches,
+            false => self.switches,
+        }
+    }
+
+    /// Transitions per detent of the current step mode.
+    pub fn divider(&self) -> i8 {
+        self.step_mode.transitions()
+    }
+
+    /// Flips the sign of every reported rotation, as if A and B were swapped.
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// Forgets the pin history, any partial detent and the accumulated rotation.
+    pub fn reset(&mut self) {
+        self.state = 0;
+        self.switches = 0;
+        self.accumulated = 0;
+        self.primed = false;
+        self.a_only = 0;
+        self.b_only = 0;
+        self.last_dir = 0;
+        self.pending_reversal = 0;
+        self.trend = 0;
+    }
+
+    /// Returns the sum of all rotations reported since the previous call and clears it.
+    /// Lets an interrupt feed every transition through `step` while a slower
+    /// loop drains the detents, so fast spins lose no steps.
+    pub fn take_accumulated(&mut self) -> Rotation {
+        Rotation(core::mem::take(&mut self.accumulated))
+    }
+
+    /// Resets and seeds the history from the given levels, so the next genuine
+    /// transition is decoded correctly and the seeding itself reports nothing.
+    pub fn seed(&mut self, a_low: bool, b_low: bool) {
+        self.reset();
+        self.state = self.encode_levels(a_low, b_low);
+        self.primed = true;
+    }
+
+    // Keeps only the last sampled levels as history
+    pub(crate) fn settle(&mut self) {
+        self.state &= 0b1100;
+        self.switches = 0;
+    }
+
+    #[inline]
+    fn encode_levels(&self, a_low: bool, b_low: bool) -> u8 {
+        let a_active = a_low == (self.polarity.0 == Polarity::ActiveLow);
+        let b_active = b_low == (self.polarity.1 == Polarity::ActiveLow);
+        match (a_active, b_active) {
+            (false, false) => 0b0000,
+            (false, true) => 0b0100,
+            (true, false) => 0b1000,
+            (true, true) => 0b1100,
+        }
+    }
+
+    /// Runs every `(a_low, b_low)` sample through [`step`](Self::step)
+    /// and returns the sum of the detents. State carries over between calls, so consecutive
+    /// buffers of a capture decode as one.
+    pub fn decode_samples<I>(&mut self, samples: I) -> Rotation
+    where
+        I: IntoIterator<Item = (bool, bool)>,
+    {
+        samples.into_iter().fold(Rotation(0), |Rotation(sum), (a_low, b_low)| {
+            Rotation(sum.saturating_add(self.step(a_low, b_low).0))
+        })
+    }
+
+    /// Decodes captured port bytes with A at bit `a_bit` and B at bit `b_bit`, a set bit
+    /// meaning the line is high. See [`decode_samples`](Self::decode_samples).
+    pub fn decode_packed(&mut self, samples: &[u8], a_bit: u8, b_bit: u8) -> Rotation {
+        self.decode_samples(samples.iter().map(|&s| (s >> a_bit & 1 == 0, s >> b_bit & 1 == 0)))
+    }
+
+    // Counts transitions towards the next detent, returns the detents completed. At most
+    // two transitions arrive at once, so subtracting beats a software division on 8-bit cores
+    #[inline]
+    fn advance(&mut self, transitions: i8) -> Rotation {
+        let divider = self.step_mode.transitions();
+        let mut switches = self.switches.saturating_add(transitions);
+        let mut detents = 0;
+        while switches >= divider {
+            switches -= divider;
+            detents += 1;
+        }
+        while switches <= -divider {
+            switches += divider;
+            detents -= 1;
+        }
+        self.switches = switches;
+        Rotation(detents)
+    }
+
+    /// Decodes one sample, `true` meaning the line is low. Polarity is applied on top.
+    pub fn step(&mut self, a_low: bool, b_low: bool) -> Rotation {
+        let state = self.state >> 2 | self.encode_levels(a_low, b_low);
+        self.state = state;
+        if self.primed {
+            match (state >> 2 ^ state) & 0b11 {
+                0b11 => self.errors = self.errors.saturating_add(1),
+                0b10 => {
+                    self.a_only = self.a_only.saturating_add(1);
+                    self.b_only = 0;
+                }
+                0b01 => {
+                    self.b_only = self.b_only.saturating_add(1);
+                    self.a_only = 0;
+                }
+                _ => {}
+            }
+        }
+        self.primed = true;
+
+        let rot = match TRANSITIONS[state as usize] {
+            REST if self.rests_at(state >> 2 == 0b11) => {
+                let s = self.switches;
+                self.switches = 0;
+                Rotation(s.signum() as Angle)
+            }
+            REST | 0 => Rotation(0),
+            DOUBLE => match self.double {
+                DoubleTransition::Ignore => Rotation(0),
+                DoubleTransition::Infer => self.advance(2 * self.trend),
+            },
+            dir => {
+                self.trend = dir;
+                self.advance(dir)
+            }
+        };
+        let rot = Rotation(self.filter_chatter(rot.0));
+        let rot = match self.reversed {
+            true => Rotation(-rot.0),
+            false => rot,
+        };
+        match rot.0.signum() {
+            1 => self.odometer.0 = self.odometer.0.saturating_add(1),
+            -1 => self.odometer.1 = self.odometer.1.saturating_add(1),
+            _ => {}
+        }
+        self.accumulated = self.accumulated.saturating_add(rot.0);
+        rot
+    }
+}
+
+/// Quadrature decoder reading A and B through a [`QuadratureInput`], either a pair of pins
+/// `(a, b)` or a single port read, see [`QuadratureDecoder`]. `M` sets the initial
+/// [`StepMode`], see [`Steps`].
+pub struct Rotary<P, M = FullStep> {
+    port: P,
+    decoder: QuadratureDecoder<M>,
+    // Levels of A and B, `true` when low
+    lines: [Debounced; 2],
+}
+
+/// [`Rotary`] reading two separate pins, the former `Rotary<A, B, M>`.
+pub type PinRotary<A, B, M = FullStep> = Rotary<(A, B), M>;
+
+impl<A, B, M: Steps> Rotary<(A, B), M>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+{
+    pub fn new(a_pin: A, b_pin: B) -> Self {
+        Self::from_decoder(a_pin, b_pin, QuadratureDecoder::new())
+    }
+
+    pub fn from_decoder(a_pin: A, b_pin: B, decoder: QuadratureDecoder<M>) -> Self {
+        Self::from_port_with_decoder((a_pin, b_pin), decoder)
+    }
+
+    /// Creates a rotary completing partial detents when at rest in `rest`. Default is `Open`.
+    pub fn with_rest_state(a_pin: A, b_pin: B, rest: RestState) -> Self {
+        Self::from_decoder(a_pin, b_pin, QuadratureDecoder::with_rest_state(rest))
+    }
+
+    /// Creates a rotary with the given active levels of A and B. Pulled-up mechanical
+    /// encoders, the default, are active low.
+    pub fn with_polarity(a_pin: A, b_pin: B, a: Polarity, b: Polarity) -> Self {
+        Self::from_decoder(a_pin, b_pin, QuadratureDecoder::with_polarity(a, b))
+    }
+
+    pub fn with_step_mode(a_pin: A, b_pin: B, step_mode: StepMode) -> Self {
+        Self::from_decoder(a_pin, b_pin, QuadratureDecoder::with_step_mode(step_mode))
+    }
+
+    /// Pin access for reads outside the rotary. Reconfiguring a pin invalidates the
+    /// pin history, call [`reset_from_pins`](Self::reset_from_pins) afterwards.
+    pub fn a_pin(&mut self) -> &mut A {
+        &mut self.port.0
+    }
+
+    pub fn b_pin(&mut self) -> &mut B {
+        &mut self.port.1
+    }
+
+    /// Gives the pins back. The decoding state and settings are discarded.
+    pub fn into_parts(self) -> (A, B) {
+        self.port
+    }
+}
+
+impl<P, M: Steps> Rotary<P, M>
+where
+    P: QuadratureInput,
+{
+    /// Creates a rotary sampling both lines with one read of `port`, so a transition cannot
+    /// fall between the reads of A and B. Decodes a pair of pins alike [`Rotary::new`].
+    pub fn from_port(port: P) -> Self {
+        Self::from_port_with_decoder(port, QuadratureDecoder::new())
+    }
+
+    pub fn from_port_with_decoder(port: P, decoder: QuadratureDecoder<M>) -> Self {
+        Self { port, decoder, lines: [Debounced::new(); 2] }
+    }
+
+    pub fn port(&mut self) -> &mut P {
+        &mut self.port
+    }
+
+    /// Gives the port back. The decoding state and settings are discarded.
+    pub fn into_port(self) -> P {
+        self.port
+    }
+
+    pub fn decoder(&mut self) -> &mut QuadratureDecoder<M> {
+        &mut self.decoder
+    }
+
+    pub fn set_chatter_filter(&mut self, enabled: bool) {
+        self.decoder.set_chatter_filter(enabled)
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.decoder.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.decoder.health()
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.decoder.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.decoder.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.decoder.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.decoder.reset_odometer()
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.decoder.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.decoder.set_double_transition(double)
+    }
+
+    pub fn polarity(&self) -> (Polarity, Polarity) {
+        self.decoder.polarity()
+    }
+
+    pub fn set_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.decoder.set_polarity(a, b)
+    }
+
+    pub fn step_mode(&self) -> StepMode {
+        self.decoder.step_mode()
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.decoder.set_step_mode(step_mode)
+    }
+
+    pub fn sub_steps(&self) -> i8 {
+        self.decoder.sub_steps()
+    }
+
+    pub fn divider(&self) -> i8 {
+        self.decoder.divider()
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.decoder.set_reversed(reversed)
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.decoder.is_reversed()
+    }
+
+    /// Debounces A and B separately: a level change is only decoded after `samples`
+    /// consecutive samples of the new level. Default is 1, every sample is decoded as read.
+    /// Applies to `update` and `update_from_levels`, not to the sample buffer decoders.
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        for line in self.lines.iter_mut() {
+            line.set_samples(samples);
+        }
+    }
+
+    /// Forgets the pin history, any partial detent and the accumulated rotation.
+    pub fn reset(&mut self) {
+        self.decoder.reset();
+        self.lines.iter_mut().for_each(|line| line.reset(false));
+    }
+
+    pub fn take_accumulated(&mut self) -> Rotation {
+        self.decoder.take_accumulated()
+    }
+
+    /// Resets and seeds the pin history from the current levels, so the next genuine
+    /// transition is decoded correctly and the seeding itself reports nothing. Meant for
+    /// pins reconfigured in between, e.g. around sleep, where the old history is stale.
+    pub fn reset_from_pins(&mut self) -> Result<(), P::Error> {
+        let (a_low, b_low) = self.read_levels()?;
+        self.decoder.seed(a_low, b_low);
+        self.lines[0].reset(a_low);
+        self.lines[1].reset(b_low);
+        Ok(())
+    }
+
+    pub(crate) fn settle(&mut self) {
+        self.decoder.settle()
+    }
+
+    #[inline]
+    fn read_levels(&mut self) -> Result<(bool, bool), P::Error> {
+        let (a, b) = self.port.read()?;
+        Ok((!a, !b))
+    }
+
+    pub fn update(&mut self) -> Result<Rotation, P::Error> {
+        let (a_low, b_low) = self.read_levels()?;
+        Ok(self.update_from_levels(a_low, b_low))
+    }
+
+    pub fn decode_samples<I>(&mut self, samples: I) -> Rotation
+    where
+        I: IntoIterator<Item = (bool, bool)>,
+    {
+        self.decoder.decode_samples(samples)
+    }
+
+    pub fn decode_packed(&mut self, samples: &[u8], a_bit: u8, b_bit: u8) -> Rotation {
+        self.decoder.decode_packed(samples, a_bit, b_bit)
+    }
+
+    /// Updates from levels sampled elsewhere, e.g. in a GPIO edge interrupt.
+    /// `true` means the line is low, polarity is applied as for the pins.
+    pub fn update_from_levels(&mut self, a_low: bool, b_low: bool) -> Rotation {
+        self.lines[0].sample(a_low);
+        self.lines[1].sample(b_low);
+        self.decoder.step(self.lines[0].level(), self.lines[1].level())
+    }
+}
+
+/// Maps the time since the previous detent to a rotation multiplier. [`Linear`] divides in
+/// 32 bits, on cores without a hardware divider [`Exponential`] or [`Stepped`] only shift and
+/// compare.
+pub trait AccelCurve {
+    /// `acceleration` is the multiplier configured on the rotary. A gain of zero is
+    /// raised to one, so a detent always produces a step.
+    fn gain(&self, dt: MillisDurationU32, acceleration: u16) -> u16;
+}
+
+/// `acceleration` at `fast` or quicker, one at `slow` or slower, linear in between.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Linear {
+    fast: MillisDurationU32,
+    slow: MillisDurationU32,
+}
+
+impl Default for Linear {
+    fn default() -> Self {
+        Self {
+            fast: MillisDurationU32::from_ticks(LIMITED_ROTATION_MS),
+            slow: MillisDurationU32::from_ticks(SINGLE_ROTATION_MS),
+        }
+    }
+}
+
+impl Linear {
+    /// `None` unless `fast < slow`.
+    pub fn new(slow: MillisDurationU32, fast: MillisDurationU32) -> Option<Self> {
+        let mut curve = Self::default();
+        curve.set_window(slow, fast).then_some(curve)
+    }
+
+    /// Sets the window, leaving it unchanged and returning `false` unless `fast < slow`.
+    pub fn set_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        if fast < slow {
+            self.fast = fast;
+            self.slow = slow;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl AccelCurve for Linear {
+    fn gain(&self, dt: MillisDurationU32, acceleration: u16) -> u16 {
+        let (dt, fast, slow) = (dt.ticks(), self.fast.ticks(), self.slow.ticks());
+        match dt {
+            dt if dt <= fast => acceleration,
+            dt if dt >= slow => 1,
+            #[cfg(not(feature = "narrow"))]
+            dt => {
+                let (mut elapsed, mut size) = (dt - fast, slow - fast);
+                // Keeps the product within 32 bits for windows longer than a minute
+                while size > u16::MAX as u32 {
+                    elapsed >>= 1;
+                    size >>= 1;
+                }
+                let acc = acceleration as u32;
+                (acc - (acc * elapsed / size)) as u16
+            }
+            // Rounds up like the 32 bit path and matches it for accelerations up to 255 and
+            // windows up to 255 ms. Beyond, the window and the acceleration are cut to 8 bits,
+            // within 1/64 of the acceleration
+            #[cfg(feature = "narrow")]
+            dt => {
+                let (mut elapsed, mut size) = (dt - fast, slow - fast);
+                while size > u8::MAX as u32 {
+                    elapsed >>= 1;
+                    size >>= 1;
+                }
+                let shift = 8u32.saturating_sub(acceleration.leading_zeros());
+                let (acc, size) = (acceleration >> shift, size as u16);
+                let remaining = (size - elapsed as u16).max(1);
+                (acc * remaining).div_ceil(size) << shift
+            }
+        }
+    }
+}
+
+/// `acceleration` at `fast` or quicker, halved every `half_life` after it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Exponential {
+    pub fast: MillisDurationU32,
+    pub half_life: MillisDurationU32,
+}
+
+impl Default for Exponential {
+    fn default() -> Self {
+        Self {
+            fast: MillisDurationU32::from_ticks(LIMITED_ROTATION_MS),
+            half_life: MillisDurationU32::from_ticks(10),
+        }
+    }
+}
+
+impl AccelCurve for Exponential {
+    fn gain(&self, dt: MillisDurationU32, acceleration: u16) -> u16 {
+        if dt <= self.fast || self.half_life.ticks() == 0 {
+            return acceleration;
+        }
+        let (mut left, half_life) = ((dt - self.fast).ticks(), self.half_life.ticks());
+        let mut gain = acceleration;
+        while left >= half_life && gain != 0 {
+            left -= half_life;
+            gain >>= 1;
+        }
+        gain
+    }
+}
+
+/// Discrete tiers `(max_dt, gain)` checked in order, one when no tier matches.
+/// Ignores the configured acceleration.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Stepped<const N: usize> {
+    pub tiers: [(MillisDurationU32, u16); N],
+}
+
+impl<const N: usize> AccelCurve for Stepped<N> {
+    fn gain(&self, dt: MillisDurationU32, _: u16) -> u16 {
+        self.tiers
+            .iter()
+            .find(|(max_dt, _)| dt <= *max_dt)
+            .map_or(1, |&(_, gain)| gain)
+    }
+}
+
+/// Count of recent detents kept for rate based acceleration.
+pub const RATE_SLOTS: usize = 16;
+
+// Instants of the recent detents for rate based acceleration
+pub(crate) struct RateWindow<T> {
+    window: MillisDurationU32,
+    table: &'static [(u8, u16)],
+    detents: [Option<T>; RATE_SLOTS],
+    next: usize,
+}
+
+impl<T: Instant> RateWindow<T> {
+    pub(crate) fn new(window: MillisDurationU32, table: &'static [(u8, u16)]) -> Self {
+        Self { window, table, detents: [None; RATE_SLOTS], next: 0 }
+    }
+
+    #[inline]
+    fn is_enabled(&self) -> bool {
+        self.window.ticks() != 0
+    }
+
+    fn clear(&mut self) {
+        self.detents = [None; RATE_SLOTS];
+        self.next = 0;
+    }
+
+    // Records a detent and returns the gain for the count of detents within the window
+    fn push(&mut self, now: T) -> u16 {
+        self.detents[self.next] = Some(now);
+        self.next = (self.next + 1) % RATE_SLOTS;
+        let count = self.detents
+            .iter()
+            .flatten()
+            .filter(|&&at| now.duration_since(at) <= self.window)
+            .count();
+        self.table
+            .iter()
+            .rev()
+            .find(|&&(min_count, _)| count >= min_count as usize)
+            .map_or(1, |&(_, gain)| gain)
+    }
+}
+
+/// Upper bounds of the first [`RotaryStats::histogram`] buckets in ms, the last bucket
+/// takes longer intervals.
+#[cfg(feature = "diagnostics")]
+pub const STATS_BUCKETS: [u32; 7] = [5, 10, 20, 40, 80, 160, 320];
+
+/// Longest interval in ms between the detents of one burst.
+#[cfg(feature = "diagnostics")]
+pub const STATS_BURST_GAP: u32 = 100;
+
+/// Detent timing collected for tuning acceleration. Detents dropped by the glitch filter
+/// or the reversal lockout are not counted.
+#[cfg(feature = "diagnostics")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct RotaryStats {
+    /// Shortest interval between two detents.
+    pub min_interval: Option<MillisDurationU32>,
+    /// Most detents in one direction, each within [`STATS_BURST_GAP`] of the previous.
+    pub max_burst: u16,
+    /// Count of intervals per bucket of [`STATS_BUCKETS`].
+    pub histogram: [u32; 8],
+    burst: u16,
+}
+
+#[cfg(feature = "diagnostics")]
+impl RotaryStats {
+    fn record(&mut self, dt: Option<MillisDurationU32>, same_dir: bool) {
+        self.burst = match dt {
+            Some(dt) => {
+                self.min_interval = Some(self.min_interval.map_or(dt, |min| min.min(dt)));
+                let bucket = STATS_BUCKETS.iter().position(|&max| dt.ticks() <= max).unwrap_or(7);
+                self.histogram[bucket] = self.histogram[bucket].saturating_add(1);
+                match same_dir && dt.ticks() <= STATS_BURST_GAP {
+                    true => self.burst.saturating_add(1),
+                    false => 1,
+                }
+            }
+            None => 1,
+        };
+        self.max_burst = self.max_burst.max(self.burst);
+    }
+}
+
+// Detent filtering and acceleration shared by the time based rotaries
+pub(crate) struct Accelerator<T, G> {
+    pub(crate) last_rot_at: Option<T>,
+    pub(crate) acceleration: u16,
+    pub(crate) curve: G,
+    pub(crate) rate: RateWindow<T>,
+    pub(crate) max_step: u16,
+    pub(crate) reversal_lockout: MillisDurationU32,
+    pub(crate) last_dir: Angle,
+    pub(crate) min_interval: MillisDurationU32,
+    pub(crate) detent_gap: Option<MillisDurationU32>,
+    pub(crate) smoothing: u8,
+    // Filtered detents per second with 8 fractional bits
+    smoothed: u32,
+    #[cfg(feature = "diagnostics")]
+    pub(crate) stats: RotaryStats,
+    pub(crate) gain_decay: u8,
+    last_gain: u16,
+}
+
+impl<T: Instant, G: AccelCurve> Accelerator<T, G> {
+    pub(crate) fn new(acceleration: u16, curve: G) -> Self {
+        Self {
+            last_rot_at: None,
+            acceleration,
+            curve,
+            rate: RateWindow::new(MillisDurationU32::from_ticks(0), &[]),
+            max_step: 0,
+            reversal_lockout: MillisDurationU32::from_ticks(0),
+            last_dir: 0,
+            min_interval: MillisDurationU32::from_ticks(0),
+            detent_gap: None,
+            smoothing: 3,
+            smoothed: 0,
+            #[cfg(feature = "diagnostics")]
+            stats: RotaryStats::default(),
+            gain_decay: 0,
+            last_gain: 1,
         }
     }
-}
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-#[repr(transparent)]
-pub struct Rotation(i32);
+    pub(crate) fn clear(&mut self) {
+        self.last_rot_at = None;
+        self.detent_gap = None;
+        self.smoothed = 0;
+        self.last_gain = 1;
+        self.rate.clear();
+    }
 
-impl Rotation {
-    pub fn new(angle: i32) -> Self {
-        Self(angle)
+    pub(crate) fn smoothed_velocity(&self) -> u16 {
+        (self.smoothed >> 8) as u16
     }
 
-    #[inline]
-    pub fn direction(self) -> Direction {
-        match self.0 {
-            0 => Direction::None,
-            1..=i32::MAX => Direction::Cw,
-            i32::MIN..=-1 => Direction::Ccw,
+    // Detents per second for a span since the last detent, scaled by 256
+    #[cfg(not(feature = "narrow"))]
+    fn rate_of(span: MillisDurationU32) -> u32 {
+        (1000 << 8) / span.ticks().max(1)
+    }
+
+    // Same in a 16 bit division, dropping the lowest four fractional bits
+    #[cfg(feature = "narrow")]
+    fn rate_of(span: MillisDurationU32) -> u32 {
+        ((16_000 / span.ticks().clamp(1, u16::MAX as u32) as u16) as u32) << 4
+    }
+
+    pub(crate) fn velocity(&self, now: T) -> u16 {
+        match (self.last_rot_at, self.detent_gap) {
+            (Some(last), Some(gap)) => {
+                // Any span over a second gives zero, so a 16 bit division does
+                let span = gap.max(now.duration_since(last)).ticks().clamp(1, 1001);
+                1000 / span as u16
+            }
+            _ => 0,
         }
     }
 
-    #[inline(always)]
-    pub fn angle(self) -> i32 {
-        self.0
+    pub(crate) fn apply(&mut self, now: T, rot: Rotation) -> Rotation {
+        let Rotation(rot) = self.accelerate(now, rot);
+        if let (0, Some(last)) = (rot, self.last_rot_at) {
+            self.smoothed = self.smoothed.min(Self::rate_of(now.duration_since(last)));
+        }
+        match self.max_step {
+            0 => Rotation(rot),
+            max => {
+                let max = gain_angle(max);
+                Rotation(rot.clamp(-max, max))
+            }
+        }
     }
 
-    pub fn is_zero(self) -> bool {
-        self.0 == 0
+    fn accelerate(&mut self, now: T, rot: Rotation) -> Rotation {
+        let reversed = rot.0.signum() != self.last_dir;
+        if rot.0 != 0 {
+            if let Some(last) = self.last_rot_at {
+                let dt = now.duration_since(last);
+                if dt < self.min_interval {
+                    return Rotation(0);
+                }
+                if rot.0.signum() != self.last_dir && dt < self.reversal_lockout {
+                    return Rotation(0);
+                }
+                self.detent_gap = Some(dt);
+                let shift = self.smoothing.min(16);
+                let delta = Self::rate_of(dt) as i32 - self.smoothed as i32;
+                self.smoothed = (self.smoothed as i32 + (delta >> shift)) as u32;
+            }
+            #[cfg(feature = "diagnostics")]
+            self.stats.record(self.last_rot_at.map(|last| now.duration_since(last)), rot.0.signum() == self.last_dir);
+            self.last_dir = rot.0.signum();
+        }
+        let Rotation(base) = rot;
+        if base == 0 {
+            return rot;
+        }
+        let last = self.last_rot_at.replace(now);
+        let gain = match last {
+            _ if self.rate.is_enabled() => self.rate.push(now).max(1),
+            None => 1,
+            Some(_) if self.acceleration == 0 => 1,
+            Some(last) => self.curve.gain(now.duration_since(last), self.acceleration).max(1),
+        };
+        let gain = match (self.gain_decay, last) {
+            (0, _) | (_, None) => gain,
+            // A reversal or a long pause ends the ramp
+            _ if reversed => gain,
+            (_, Some(last)) if now.duration_since(last).ticks() >= DECAY_PAUSE_MS => gain,
+            (shift, _) => gain.max(self.last_gain >> shift.min(15)),
+        };
+        self.last_gain = gain;
+        Rotation(base.saturating_mul(gain_angle(gain)))
     }
 }
 
-pub enum RotaryError<A, B> {
-    APin(A),
-    BPin(B),
+/// Fast burst of detents in one direction followed by a pause.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Flick {
+    pub direction: Direction,
+    pub detents: u16,
 }
 
-// TODO: Use const generic of enum
-///
-pub struct Rotary<A, B, const ROTATION_DIVIDER: i8 = 4> {
-    a_pin: A,
-    b_pin: B,
-    state: u8,
-    switches: i8,
+// Burst of detents in one direction, started at `start` and last extended at `last`
+#[derive(Copy, Clone)]
+struct Burst<T> {
+    start: T,
+    last: T,
+    direction: Direction,
+    detents: u16,
 }
 
-impl<A, B, const ROTATION_DIVIDER: i8> Rotary<A, B, ROTATION_DIVIDER>
-where
-    A: InputPin,
-    B: InputPin,
-{
-    pub fn new(a_pin: A, b_pin: B) -> Self {
+struct FlickDetector<T> {
+    min_detents: u8,
+    window: MillisDurationU32,
+    quiet: MillisDurationU32,
+    burst: Option<Burst<T>>,
+    pending: Option<Flick>,
+}
+
+impl<T: Instant> FlickDetector<T> {
+    fn new() -> Self {
         Self {
-            a_pin,
-            b_pin,
-            state: 0,
-            switches: 0,
+            min_detents: 0,
+            window: MillisDurationU32::from_ticks(0),
+            quiet: MillisDurationU32::from_ticks(0),
+            burst: None,
+            pending: None,
         }
     }
 
-    pub fn update(&mut self) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
-        let a_low = self.a_pin.is_low().map_err(RotaryError::APin)?;
-        let b_low = self.b_pin.is_low().map_err(RotaryError::BPin)?;
-
-        let state = self.state >> 2 | match (a_low, b_low) {
-            (false, false) => 0b0000,
-            (false, true) => 0b0100,
-            (true, false) => 0b1000,
-            (true, true) => 0b1100,
-        };
-        self.state = state;
+    fn clear(&mut self) {
+        self.burst = None;
+        self.pending = None;
+    }
 
-        let overflow_switches = |switch_origin: &mut i8, switches: i8| {
-            if switches.abs() >= ROTATION_DIVIDER {
-                *switch_origin = 0;
-                Rotation(switches.signum() as i32)
-            } else {
-                *switch_origin = switches;
-                Rotation(0)
+    fn update(&mut self, now: T, rot: Rotation) {
+        if self.min_detents == 0 {
+            return;
+        }
+        let direction = rot.direction();
+        if direction.is_none() {
+            if let Some(burst) = self.burst {
+                if now.duration_since(burst.last) >= self.quiet {
+                    self.burst = None;
+                    if burst.detents >= self.min_detents as u16 {
+                        self.pending = Some(Flick { direction: burst.direction, detents: burst.detents });
+                    }
+                }
             }
-        };
-
-        let rot = match state {
-            0b0001 | 0b0111 | 0b1110 | 0b1000 | 0b0110 => {
-                let switches = self.switches - 1;
-                overflow_switches(&mut self.switches, switches)
-            },
-            0b0010 | 0b1011 | 0b1101 | 0b0100 | 0b1001 => {
-                let switches = self.switches + 1;
-                overflow_switches(&mut self.switches, switches)
-            },
-            0b0000 | 0b0011 => {
-                let s = self.switches;
-                self.switches = 0;
-                Rotation(s.signum() as i32)
+            return;
+        }
+        match &mut self.burst {
+            Some(burst) if burst.direction == direction && now.duration_since(burst.start) <= self.window => {
+                burst.last = now;
+                burst.detents = burst.detents.saturating_add(1);
             }
-            _ => Rotation(0),
-        };
-        Ok(rot)
+            _ => self.burst = Some(Burst { start: now, last: now, direction, detents: 1 }),
+        }
     }
 }
 
-pub struct TimeRotary<A, B, T, const ROTATION_DIVIDER: i8 = 4> where T: Instant {
-    rotary: Rotary<A, B, ROTATION_DIVIDER>,
-    last_rot_at: Option<T>,
-    acceleration: u16,
+pub struct TimeRotary<A, B, T, M = FullStep, G = Linear> where T: Instant {
+    rotary: PinRotary<A, B, M>,
+    grace: StartupGrace<T>,
+    accel: Accelerator<T, G>,
+    flick: FlickDetector<T>,
 }
 
-impl<A, B, T, const ROTATION_DIVIDER: i8> TimeRotary<A, B, T, ROTATION_DIVIDER>
+impl<A, B, T, M: Steps> TimeRotary<A, B, T, M>
     where
-        A: InputPin,
-        B: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
         T: Instant,
 {
-    pub fn set_acceleration(&mut self, acceleration: u16) {
-        self.acceleration = acceleration;
-    }
-
     pub fn new(a_pin: A, b_pin: B) -> Self {
         Self::with_acceleration(a_pin, b_pin, 1)
     }
 
+    /// `acceleration` is the maximal multiplier of a detent, zero disables acceleration.
     pub fn with_acceleration(a_pin: A, b_pin: B, acceleration: u16) -> Self {
+        Self::with_curve(a_pin, b_pin, acceleration, Linear::default())
+    }
+
+    /// Creates a rotary reporting no rotation during `grace` since the first `update`,
+    /// while pull-ups settle. Afterwards it decodes from the settled levels.
+    pub fn with_startup_grace(a_pin: A, b_pin: B, grace: MillisDurationU32) -> Self {
+        let mut rotary = Self::new(a_pin, b_pin);
+        rotary.grace = StartupGrace::new(grace);
+        rotary
+    }
+
+    /// Creates a rotary accelerating by the count of detents within `window` (including the
+    /// current one) instead of the gap to the previous detent. `table` holds `(min_count, gain)`
+    /// ascending by count, fewer detents than the first entry give a gain of one.
+    pub fn with_rate_acceleration(
+        a_pin: A,
+        b_pin: B,
+        window: MillisDurationU32,
+        table: &'static [(u8, u16)],
+    ) -> Self {
+        let mut rotary = Self::new(a_pin, b_pin);
+        rotary.set_rate_acceleration(window, table);
+        rotary
+    }
+
+    /// Sets the detent intervals of no acceleration (`slow`) and full acceleration (`fast`),
+    /// 100 and 20 ms by default. Returns `false` and keeps the window unless `fast < slow`.
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.accel.curve.set_window(slow, fast)
+    }
+}
+
+impl<A, B, T, M: Steps, G> TimeRotary<A, B, T, M, G>
+    where
+        A: ButtonInput,
+        B: ButtonInput,
+        T: Instant,
+        G: AccelCurve,
+{
+    pub fn with_curve(a_pin: A, b_pin: B, acceleration: u16, curve: G) -> Self {
         Self {
             rotary: Rotary::new(a_pin, b_pin),
-            last_rot_at: None,
-            acceleration,
+            grace: StartupGrace::new(MillisDurationU32::from_ticks(0)),
+            accel: Accelerator::new(acceleration, curve),
+            flick: FlickDetector::new(),
         }
     }
 
+    /// Detects a [`Flick`] when at least `min_detents` detents in one direction start within
+    /// `window` and no detent follows for `quiet`. Rotations are still reported during the
+    /// burst, the flick is picked up with [`take_flick`](Self::take_flick). A change of
+    /// direction starts a new burst. Zero `min_detents` disables it.
+    pub fn set_flick(&mut self, min_detents: u8, window: MillisDurationU32, quiet: MillisDurationU32) {
+        self.flick = FlickDetector { min_detents, window, quiet, ..FlickDetector::new() };
+    }
+
+    /// Flick detected since the previous call.
+    pub fn take_flick(&mut self) -> Option<Flick> {
+        self.flick.pending.take()
+    }
+
+    /// Detents per second from the gap between the last two detents, not smoothed further.
+    /// Once the time since the last detent exceeds that gap it is used instead, so the
+    /// velocity decays to zero about a second after the rotation stops.
+    pub fn velocity(&self, now: T) -> u16 {
+        self.accel.velocity(now)
+    }
+
+    /// Time since the last reported rotation, `None` before the first one and after a reset.
+    pub fn idle_for(&self, now: T) -> Option<MillisDurationU32> {
+        self.accel.last_rot_at.map(|at| now.duration_since(at))
+    }
+
+    /// Detent timing since creation or [`reset_stats`](Self::reset_stats), kept over `reset`.
+    #[cfg(feature = "diagnostics")]
+    pub fn stats(&self) -> RotaryStats {
+        self.accel.stats
+    }
+
+    #[cfg(feature = "diagnostics")]
+    pub fn reset_stats(&mut self) {
+        self.accel.stats = RotaryStats::default();
+    }
+
+    /// Detents per second filtered by an exponential moving average. Every detent moves the
+    /// value by `1 / 2^shift` of the way to its own velocity, zero disables the filtering.
+    /// The shift saturates at 16 and defaults to 3.
+    pub fn set_velocity_smoothing(&mut self, shift: u8) {
+        self.accel.smoothing = shift;
+    }
+
+    /// Velocity filtered as set by [`set_velocity_smoothing`](Self::set_velocity_smoothing).
+    /// Between detents it is bounded by the rate implied by the time since the last one, so it
+    /// decays to zero about a second after the rotation stops. The bound is only applied by
+    /// `update`: with infrequent updates the value is kept from the last one in between.
+    pub fn smoothed_velocity(&self) -> u16 {
+        self.accel.smoothed_velocity()
+    }
+
+    /// Discards a detent arriving sooner than `interval` after the previous one as a glitch,
+    /// in any direction. Discarded detents do not count for acceleration. Zero disables it.
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.accel.min_interval = interval;
+    }
+
+    /// Drops detents against the previous direction arriving within `lockout` after it.
+    /// Dropped detents do not count for acceleration. Zero disables it.
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.accel.reversal_lockout = lockout;
+    }
+
+    /// Limits the magnitude of a single accelerated rotation. Zero means no limit.
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.accel.max_step = max_step;
+    }
+
+    /// Switches to rate based acceleration, see [`TimeRotary::with_rate_acceleration`].
+    /// Zero window switches back to the curve.
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.accel.rate = RateWindow::new(window, table);
+    }
+
+    /// Zero disables acceleration, every detent is a single step regardless of the curve.
+    pub fn set_acceleration(&mut self, acceleration: u16) {
+        self.accel.acceleration = acceleration;
+    }
+
+    /// Ramps the gain down when slowing: a detent keeps at least the previous gain shifted
+    /// right by `shift`, so the ramp divides by powers of two, one halving it per detent.
+    /// A reversal or a pause of a second, ten single step gaps of the default [`Linear`]
+    /// window, drops the gain at once. Zero disables it.
+    pub fn set_gain_decay(&mut self, shift: u8) {
+        self.accel.gain_decay = shift;
+    }
+
+    pub fn acceleration(&self) -> u16 {
+        self.accel.acceleration
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.accel.curve = curve;
+    }
+
+    pub fn curve(&self) -> &G {
+        &self.accel.curve
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.rotary.set_step_mode(step_mode)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.rotary.set_reversed(reversed)
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.rotary.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.rotary.set_double_transition(double)
+    }
+
+    pub fn set_chatter_filter(&mut self, enabled: bool) {
+        self.rotary.set_chatter_filter(enabled)
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.rotary.set_debounce_samples(samples)
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.rotary.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.rotary.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.rotary.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.rotary.reset_odometer()
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.rotary.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.rotary.health()
+    }
+
+    pub fn sub_steps(&self) -> i8 {
+        self.rotary.sub_steps()
+    }
+
+    pub fn divider(&self) -> i8 {
+        self.rotary.divider()
+    }
+
+    pub fn set_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.rotary.set_polarity(a, b)
+    }
+
+    pub fn a_pin(&mut self) -> &mut A {
+        self.rotary.a_pin()
+    }
+
+    pub fn b_pin(&mut self) -> &mut B {
+        self.rotary.b_pin()
+    }
+
+    pub fn into_parts(self) -> (A, B) {
+        self.rotary.into_parts()
+    }
+
+    /// Forgets the pin history, any partial detent, the acceleration history and a pending flick.
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.accel.clear();
+        self.flick.clear();
+    }
+
+    pub fn reset_from_pins(&mut self) -> Result<(), RotaryError<A::Error, B::Error>> {
+        self.rotary.reset_from_pins()?;
+        self.accel.clear();
+        self.flick.clear();
+        Ok(())
+    }
+
     pub fn update(&mut self, now: T) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
         let rot = self.rotary.update()?;
-        match rot {
-            Rotation(0) => Ok(rot),
-            Rotation(base) => match self.last_rot_at.replace(now) {
-                None => Ok(Rotation(base)),
-                Some(last) => {
-                    let dt = now.duration_since(last);
-                    match dt.to_millis() {
-                        dt if dt <= LIMITED_ROTATION_MS => Ok(Rotation(base * self.acceleration as i32)),
-                        dt if dt >= SINGLE_ROTATION_MS => Ok(Rotation(base)), // handle 0 acceleraton?
-                        dt => {
-                            let low_plus_dt = dt - LIMITED_ROTATION_MS;
-                            let size = SINGLE_ROTATION_MS - LIMITED_ROTATION_MS;
-                            let acc = self.acceleration as u32;
-                            let rot = acc - (acc * low_plus_dt / size);
-                            Ok(Rotation(base * rot as i32))
-                        }
-                    }
-                }
-            },
+        Ok(self.apply(now, rot))
+    }
+
+    /// Updates from levels sampled elsewhere, see [`Rotary::update_from_levels`].
+    pub fn update_from_levels(&mut self, now: T, a_low: bool, b_low: bool) -> Rotation {
+        let rot = self.rotary.update_from_levels(a_low, b_low);
+        self.apply(now, rot)
+    }
+
+    fn apply(&mut self, now: T, rot: Rotation) -> Rotation {
+        if self.grace.is_active() {
+            if self.grace.update(now) {
+                self.rotary.settle();
+                self.accel.clear();
+            }
+            return Rotation(0);
         }
+        let rot = self.accel.apply(now, rot);
+        self.flick.update(now, rot);
+        rot
     }
 }
 
-pub struct ClockRotary<A, B, C, const ROTATION_DIVIDER: i8 = 4>
+pub struct ClockRotary<A, B, C, M = FullStep, G = Linear>
     where
-        A: InputPin,
-        B: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
         C: Clock,
 {
-    rotary: TimeRotary<A, B, C::Instant, ROTATION_DIVIDER>,
+    rotary: TimeRotary<A, B, C::Instant, M, G>,
     clock: C,
 }
 
-impl<A, B, C, const ROTATION_DIVIDER: i8> ClockRotary<A, B, C, ROTATION_DIVIDER>
+impl<A, B, C, M: Steps> ClockRotary<A, B, C, M>
     where
-        A: InputPin,
-        B: InputPin,
+        A: ButtonInput,
+        B: ButtonInput,
         C: Clock,
 {
-    pub fn set_acceleration(&mut self, acceleration: u16) {
-        self.rotary.set_acceleration(acceleration);
-    }
-
     pub fn new(a_pin: A, b_pin: B, clock: C) -> Self {
         Self::with_acceleration(a_pin, b_pin, clock, 1)
     }
@@ -203,7 +1739,175 @@ impl<A, B, C, const ROTATION_DIVIDER: i8> ClockRotary<A, B, C, ROTATION_DIVIDER>
         }
     }
 
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.rotary.set_accel_window(slow, fast)
+    }
+}
+
+impl<A, B, C, M: Steps, G> ClockRotary<A, B, C, M, G>
+    where
+        A: ButtonInput,
+        B: ButtonInput,
+        C: Clock,
+        G: AccelCurve,
+{
+    pub fn set_acceleration(&mut self, acceleration: u16) {
+        self.rotary.set_acceleration(acceleration);
+    }
+
+    pub fn acceleration(&self) -> u16 {
+        self.rotary.acceleration()
+    }
+
+    /// See [`TimeRotary::set_gain_decay`], `shift` divides the gain by a power of two.
+    pub fn set_gain_decay(&mut self, shift: u8) {
+        self.rotary.set_gain_decay(shift)
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.rotary.set_curve(curve)
+    }
+
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.rotary.set_rate_acceleration(window, table)
+    }
+
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.rotary.set_max_step(max_step)
+    }
+
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.rotary.set_reversal_lockout(lockout)
+    }
+
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.rotary.set_min_detent_interval(interval)
+    }
+
+    pub fn set_flick(&mut self, min_detents: u8, window: MillisDurationU32, quiet: MillisDurationU32) {
+        self.rotary.set_flick(min_detents, window, quiet)
+    }
+
+    pub fn take_flick(&mut self) -> Option<Flick> {
+        self.rotary.take_flick()
+    }
+
+    pub fn set_step_mode(&mut self, step_mode: StepMode) {
+        self.rotary.set_step_mode(step_mode)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.rotary.set_reversed(reversed)
+    }
+
+    pub fn set_rest_state(&mut self, rest: RestState) {
+        self.rotary.set_rest_state(rest)
+    }
+
+    pub fn set_double_transition(&mut self, double: DoubleTransition) {
+        self.rotary.set_double_transition(double)
+    }
+
+    pub fn set_chatter_filter(&mut self, enabled: bool) {
+        self.rotary.set_chatter_filter(enabled)
+    }
+
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.rotary.set_debounce_samples(samples)
+    }
+
+    pub fn error_count(&self) -> u16 {
+        self.rotary.error_count()
+    }
+
+    pub fn clear_error_count(&mut self) {
+        self.rotary.clear_error_count()
+    }
+
+    pub fn odometer(&self) -> (u32, u32) {
+        self.rotary.odometer()
+    }
+
+    pub fn reset_odometer(&mut self) {
+        self.rotary.reset_odometer()
+    }
+
+    pub fn set_health_window(&mut self, window: u8) {
+        self.rotary.set_health_window(window)
+    }
+
+    pub fn health(&self) -> RotaryHealth {
+        self.rotary.health()
+    }
+
+    pub fn sub_steps(&self) -> i8 {
+        self.rotary.sub_steps()
+    }
+
+    pub fn divider(&self) -> i8 {
+        self.rotary.divider()
+    }
+
+    pub fn set_polarity(&mut self, a: Polarity, b: Polarity) {
+        self.rotary.set_polarity(a, b)
+    }
+
+    pub fn from_parts(rotary: TimeRotary<A, B, C::Instant, M, G>, clock: C) -> Self {
+        Self { rotary, clock }
+    }
+
+    pub fn into_parts(self) -> (TimeRotary<A, B, C::Instant, M, G>, C) {
+        (self.rotary, self.clock)
+    }
+
+    pub fn rotary(&mut self) -> &mut TimeRotary<A, B, C::Instant, M, G> {
+        &mut self.rotary
+    }
+
+    /// Same as [`rotary`](Self::rotary), named alike on every clock wrapper.
+    pub fn inner(&mut self) -> &mut TimeRotary<A, B, C::Instant, M, G> {
+        &mut self.rotary
+    }
+
+    pub fn clock(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    pub fn reset(&mut self) {
+        self.rotary.reset()
+    }
+
+    pub fn reset_from_pins(&mut self) -> Result<(), RotaryError<A::Error, B::Error>> {
+        self.rotary.reset_from_pins()
+    }
+
     pub fn update(&mut self) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
         self.rotary.update(self.clock.now())
     }
+
+    pub fn velocity(&mut self) -> u16 {
+        self.rotary.velocity(self.clock.now())
+    }
+
+    pub fn idle_for(&mut self) -> Option<MillisDurationU32> {
+        self.rotary.idle_for(self.clock.now())
+    }
+
+    #[cfg(feature = "diagnostics")]
+    pub fn stats(&self) -> RotaryStats {
+        self.rotary.stats()
+    }
+
+    #[cfg(feature = "diagnostics")]
+    pub fn reset_stats(&mut self) {
+        self.rotary.reset_stats()
+    }
+
+    pub fn set_velocity_smoothing(&mut self, shift: u8) {
+        self.rotary.set_velocity_smoothing(shift)
+    }
+
+    pub fn smoothed_velocity(&self) -> u16 {
+        self.rotary.smoothed_velocity()
+    }
 }
@@ -0,0 +1,153 @@
+use crate::rotary::{AccelCurve, FullStep, Linear, PinRotary, RotaryError, Rotation, Steps, TimeRotary};
+use crate::{ButtonInput, Instant};
+
+fn clamp(angle: i64) -> i32 {
+    angle.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+/// Scales rotations by `num / den`, carrying the remainder to the next rotation so the
+/// total is exact: three detents at 1/3 give exactly one step. Rounds toward zero, so
+/// turning back and forth leaves nothing behind.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Scale {
+    num: i32,
+    den: u16,
+    carry: i64,
+}
+
+impl Scale {
+    /// `None` for a zero `den`.
+    pub fn new(num: i32, den: u16) -> Option<Self> {
+        (den != 0).then_some(Self { num, den, carry: 0 })
+    }
+
+    pub fn ratio(&self) -> (i32, u16) {
+        (self.num, self.den)
+    }
+
+    /// Changes the ratio, dropping the carried remainder. Returns `false` and keeps the
+    /// ratio for a zero `den`.
+    pub fn set_ratio(&mut self, num: i32, den: u16) -> bool {
+        if den == 0 {
+            return false;
+        }
+        *self = Self { num, den, carry: 0 };
+        true
+    }
+
+    /// Remainder carried to the next rotation, in `1 / den` steps.
+    pub fn carry(&self) -> i64 {
+        self.carry
+    }
+
+    pub fn clear(&mut self) {
+        self.carry = 0;
+    }
+
+    pub fn apply(&mut self, rot: Rotation) -> Rotation {
+        Rotation::new(clamp(self.apply_wide(rot.angle() as i64)))
+    }
+
+    // Scales `angle` in 64 bits so callers can widen before the `Rotation` range, e.g. the
+    // `i16` one with `narrow`
+    fn apply_wide(&mut self, angle: i64) -> i64 {
+        let total = angle.saturating_mul(self.num as i64).saturating_add(self.carry);
+        let den = self.den as i64;
+        let steps = total / den;
+        self.carry = total - steps * den;
+        steps
+    }
+}
+
+/// Detents to user units: a per-detent step of `num / den` units times a multiplier, e.g.
+/// 1/2 for 0.5 °C steps with a multiplier of 10 for a coarse mode. The remainder is kept
+/// when the multiplier changes.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct StepMapper {
+    scale: Scale,
+    multiplier: i32,
+}
+
+impl StepMapper {
+    /// `None` for a zero `den`. The multiplier starts at one.
+    pub fn new(num: i32, den: u16) -> Option<Self> {
+        Scale::new(num, den).map(|scale| Self { scale, multiplier: 1 })
+    }
+
+    pub fn multiplier(&self) -> i32 {
+        self.multiplier
+    }
+
+    pub fn set_multiplier(&mut self, multiplier: i32) {
+        self.multiplier = multiplier;
+    }
+
+    /// Units to apply for `rot`. Computed in 64 bits, only the result saturates at the `i32`
+    /// range, so a large step is not cut to the `i16` rotation range with `narrow`.
+    pub fn apply(&mut self, rot: Rotation) -> i32 {
+        clamp(self.scale.apply_wide(rot.angle() as i64 * self.multiplier as i64))
+    }
+}
+
+/// [`Rotary`](crate::rotary::Rotary) with scaled output.
+pub struct ScaledRotary<A, B, M = FullStep> {
+    rotary: PinRotary<A, B, M>,
+    scale: Scale,
+}
+
+impl<A, B, M: Steps> ScaledRotary<A, B, M>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+{
+    pub fn new(rotary: PinRotary<A, B, M>, scale: Scale) -> Self {
+        Self { rotary, scale }
+    }
+
+    pub fn rotary(&mut self) -> &mut PinRotary<A, B, M> {
+        &mut self.rotary
+    }
+
+    pub fn scale(&mut self) -> &mut Scale {
+        &mut self.scale
+    }
+
+    pub fn update(&mut self) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
+        let rot = self.rotary.update()?;
+        Ok(self.scale.apply(rot))
+    }
+}
+
+/// [`TimeRotary`] with scaled output, applied after acceleration.
+pub struct TimeScaledRotary<A, B, T, M = FullStep, G = Linear>
+where
+    T: Instant,
+{
+    rotary: TimeRotary<A, B, T, M, G>,
+    scale: Scale,
+}
+
+impl<A, B, T, M: Steps, G> TimeScaledRotary<A, B, T, M, G>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    T: Instant,
+    G: AccelCurve,
+{
+    pub fn new(rotary: TimeRotary<A, B, T, M, G>, scale: Scale) -> Self {
+        Self { rotary, scale }
+    }
+
+    pub fn rotary(&mut self) -> &mut TimeRotary<A, B, T, M, G> {
+        &mut self.rotary
+    }
+
+    pub fn scale(&mut self) -> &mut Scale {
+        &mut self.scale
+    }
+
+    pub fn update(&mut self, now: T) -> Result<Rotation, RotaryError<A::Error, B::Error>> {
+        let rot = self.rotary.update(now)?;
+        Ok(self.scale.apply(rot))
+    }
+}
@@ -0,0 +1,109 @@
+use crate::button::{Button, ButtonAction};
+use crate::encoder::EncoderAction;
+use crate::input::NoPin;
+use crate::rotary::{FullStep, QuadratureDecoder, RotaryError, Rotation, Steps};
+use crate::ButtonInput;
+
+/// Encoder whose key pulls the A line low, so it needs only two pins. A line held low
+/// with B unchanged for more than `press_samples` updates is taken for a press: decoding
+/// stops until A is released, then the decoder is reseeded from the current levels.
+///
+/// Limitations:
+/// * rotation while pressed is dropped, `RotatePressed` is never reported,
+/// * `Press` is reported `press_samples` updates late,
+/// * the encoder has to rest with A high, detents resting with A low read as presses,
+/// * a rotation slower than `press_samples` updates per state reads as a press.
+pub struct SharedPinEncoder<AB, B, M = FullStep>
+where
+    AB: ButtonInput,
+    B: ButtonInput,
+{
+    ab_pin: AB,
+    b_pin: B,
+    decoder: QuadratureDecoder<M>,
+    button: Button<NoPin>,
+    press_samples: u8,
+    low_run: u8,
+    last_b_low: bool,
+}
+
+impl<AB, B, M: Steps> SharedPinEncoder<AB, B, M>
+where
+    AB: ButtonInput,
+    B: ButtonInput,
+{
+    pub fn new(ab_pin: AB, b_pin: B, press_samples: u8) -> Self {
+        Self {
+            ab_pin,
+            b_pin,
+            decoder: QuadratureDecoder::new(),
+            button: Button::new(NoPin),
+            press_samples,
+            low_run: 0,
+            last_b_low: false,
+        }
+    }
+
+    pub fn set_press_samples(&mut self, samples: u8) {
+        self.press_samples = samples;
+    }
+
+    pub fn decoder(&mut self) -> &mut QuadratureDecoder<M> {
+        &mut self.decoder
+    }
+
+    /// Reports `None` instead of `Held` for the first `samples` held updates after `Press`.
+    pub fn set_hold_delay(&mut self, samples: u16) {
+        self.button.set_hold_delay(samples)
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.button.is_down()
+    }
+
+    pub fn into_parts(self) -> (AB, B) {
+        (self.ab_pin, self.b_pin)
+    }
+
+    pub fn reset(&mut self) {
+        self.decoder.reset();
+        self.button.reset();
+        self.low_run = 0;
+    }
+
+    pub fn update(&mut self) -> Result<EncoderAction, RotaryError<AB::Error, B::Error>> {
+        let a_low = !self.ab_pin.is_high().map_err(RotaryError::APin)?;
+        let b_low = !self.b_pin.is_high().map_err(RotaryError::BPin)?;
+        Ok(self.update_from_levels(a_low, b_low))
+    }
+
+    /// Updates from levels sampled elsewhere, `true` meaning the line is low.
+    pub fn update_from_levels(&mut self, a_low: bool, b_low: bool) -> EncoderAction {
+        self.low_run = match a_low && b_low == self.last_b_low {
+            true => self.low_run.saturating_add(1),
+            false => a_low as u8,
+        };
+        self.last_b_low = b_low;
+        let was_pressed = self.button.is_down();
+        let pressed = match was_pressed {
+            true => a_low,
+            false => self.low_run > self.press_samples,
+        };
+        let rotation = match (was_pressed, pressed) {
+            (_, true) => Rotation::ZERO,
+            (true, false) => {
+                self.decoder.seed(a_low, b_low);
+                Rotation::ZERO
+            }
+            (false, false) => self.decoder.step(a_low, b_low),
+        };
+        match self.button.update_with_level(pressed) {
+            ButtonAction::None if rotation.is_zero() => EncoderAction::None,
+            ButtonAction::None => EncoderAction::Rotate(rotation),
+            ButtonAction::Press => EncoderAction::Press,
+            ButtonAction::Held => EncoderAction::Held,
+            ButtonAction::Click => EncoderAction::Click,
+            ButtonAction::Release => EncoderAction::Release,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Scripted encoder pins for host tests.
+//!
+//! Build the timeline first, then hand the pins to a device and call
+//! [`SimEncoder::advance`] before every update:
+//!
+//! ```ignore
+//! let mut sim = SimEncoder::<64>::new();
+//! sim.push_rotation(3).push_press().push_rotation(-2).push_release();
+//! let mut encoder = Encoder::<_, _, _, FullStep>::new(sim.a(), sim.b(), sim.k());
+//! while sim.advance() {
+//!     let action = encoder.update()?;
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use embedded_hal::digital::v2::InputPin;
+
+const A: u8 = 0b001;
+const B: u8 = 0b010;
+const K: u8 = 0b100;
+const IDLE: u8 = A | B | K;
+
+// Clockwise detent from rest, A and B levels with pull-ups
+const CW: [u8; 4] = [A, 0, B, A | B];
+const CCW: [u8; 4] = [B, 0, A, A | B];
+
+/// Timeline of up to `N` samples of a pulled-up mechanical encoder: A and B high at rest,
+/// the active low key released. Before the first [`advance`](Self::advance) the pins
+/// read the rest levels, after the last sample they keep its levels. Pushing past `N`
+/// samples panics.
+pub struct SimEncoder<const N: usize> {
+    samples: [u8; N],
+    len: usize,
+    key: bool,
+    cursor: Cell<Option<usize>>,
+}
+
+impl<const N: usize> Default for SimEncoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SimEncoder<N> {
+    pub fn new() -> Self {
+        Self {
+            samples: [IDLE; N],
+            len: 0,
+            key: false,
+            cursor: Cell::new(None),
+        }
+    }
+
+    /// Samples in the timeline.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn push(&mut self, levels: u8) -> &mut Self {
+        assert!(self.len < N, "sim timeline full");
+        let key = if self.key { 0 } else { K };
+        self.samples[self.len] = levels | key;
+        self.len += 1;
+        self
+    }
+
+    fn rest_levels(&self) -> u8 {
+        match self.len {
+            0 => A | B,
+            n => self.samples[n - 1] & (A | B),
+        }
+    }
+
+    /// Pushes four transitions per detent, clockwise for positive `detents`.
+    /// The key keeps its level, so rotations while held are scripted between
+    /// [`push_press`](Self::push_press) and [`push_release`](Self::push_release).
+    pub fn push_rotation(&mut self, detents: i32) -> &mut Self {
+        let cycle = if detents >= 0 { CW } else { CCW };
+        for _ in 0..detents.unsigned_abs() {
+            for levels in cycle {
+                self.push(levels);
+            }
+        }
+        self
+    }
+
+    pub fn push_press(&mut self) -> &mut Self {
+        self.key = true;
+        self.push(self.rest_levels())
+    }
+
+    pub fn push_release(&mut self) -> &mut Self {
+        self.key = false;
+        self.push(self.rest_levels())
+    }
+
+    /// Holds the current levels for `samples` samples.
+    pub fn push_idle(&mut self, samples: usize) -> &mut Self {
+        for _ in 0..samples {
+            self.push(self.rest_levels());
+        }
+        self
+    }
+
+    /// Press lasting `samples` samples, followed by the release.
+    pub fn push_click(&mut self, samples: usize) -> &mut Self {
+        self.push_press();
+        self.push_idle(samples.saturating_sub(1));
+        self.push_release()
+    }
+
+    /// Moves to the next sample, `false` once the timeline is exhausted.
+    pub fn advance(&self) -> bool {
+        let next = self.cursor.get().map_or(0, |i| i + 1);
+        if next < self.len {
+            self.cursor.set(Some(next));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Index of the current sample, `None` before the first `advance`.
+    pub fn position(&self) -> Option<usize> {
+        self.cursor.get()
+    }
+
+    /// Starts the timeline over.
+    pub fn rewind(&self) {
+        self.cursor.set(None);
+    }
+
+    pub fn a(&self) -> SimPin<'_, N> {
+        SimPin { sim: self, line: A }
+    }
+
+    pub fn b(&self) -> SimPin<'_, N> {
+        SimPin { sim: self, line: B }
+    }
+
+    pub fn k(&self) -> SimPin<'_, N> {
+        SimPin { sim: self, line: K }
+    }
+
+    fn levels(&self) -> u8 {
+        self.cursor.get().map_or(IDLE, |i| self.samples[i])
+    }
+}
+
+/// One line of a [`SimEncoder`].
+pub struct SimPin<'a, const N: usize> {
+    sim: &'a SimEncoder<N>,
+    line: u8,
+}
+
+impl<const N: usize> InputPin for SimPin<'_, N> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.sim.levels() & self.line != 0)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.sim.levels() & self.line == 0)
+    }
+}
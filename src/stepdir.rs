@@ -0,0 +1,208 @@
+use fugit::MillisDurationU32;
+use crate::button::{self, Button, Edge, EdgeButton};
+use crate::encoder::{combine, EncoderAction, PinsError};
+use crate::rotary::{Accelerator, AccelCurve, Linear, RateWindow, RotaryError, Rotation};
+use crate::{ButtonInput, Instant};
+
+/// Decoder of a step/direction output, e.g. a CNC hand wheel. Counts debounced rising
+/// edges of the step line, each one detent signed by the direction line sampled at the
+/// edge, high meaning clockwise. Direction changes between edges are taken as they come.
+/// Pin errors of the step line are reported as `APin`, of the direction line as `BPin`.
+pub struct StepDirRotary<S, D>
+where
+    S: ButtonInput,
+{
+    step: EdgeButton<S>,
+    dir: D,
+    reversed: bool,
+}
+
+impl<S, D> StepDirRotary<S, D>
+where
+    S: ButtonInput,
+    D: ButtonInput,
+{
+    pub fn new(step_pin: S, dir_pin: D) -> Self {
+        Self { step: EdgeButton::new(step_pin), dir: dir_pin, reversed: false }
+    }
+
+    /// Consecutive identical step samples needed to accept an edge, see
+    /// [`EdgeButton::set_debounce_samples`].
+    pub fn set_debounce_samples(&mut self, samples: u8) {
+        self.step.set_debounce_samples(samples)
+    }
+
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn step_pin(&mut self) -> &mut S {
+        self.step.k_pin()
+    }
+
+    pub fn dir_pin(&mut self) -> &mut D {
+        &mut self.dir
+    }
+
+    pub fn into_parts(self) -> (S, D) {
+        (self.step.into_pin(), self.dir)
+    }
+
+    /// Forgets the step history, a step line high at the next update counts as an edge.
+    pub fn reset(&mut self) {
+        self.step.reset()
+    }
+
+    pub fn update(&mut self) -> Result<Rotation, RotaryError<S::Error, D::Error>> {
+        let edge = self.step.update().map_err(|button::Error::KPin(e)| RotaryError::APin(e))?;
+        if edge != Edge::Pressed {
+            return Ok(Rotation::ZERO);
+        }
+        let cw = self.dir.is_high().map_err(RotaryError::BPin)? != self.reversed;
+        Ok(match cw {
+            true => Rotation::CW_STEP,
+            false => Rotation::CCW_STEP,
+        })
+    }
+}
+
+/// [`StepDirRotary`] with the acceleration of [`TimeRotary`](crate::rotary::TimeRotary).
+pub struct TimeStepDirRotary<S, D, T, G = Linear>
+where
+    S: ButtonInput,
+{
+    rotary: StepDirRotary<S, D>,
+    accel: Accelerator<T, G>,
+}
+
+impl<S, D, T> TimeStepDirRotary<S, D, T>
+where
+    S: ButtonInput,
+    D: ButtonInput,
+    T: Instant,
+{
+    pub fn new(step_pin: S, dir_pin: D) -> Self {
+        Self::with_acceleration(step_pin, dir_pin, 1)
+    }
+
+    /// `acceleration` is the maximal multiplier of a detent, zero disables acceleration.
+    pub fn with_acceleration(step_pin: S, dir_pin: D, acceleration: u16) -> Self {
+        Self::with_curve(step_pin, dir_pin, acceleration, Linear::default())
+    }
+
+    /// See [`TimeRotary::set_accel_window`](crate::rotary::TimeRotary::set_accel_window).
+    pub fn set_accel_window(&mut self, slow: MillisDurationU32, fast: MillisDurationU32) -> bool {
+        self.accel.curve.set_window(slow, fast)
+    }
+}
+
+impl<S, D, T, G> TimeStepDirRotary<S, D, T, G>
+where
+    S: ButtonInput,
+    D: ButtonInput,
+    T: Instant,
+    G: AccelCurve,
+{
+    pub fn with_curve(step_pin: S, dir_pin: D, acceleration: u16, curve: G) -> Self {
+        Self { rotary: StepDirRotary::new(step_pin, dir_pin), accel: Accelerator::new(acceleration, curve) }
+    }
+
+    pub fn rotary(&mut self) -> &mut StepDirRotary<S, D> {
+        &mut self.rotary
+    }
+
+    pub fn set_acceleration(&mut self, acceleration: u16) {
+        self.accel.acceleration = acceleration;
+    }
+
+    pub fn acceleration(&self) -> u16 {
+        self.accel.acceleration
+    }
+
+    pub fn set_curve(&mut self, curve: G) {
+        self.accel.curve = curve;
+    }
+
+    pub fn set_rate_acceleration(&mut self, window: MillisDurationU32, table: &'static [(u8, u16)]) {
+        self.accel.rate = RateWindow::new(window, table);
+    }
+
+    pub fn set_max_step(&mut self, max_step: u16) {
+        self.accel.max_step = max_step;
+    }
+
+    pub fn set_reversal_lockout(&mut self, lockout: MillisDurationU32) {
+        self.accel.reversal_lockout = lockout;
+    }
+
+    pub fn set_min_detent_interval(&mut self, interval: MillisDurationU32) {
+        self.accel.min_interval = interval;
+    }
+
+    pub fn velocity(&self, now: T) -> u16 {
+        self.accel.velocity(now)
+    }
+
+    /// Forgets the step history and the acceleration history.
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.accel.clear();
+    }
+
+    pub fn update(&mut self, now: T) -> Result<Rotation, RotaryError<S::Error, D::Error>> {
+        let rot = self.rotary.update()?;
+        Ok(self.accel.apply(now, rot))
+    }
+}
+
+/// [`StepDirRotary`] with a key, reporting like [`Encoder`](crate::encoder::Encoder).
+/// Pin errors of the step line are reported as `APin`, of the direction line as `BPin`.
+pub struct StepDirEncoder<S, D, K>
+where
+    S: ButtonInput,
+    K: ButtonInput,
+{
+    rotary: StepDirRotary<S, D>,
+    button: Button<K>,
+    rotated_on_hold: bool,
+}
+
+impl<S, D, K> StepDirEncoder<S, D, K>
+where
+    S: ButtonInput,
+    D: ButtonInput,
+    K: ButtonInput,
+{
+    pub fn new(step_pin: S, dir_pin: D, k_pin: K) -> Self {
+        Self {
+            rotary: StepDirRotary::new(step_pin, dir_pin),
+            button: Button::new(k_pin),
+            rotated_on_hold: false,
+        }
+    }
+
+    pub fn rotary(&mut self) -> &mut StepDirRotary<S, D> {
+        &mut self.rotary
+    }
+
+    pub fn button(&mut self) -> &mut Button<K> {
+        &mut self.button
+    }
+
+    pub fn into_parts(self) -> (S, D, K) {
+        let (step_pin, dir_pin) = self.rotary.into_parts();
+        (step_pin, dir_pin, self.button.into_pin())
+    }
+
+    pub fn reset(&mut self) {
+        self.rotary.reset();
+        self.button.reset();
+        self.rotated_on_hold = false;
+    }
+
+    pub fn update(&mut self) -> Result<EncoderAction, PinsError<S, D, K>> {
+        let rotation = self.rotary.update()?;
+        let btn_action = self.button.update()?;
+        Ok(combine(&mut self.rotated_on_hold, rotation, btn_action))
+    }
+}
@@ -0,0 +1,188 @@
+use core::cell::RefCell;
+use critical_section::Mutex;
+use crate::button::{self, Button, ButtonAction, ClockButton, TimeButton, TimeButtonAction};
+use crate::encoder::{ClockEncoder, Encoder, EncoderAction, PinsError, TimeEncoder, TimeEncoderAction};
+use crate::rotary::{AccelCurve, Steps};
+use crate::{ButtonInput, Clock, Instant};
+
+/// Encoder shared between an interrupt polling it and the main loop, every access inside
+/// a critical section. Starts empty so it can live in a `static`:
+///
+/// ```
+/// # use simple_encoder::encoder::Encoder;
+/// # use simple_encoder::rotary::FullStep;
+/// # use simple_encoder::sync::SharedEncoder;
+/// # use embedded_hal::digital::v2::InputPin;
+/// # struct Line;
+/// # impl InputPin for Line {
+/// #     type Error = core::convert::Infallible;
+/// #     fn is_high(&self) -> Result<bool, Self::Error> { Ok(true) }
+/// #     fn is_low(&self) -> Result<bool, Self::Error> { Ok(false) }
+/// # }
+/// static ENCODER: SharedEncoder<Encoder<Line, Line, Line, FullStep>> = SharedEncoder::new();
+/// ENCODER.init(Encoder::new(Line, Line, Line));
+/// let action = ENCODER.update(); // in the interrupt
+/// let handled = ENCODER.handle_press(); // in the main loop
+/// ```
+///
+/// Sync whenever the encoder is Send, i.e. when its pins and clock are. No unsafe impl
+/// is involved, the bounds are those of [`critical_section::Mutex`].
+pub struct SharedEncoder<E> {
+    inner: Mutex<RefCell<Option<E>>>,
+}
+
+impl<E> SharedEncoder<E> {
+    pub const fn new() -> Self {
+        Self { inner: Mutex::new(RefCell::new(None)) }
+    }
+
+    /// Installs the encoder, returning the previous one.
+    pub fn init(&self, encoder: E) -> Option<E> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).replace(encoder))
+    }
+
+    /// Removes the encoder, e.g. to get its pins back.
+    pub fn take(&self) -> Option<E> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).take())
+    }
+
+    /// Runs `f` on the encoder inside a critical section, `None` before [`init`](Self::init).
+    /// Keep `f` short, interrupts are masked while it runs.
+    pub fn with<R>(&self, f: impl FnOnce(&mut E) -> R) -> Option<R> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).as_mut().map(f))
+    }
+}
+
+impl<E> Default for SharedEncoder<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A, B, K, M: Steps> SharedEncoder<Encoder<A, B, K, M>>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    K: ButtonInput,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self) -> Result<EncoderAction, PinsError<A, B, K>> {
+        self.with(Encoder::update).unwrap_or(Ok(EncoderAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(Encoder::handle_press).unwrap_or(false)
+    }
+}
+
+impl<A, B, K, T, M: Steps, G> SharedEncoder<TimeEncoder<A, B, K, T, M, G>>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    K: ButtonInput,
+    T: Instant,
+    G: AccelCurve,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self, now: T) -> Result<TimeEncoderAction, PinsError<A, B, K>> {
+        self.with(|encoder| encoder.update(now)).unwrap_or(Ok(TimeEncoderAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(TimeEncoder::handle_press).unwrap_or(false)
+    }
+}
+
+impl<A, B, K, C, M: Steps, G> SharedEncoder<ClockEncoder<A, B, K, C, M, G>>
+where
+    A: ButtonInput,
+    B: ButtonInput,
+    K: ButtonInput,
+    C: Clock,
+    G: AccelCurve,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self) -> Result<TimeEncoderAction, PinsError<A, B, K>> {
+        self.with(ClockEncoder::update).unwrap_or(Ok(TimeEncoderAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(ClockEncoder::handle_press).unwrap_or(false)
+    }
+}
+
+/// Button counterpart of [`SharedEncoder`], with the same `static` use and Send/Sync bounds.
+pub struct SharedButton<B> {
+    inner: Mutex<RefCell<Option<B>>>,
+}
+
+impl<B> SharedButton<B> {
+    pub const fn new() -> Self {
+        Self { inner: Mutex::new(RefCell::new(None)) }
+    }
+
+    /// Installs the button, returning the previous one.
+    pub fn init(&self, button: B) -> Option<B> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).replace(button))
+    }
+
+    /// Removes the button, e.g. to get its pin back.
+    pub fn take(&self) -> Option<B> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).take())
+    }
+
+    /// Runs `f` on the button inside a critical section, `None` before [`init`](Self::init).
+    pub fn with<R>(&self, f: impl FnOnce(&mut B) -> R) -> Option<R> {
+        critical_section::with(|cs| self.inner.borrow_ref_mut(cs).as_mut().map(f))
+    }
+}
+
+impl<B> Default for SharedButton<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, const INVERTED: bool> SharedButton<Button<K, INVERTED>>
+where
+    K: ButtonInput,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self) -> Result<ButtonAction, button::Error<K::Error>> {
+        self.with(Button::update).unwrap_or(Ok(ButtonAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(Button::handle_press).unwrap_or(false)
+    }
+}
+
+impl<K, T, const INVERTED: bool> SharedButton<TimeButton<K, T, INVERTED>>
+where
+    K: ButtonInput,
+    T: Instant,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self, now: T) -> Result<TimeButtonAction, button::Error<K::Error>> {
+        self.with(|button| button.update(now)).unwrap_or(Ok(TimeButtonAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(TimeButton::handle_press).unwrap_or(false)
+    }
+}
+
+impl<K, C, const INVERTED: bool> SharedButton<ClockButton<K, C, INVERTED>>
+where
+    K: ButtonInput,
+    C: Clock,
+{
+    /// Reports `None` before [`init`](Self::init).
+    pub fn update(&self) -> Result<TimeButtonAction, button::Error<K::Error>> {
+        self.with(ClockButton::update).unwrap_or(Ok(TimeButtonAction::None))
+    }
+
+    pub fn handle_press(&self) -> bool {
+        self.with(ClockButton::handle_press).unwrap_or(false)
+    }
+}
@@ -1,8 +1,14 @@
-use fugit::{ExtU32, MillisDurationU32};
+use fugit::{ExtU32, MillisDurationU32, MillisDurationU64};
 
 pub trait Instant: Copy {
     fn duration_since(self, other: Self) -> MillisDurationU32;
     fn zero() -> Self;
+
+    /// Unsaturated duration; `duration_since` saturates at `u32::MAX` ms for wide instants.
+    #[inline]
+    fn wide_duration_since(self, other: Self) -> MillisDurationU64 {
+        MillisDurationU64::from_ticks(self.duration_since(other).ticks() as u64)
+    }
 }
 
 pub trait Clock {
@@ -59,17 +65,62 @@ impl<const NOM: u32, const DENOM: u32> Instant for fugit::Instant<u32, NOM, DENO
 impl<const NOM: u32, const DENOM: u32> Instant for fugit::Instant<u64, NOM, DENOM> {
     #[inline]
     fn duration_since(self, other: Self) -> MillisDurationU32 {
-        let dur = self - other;
-        let millis = dur.to_millis();
-        if millis <= u32::MAX as u64 {
-            millis as u32
-        } else {
-            u32::MAX
-        }.millis()
+        saturate(self.wide_duration_since(other))
     }
 
     #[inline(always)]
     fn zero() -> Self {
         Self::from_ticks(0)
     }
+
+    #[inline]
+    fn wide_duration_since(self, other: Self) -> MillisDurationU64 {
+        let dur = self - other;
+        MillisDurationU64::from_ticks(dur.to_millis())
+    }
+}
+
+#[inline]
+pub(crate) fn saturate(dur: MillisDurationU64) -> MillisDurationU32 {
+    (dur.ticks().min(u32::MAX as u64) as u32).millis()
+}
+
+/// Action together with the instant it was classified at.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Stamped<A, T> {
+    pub at: T,
+    pub action: A,
+}
+
+// Startup period during which devices sample pins but report nothing
+pub(crate) struct StartupGrace<T> {
+    grace: MillisDurationU32,
+    started_at: Option<T>,
+    active: bool,
+}
+
+impl<T: Instant> StartupGrace<T> {
+    pub(crate) fn new(grace: MillisDurationU32) -> Self {
+        Self {
+            grace,
+            started_at: None,
+            active: grace.ticks() != 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // Returns `true` on the update that ends the grace period
+    pub(crate) fn update(&mut self, now: T) -> bool {
+        let start = *self.started_at.get_or_insert(now);
+        if now.duration_since(start) >= self.grace {
+            self.active = false;
+            true
+        } else {
+            false
+        }
+    }
 }
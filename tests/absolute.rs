@@ -0,0 +1,61 @@
+mod common;
+
+use common::*;
+use simple_encoder::absolute::*;
+use simple_encoder::button::Polarity;
+
+fn binary_to_gray(value: u16) -> u16 {
+    value ^ (value >> 1)
+}
+
+#[test]
+fn gray_round_trip() {
+    for value in 0..=u16::MAX {
+        assert_eq!(gray_to_binary(binary_to_gray(value)), value);
+    }
+}
+
+#[test]
+fn sweep_with_noisy_transitions() {
+    let pins: [MockPin; 4] = core::array::from_fn(|_| MockPin::new(false));
+    let mut gray = AbsoluteGray::new(pins.each_ref(), 3);
+    for _ in 0..3 {
+        gray.update_with_code(binary_to_gray(0));
+    }
+    assert_eq!(gray.position(), Some(0));
+    let mut changes = Vec::new();
+    for to in (1..16).chain((0..15).rev()) {
+        let from = gray.position().unwrap();
+        let (old, new) = (binary_to_gray(from), binary_to_gray(to));
+        // Contact bounce between the codes and a glitch to an unrelated one
+        let noise = [new, old, new, new, old, 0b1111 ^ new, new, old];
+        for code in noise {
+            assert_eq!(gray.update_with_code(code), GrayAction::None);
+        }
+        for _ in 0..3 {
+            if let GrayAction::PositionChanged { from, to } = gray.update_with_code(new) {
+                changes.push((from, to));
+            }
+        }
+        assert_eq!(gray.position(), Some(to));
+    }
+    let expected: Vec<_> = (0..15).map(|p| (p, p + 1)).chain((0..15).rev().map(|p| (p + 1, p))).collect();
+    assert_eq!(changes, expected);
+}
+
+#[test]
+fn reads_active_low_pins() {
+    let pins: [MockPin; 4] = core::array::from_fn(|_| MockPin::new(true));
+    let mut gray = AbsoluteGray::new(pins.each_ref(), 2);
+    gray.set_polarity(Polarity::ActiveLow);
+    gray.update().unwrap();
+    gray.update().unwrap();
+    assert_eq!(gray.position(), Some(0));
+    // Gray 0b0110 is position 4
+    pins[1].set(false);
+    pins[2].set(false);
+    assert_eq!(gray.update().unwrap(), GrayAction::None);
+    assert_eq!(gray.update().unwrap(), GrayAction::PositionChanged { from: 0, to: 4 });
+    pins[3].set_failing(true);
+    assert_eq!(gray.update().unwrap_err().bit, 3);
+}
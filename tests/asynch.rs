@@ -0,0 +1,312 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::*;
+use core::convert::Infallible;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use embedded_hal_1::digital::{ErrorType, InputPin};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use fugit::ExtU32;
+use simple_encoder::asynch::{AsyncButton, AsyncRotary};
+use simple_encoder::button::TimeButtonAction;
+use simple_encoder::rotary::Rotation;
+use simple_encoder::Clock;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// Levels as (a_high, b_high), moved on by one whenever the device is pending
+struct Trace {
+    levels: Vec<(bool, bool)>,
+    at: usize,
+}
+
+struct Line {
+    trace: Rc<RefCell<Trace>>,
+    b: bool,
+}
+
+impl Line {
+    fn level(&self) -> bool {
+        let trace = self.trace.borrow();
+        let (a, b) = trace.levels[trace.at];
+        if self.b { b } else { a }
+    }
+
+    async fn wait_until(&mut self, high: bool) -> Result<(), Infallible> {
+        poll_fn(|_| match self.level() == high {
+            true => Poll::Ready(Ok(())),
+            false => Poll::Pending,
+        })
+        .await
+    }
+
+    // Waits for a change of level, to `high` unless `None`
+    async fn wait_edge(&mut self, high: Option<bool>) -> Result<(), Infallible> {
+        let mut last = self.level();
+        poll_fn(|_| {
+            let level = self.level();
+            let edge = level != last && high.is_none_or(|high| high == level);
+            last = level;
+            match edge {
+                true => Poll::Ready(Ok(())),
+                false => Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+impl ErrorType for Line {
+    type Error = Infallible;
+}
+
+impl InputPin for Line {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.level())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.level())
+    }
+}
+
+impl Wait for Line {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        self.wait_until(true).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        self.wait_until(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(Some(true)).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(Some(false)).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(None).await
+    }
+}
+
+// Polls until ready, stepping the trace while pending. `None` once the trace runs out
+fn run<F: Future>(trace: &RefCell<Trace>, future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return Some(out);
+        }
+        let mut trace = trace.borrow_mut();
+        if trace.at + 1 == trace.levels.len() {
+            return None;
+        }
+        trace.at += 1;
+    }
+}
+
+fn rotary(levels: &[(bool, bool)]) -> (Rc<RefCell<Trace>>, AsyncRotary<Line, Line>) {
+    let trace = Rc::new(RefCell::new(Trace { levels: levels.to_vec(), at: 0 }));
+    let a = Line { trace: trace.clone(), b: false };
+    let b = Line { trace: trace.clone(), b: true };
+    (trace, AsyncRotary::new(a, b))
+}
+
+#[test]
+fn awaits_full_detent() {
+    let mut levels = vec![(true, true)];
+    levels.extend_from_slice(&CW);
+    let (trace, mut rotary) = rotary(&levels);
+    assert_eq!(run(&trace, rotary.next_rotation()), Some(Ok(Rotation::CW_STEP)));
+    assert_eq!(trace.borrow().at, 4);
+}
+
+#[test]
+fn dropped_future_keeps_partial_detent() {
+    let mut levels = vec![(true, true)];
+    levels.extend_from_slice(&CW[..2]);
+    let (trace, mut rotary) = rotary(&levels);
+    assert_eq!(run(&trace, rotary.next_rotation()), None);
+    assert_eq!(rotary.decoder().sub_steps(), 2);
+    trace.borrow_mut().levels.extend_from_slice(&CW[2..]);
+    assert_eq!(run(&trace, rotary.next_rotation()), Some(Ok(Rotation::CW_STEP)));
+}
+
+#[test]
+fn mock_waits_for_edges() {
+    let trace = Rc::new(RefCell::new(Trace { levels: vec![(true, true), (false, true), (true, true)], at: 0 }));
+    let mut a = Line { trace: trace.clone(), b: false };
+    // Starting high, a rising edge needs the fall first
+    assert_eq!(run(&trace, a.wait_for_rising_edge()), Some(Ok(())));
+    assert_eq!(trace.borrow().at, 2);
+    trace.borrow_mut().at = 0;
+    assert_eq!(run(&trace, a.wait_for_any_edge()), Some(Ok(())));
+    assert_eq!(trace.borrow().at, 1);
+    assert_eq!(run(&trace, a.wait_for_falling_edge()), None);
+    let mut b = Line { trace: trace.clone(), b: true };
+    trace.borrow_mut().at = 0;
+    assert_eq!(run(&trace, b.wait_for_any_edge()), None);
+}
+
+// Virtual time in ms, moved on by one whenever the device is pending. The key is high
+// within the `[start, end)` spans
+struct Sim {
+    now: Cell<u32>,
+    presses: Vec<(u32, u32)>,
+}
+
+impl Sim {
+    fn level(&self) -> bool {
+        let now = self.now.get();
+        self.presses.iter().any(|&(start, end)| (start..end).contains(&now))
+    }
+}
+
+struct Key(Rc<Sim>);
+struct SimDelay(Rc<Sim>);
+struct SimClock(Rc<Sim>);
+
+impl Key {
+    async fn wait_until(&mut self, high: bool) -> Result<(), Infallible> {
+        poll_fn(|_| match self.0.level() == high {
+            true => Poll::Ready(Ok(())),
+            false => Poll::Pending,
+        })
+        .await
+    }
+
+    async fn wait_edge(&mut self, high: Option<bool>) -> Result<(), Infallible> {
+        let mut last = self.0.level();
+        poll_fn(|_| {
+            let level = self.0.level();
+            let edge = level != last && high.is_none_or(|high| high == level);
+            last = level;
+            match edge {
+                true => Poll::Ready(Ok(())),
+                false => Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+impl ErrorType for Key {
+    type Error = Infallible;
+}
+
+impl InputPin for Key {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.0.level())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.0.level())
+    }
+}
+
+impl Wait for Key {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        self.wait_until(true).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        self.wait_until(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(Some(true)).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(Some(false)).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_edge(None).await
+    }
+}
+
+impl DelayNs for SimDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        let until = self.0.now.get() + ns.div_ceil(1_000_000);
+        poll_fn(|_| match self.0.now.get() >= until {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        })
+        .await
+    }
+}
+
+impl Clock for SimClock {
+    type Instant = Ms;
+
+    fn now(&mut self) -> Ms {
+        Ms::from_ticks(self.0.now.get())
+    }
+}
+
+// Polls until ready, advancing the time while pending. `None` past `until`
+fn run_until<F: Future>(sim: &Sim, until: u32, future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(out) = future.as_mut().poll(&mut cx) {
+            return Some(out);
+        }
+        if sim.now.get() == until {
+            return None;
+        }
+        sim.now.set(sim.now.get() + 1);
+    }
+}
+
+fn button(presses: &[(u32, u32)]) -> (Rc<Sim>, AsyncButton<Key, SimDelay, Ms>, SimClock) {
+    let sim = Rc::new(Sim { now: Cell::new(0), presses: presses.to_vec() });
+    let button = AsyncButton::new(Key(sim.clone()), SimDelay(sim.clone()));
+    (sim.clone(), button, SimClock(sim))
+}
+
+#[test]
+fn async_button_press_held_click() {
+    use TimeButtonAction::*;
+    let (sim, mut button, mut clock) = button(&[(20, 250)]);
+    // Confirmed after the 10 ms debounce, `Held` every 100 ms, durations from the clock
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Press)));
+    assert_eq!(sim.now.get(), 30);
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Held(100.millis()))));
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Held(200.millis()))));
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Click(230.millis()))));
+    assert_eq!(sim.now.get(), 260);
+    // Nothing until the next edge
+    assert!(run_until(&sim, 1000, button.next(&mut clock)).is_none());
+}
+
+#[test]
+fn async_button_debounces_edges() {
+    use TimeButtonAction::*;
+    // A glitch gone by the confirming sample, then a press with a short dropout
+    let (sim, mut button, mut clock) = button(&[(5, 8), (50, 100), (103, 130)]);
+    button.set_held_interval(1000.millis());
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Press)));
+    assert_eq!(sim.now.get(), 60);
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Click(80.millis()))));
+}
+
+#[test]
+fn async_button_handled_press() {
+    use TimeButtonAction::*;
+    let (sim, mut button, mut clock) = button(&[(0, 250)]);
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Press)));
+    assert!(button.handle_press());
+    assert!(!button.handle_press());
+    // No `Held` for the handled press, its release is `Release`
+    assert_eq!(run_until(&sim, 1000, button.next(&mut clock)), Some(Ok(Release(250.millis()))));
+    assert!(!button.handle_press());
+}
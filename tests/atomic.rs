@@ -0,0 +1,145 @@
+mod common;
+
+use common::*;
+use simple_encoder::atomic::AtomicRotary;
+use simple_encoder::rotary::{FullStep, HalfStep, QuadratureDecoder, QuarterStep};
+use std::thread;
+
+fn low((a_high, b_high): (bool, bool)) -> (bool, bool) {
+    (!a_high, !b_high)
+}
+
+#[test]
+fn interleaved_feed_and_take() {
+    let rotary = AtomicRotary::new();
+    let (mut feed, take) = rotary.split::<FullStep>();
+    // Takes after every sample of a mixed trace, one per possible preemption point
+    let mut taken = 0;
+    for i in 0..60 {
+        let cycle = if i % 3 == 2 { &CCW } else { &CW };
+        for &levels in cycle {
+            let (a_low, b_low) = low(levels);
+            feed.feed(a_low, b_low);
+            taken += take.take().angle();
+        }
+    }
+    assert_eq!(taken, 20);
+    assert_eq!(rotary.take().angle(), 0);
+}
+
+// Samples of `detents` clockwise, negative counterclockwise
+fn trace(detents: i32) -> Vec<(bool, bool)> {
+    let cycle = if detents < 0 { &CCW } else { &CW };
+    cycle.iter().cycle().take(4 * detents.unsigned_abs() as usize).map(|&levels| low(levels)).collect()
+}
+
+#[test]
+fn take_between_partial_feeds() {
+    let samples = trace(3);
+    // Every choice of samples followed by a take
+    for takes in 0..1u32 << samples.len() {
+        let rotary = AtomicRotary::new();
+        let (mut feed, take) = rotary.split::<HalfStep>();
+        let mut reference = QuadratureDecoder::<HalfStep>::new();
+        let (mut taken, mut due) = (0, 0);
+        for (i, &(a_low, b_low)) in samples.iter().enumerate() {
+            feed.feed(a_low, b_low);
+            due += reference.step(a_low, b_low).angle();
+            if takes >> i & 1 == 1 {
+                assert_eq!(take.take().angle(), due, "takes {takes:#b} sample {i}");
+                taken += due;
+                due = 0;
+            }
+        }
+        assert_eq!(taken + rotary.take().angle(), 6);
+    }
+}
+
+#[test]
+fn two_feeders_in_flight() {
+    let (first, second) = (trace(2), trace(-1));
+    let len = first.len() + second.len();
+    // Every interleaving of the two feeds, with a take after each sample
+    for order in 0..1u32 << len {
+        if order.count_ones() as usize != second.len() {
+            continue;
+        }
+        let rotary = AtomicRotary::new();
+        let (mut feeds, take) = rotary.split::<FullStep>();
+        let (mut other, _) = rotary.split::<FullStep>();
+        let mut references = [QuadratureDecoder::<FullStep>::new(), QuadratureDecoder::new()];
+        let (mut next, mut taken) = ([0, 0], 0);
+        for i in 0..len {
+            let source = (order >> i & 1) as usize;
+            let (a_low, b_low) = [&first, &second][source][next[source]];
+            next[source] += 1;
+            match source {
+                0 => feeds.feed(a_low, b_low),
+                _ => other.feed(a_low, b_low),
+            }
+            let due = references[source].step(a_low, b_low).angle();
+            assert_eq!(take.take().angle(), due, "order {order:#b} sample {i}");
+            taken += due;
+        }
+        assert_eq!(taken, 1);
+    }
+}
+
+#[test]
+fn concurrent_feeders() {
+    let rotary = AtomicRotary::new();
+    let take = rotary.split::<QuarterStep>().1;
+    let taken = thread::scope(|s| {
+        let feeders: Vec<_> = [1, -1, 1]
+            .map(|direction| {
+                let (mut feed, _) = rotary.split::<QuarterStep>();
+                s.spawn(move || {
+                    for (a_low, b_low) in trace(direction).iter().cycle().take(4_000) {
+                        feed.feed(*a_low, *b_low);
+                    }
+                })
+            })
+            .into();
+        let mut taken = 0;
+        while !feeders.iter().all(|feeder| feeder.is_finished()) {
+            taken += take.take().angle();
+        }
+        taken
+    });
+    assert_eq!(taken + rotary.take().angle(), 4_000);
+}
+
+#[test]
+fn concurrent_feed_and_take() {
+    let rotary = AtomicRotary::new();
+    let (mut feed, take) = rotary.split::<FullStep>();
+    let taken = thread::scope(|s| {
+        let feeder = s.spawn(move || {
+            for _ in 0..10_000 {
+                for &levels in &CW {
+                    let (a_low, b_low) = low(levels);
+                    feed.feed(a_low, b_low);
+                }
+            }
+        });
+        let mut taken = 0;
+        while !feeder.is_finished() {
+            taken += take.take().angle();
+        }
+        taken
+    });
+    assert_eq!(taken + rotary.take().angle(), 10_000);
+}
+
+#[test]
+fn count_saturates() {
+    let rotary = AtomicRotary::new();
+    let (mut feed, _) = rotary.split::<QuarterStep>();
+    for _ in 0..10_000 {
+        for &levels in &CW {
+            let (a_low, b_low) = low(levels);
+            feed.feed(a_low, b_low);
+        }
+    }
+    assert_eq!(rotary.take().angle(), i16::MAX as i32);
+}
@@ -0,0 +1,1234 @@
+mod common;
+
+use common::*;
+use fugit::ExtU32;
+use simple_encoder::button::*;
+
+fn run(button: &mut Button<&MockPin>, pin: &MockPin, levels: &[bool]) -> Vec<ButtonAction> {
+    levels
+        .iter()
+        .map(|&high| {
+            pin.set(high);
+            button.update().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn press_held_click() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    assert_eq!(run(&mut button, &pin, &[false, true, true, true, false, false]), [None, Press, Held, Held, Click, None]);
+}
+
+#[test]
+fn bounce_is_filtered() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    button.set_debounce_samples(3);
+    let levels = [true, false, true, true, true, false, true, false, false, false];
+    let actions = run(&mut button, &pin, &levels);
+    assert_eq!(actions.iter().filter(|a| a.is_press()).count(), 1);
+    assert_eq!(actions.iter().filter(|a| a.is_click()).count(), 1);
+    assert_eq!(actions[4], Press);
+    assert_eq!(actions[9], Click);
+}
+
+#[test]
+fn handled_press_releases() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    run(&mut button, &pin, &[true]);
+    button.handle_press();
+    assert_eq!(run(&mut button, &pin, &[true, false]), [None, Release]);
+}
+
+#[test]
+fn pin_error() {
+    let pin = MockPin::new(false);
+    let mut button = Button::<_>::new(&pin);
+    pin.set_failing(true);
+    assert_eq!(button.update(), Err(Error::KPin(MockError)));
+}
+
+#[test]
+fn clock_button_accessors() {
+    let pin = MockPin::new(true);
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    button.clock().now = 50;
+    button.inner().update(Ms::from_ticks(60)).unwrap();
+    let (_, clock) = button.into_parts();
+    assert_eq!(clock.now, 50);
+}
+
+// Polls every `step` ms from 0 to `until`, pressed within the `[start, end)` spans.
+// Returns the actions other than `None` with their time
+fn timeline(
+    button: &mut TimeButton<&MockPin, Ms>,
+    pin: &MockPin,
+    presses: &[(u32, u32)],
+    step: u32,
+    until: u32,
+) -> Vec<(u32, TimeButtonAction)> {
+    (0..=until)
+        .step_by(step as usize)
+        .filter_map(|t| {
+            pin.set(presses.iter().any(|&(start, end)| (start..end).contains(&t)));
+            match button.update(Ms::from_ticks(t)).unwrap() {
+                TimeButtonAction::None => None,
+                act => Some((t, act)),
+            }
+        })
+        .collect()
+}
+
+fn without_held(actions: Vec<(u32, TimeButtonAction)>) -> Vec<(u32, TimeButtonAction)> {
+    actions.into_iter().filter(|(_, act)| act.held_duration().is_none()).collect()
+}
+
+#[test]
+fn double_click_within_window() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_double_click_window(&pin, 300.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (150, 200)], 10, 800));
+    assert_eq!(actions, [(0, Press), (50, Release(50.millis())), (150, Press), (200, DoubleClick(50.millis()))]);
+}
+
+#[test]
+fn single_click_waits_for_window() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_double_click_window(&pin, 300.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50)], 10, 800));
+    // Reported once the window since the release has passed
+    assert_eq!(actions, [(0, Press), (50, Release(50.millis())), (360, Click(50.millis()))]);
+
+    // Without the window the click is immediate
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50)], 10, 800));
+    assert_eq!(actions, [(0, Press), (50, Click(50.millis()))]);
+}
+
+#[test]
+fn double_click_window_expires_between_updates() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_double_click_window(&pin, 300.millis());
+    let mut at = |t, high| {
+        pin.set(high);
+        button.update(Ms::from_ticks(t)).unwrap()
+    };
+    assert_eq!([at(0, true), at(50, false)], [Press, Release(50.millis())]);
+    // The next update already sees a new press: the click comes first, the press next
+    assert_eq!(at(500, true), Click(50.millis()));
+    assert_eq!(at(510, true), Press);
+    // Timed from the real start of the press
+    assert_eq!(at(560, false), Release(60.millis()));
+}
+
+#[test]
+fn clock_button_double_click() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = ClockButton::<_, _>::with_double_click_window(&pin, StepClock { now: 0, step: 10 }, 100.millis());
+    let mut actions = Vec::new();
+    for high in [true, false, true, false] {
+        pin.set(high);
+        actions.push(button.update().unwrap());
+    }
+    assert_eq!(actions, [Press, Release(10.millis()), Press, DoubleClick(10.millis())]);
+}
+
+#[test]
+fn long_press_fires_once() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_long_press(100.millis());
+    let actions = timeline(&mut button, &pin, &[(0, 200)], 25, 300);
+    assert_eq!(
+        actions,
+        [
+            (0, Press),
+            (25, Held(25.millis())),
+            (50, Held(50.millis())),
+            (75, Held(75.millis())),
+            (100, LongPress(100.millis())),
+            (125, Held(125.millis())),
+            (150, Held(150.millis())),
+            (175, Held(175.millis())),
+            (200, Release(200.millis())),
+        ]
+    );
+    // A shorter press still clicks
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_long_press(100.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50)], 25, 300));
+    assert_eq!(actions, [(0, Press), (50, Click(50.millis()))]);
+}
+
+#[test]
+fn handled_press_never_long_presses() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_long_press(100.millis());
+    pin.set(true);
+    assert_eq!(button.update(Ms::from_ticks(0)).unwrap(), Press);
+    assert!(button.handle_press());
+    let actions = timeline(&mut button, &pin, &[(0, 200)], 25, 300);
+    assert_eq!(actions, [(200, Release(200.millis()))]);
+}
+
+#[test]
+fn long_press_is_no_double_click() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_double_click_window(&pin, 300.millis());
+    button.set_long_press(100.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (100, 300)], 25, 400));
+    assert_eq!(actions, [(0, Press), (50, Release(50.millis())), (100, Press), (200, LongPress(100.millis())), (300, Release(200.millis()))]);
+}
+
+fn sample(button: &mut TimeButton<&MockPin, Ms>, pin: &MockPin, t: u32, high: bool) -> TimeButtonAction {
+    pin.set(high);
+    button.update(Ms::from_ticks(t)).unwrap()
+}
+
+#[test]
+fn handled_time_button_releases_with_duration() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert_eq!(sample(&mut button, &pin, 10, true), Press);
+    assert!(button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 30, true), None);
+    assert_eq!(sample(&mut button, &pin, 90, false), Release(80.millis()));
+    assert_eq!(sample(&mut button, &pin, 100, false), None);
+}
+
+// Contact bounce of `flips` single sample flips settling at `level`
+fn bouncing(level: bool, flips: usize) -> Vec<bool> {
+    let mut levels: Vec<_> = (0..flips).map(|i| (i % 2 == 0) == level).collect();
+    levels.extend([level; 8]);
+    levels
+}
+
+#[test]
+fn debounce_depth_gives_one_press_per_bounce() {
+    for samples in [2, 4, 6] {
+        let pin = MockPin::new(false);
+        let mut button = Button::new(&pin);
+        button.set_debounce_samples(samples);
+        let mut levels = bouncing(true, 9);
+        levels.extend(bouncing(false, 9));
+        let actions = run(&mut button, &pin, &levels);
+        assert_eq!(actions.iter().filter(|a| a.is_press()).count(), 1, "{samples} samples");
+        assert_eq!(actions.iter().filter(|a| a.is_click()).count(), 1, "{samples} samples");
+    }
+    // The default accepts every sample, so each bounce is a click
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    let actions = run(&mut button, &pin, &bouncing(true, 9));
+    assert_eq!(actions.iter().filter(|a| a.is_press()).count(), 5);
+    assert_eq!(actions.iter().filter(|a| a.is_click()).count(), 4);
+}
+
+#[test]
+fn time_button_debounce_samples() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_debounce_samples(3);
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 10), (20, 100)], 10, 200));
+    assert_eq!(actions, [(40, Press), (120, Click(80.millis()))]);
+}
+
+#[test]
+fn time_debounce_ignores_short_presses() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_debounce(10.millis());
+    // Shorter than the window: nothing at all
+    assert_eq!(timeline(&mut button, &pin, &[(10, 18)], 2, 100), []);
+    // Accepted once the level lasted the window, durations count from then
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_debounce(10.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(10, 60)], 2, 100));
+    assert_eq!(actions, [(20, Press), (70, Click(50.millis()))]);
+}
+
+#[test]
+fn time_debounce_merges_short_releases() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_debounce(10.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (54, 100)], 2, 200));
+    assert_eq!(actions, [(10, Press), (110, Click(100.millis()))]);
+    // Zero restores immediate levels
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_debounce(10.millis());
+    button.set_debounce(0.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 4)], 2, 20));
+    assert_eq!(actions, [(0, Press), (4, Click(4.millis()))]);
+}
+
+#[test]
+fn repeat_after_initial_delay() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_repeat(300.millis(), 100.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 650)], 10, 800));
+    assert_eq!(
+        actions,
+        [(0, Press), (300, Repeat(1)), (400, Repeat(1)), (500, Repeat(1)), (600, Repeat(1)), (650, Release(650.millis()))]
+    );
+    // A press too short to repeat still clicks
+    let actions = without_held(timeline(&mut button, &pin, &[(1000, 1200)], 10, 1300));
+    assert_eq!(actions, [(1000, Press), (1200, Click(200.millis()))]);
+}
+
+#[test]
+fn repeat_follows_time_not_updates() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_repeat(300.millis(), 100.millis());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(sample(&mut button, &pin, 350, true), Repeat(1));
+    // Three steps were due since the last report
+    assert_eq!(sample(&mut button, &pin, 620, true), Repeat(3));
+    assert_eq!(sample(&mut button, &pin, 690, true), Held(690.millis()));
+    assert_eq!(sample(&mut button, &pin, 700, true), Repeat(1));
+    assert_eq!(sample(&mut button, &pin, 710, false), Release(710.millis()));
+}
+
+#[test]
+fn repeat_is_disabled_by_default() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 100 });
+    let mut actions = Vec::new();
+    for high in [true; 10].into_iter().chain([false]) {
+        pin.set(high);
+        actions.push(button.update().unwrap());
+    }
+    assert!(actions[1..10].iter().all(|act| act.held_duration().is_some()));
+    assert_eq!(actions[10], Click(1000.millis()));
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 100 });
+    button.set_repeat(300.millis(), 200.millis());
+    let mut actions = Vec::new();
+    for high in [true; 10].into_iter().chain([false]) {
+        pin.set(high);
+        actions.push(button.update().unwrap());
+    }
+    let repeats: Vec<_> = actions.iter().filter(|act| matches!(act, Repeat(_))).collect();
+    assert_eq!(repeats, [&Repeat(1); 4]);
+    assert_eq!(actions[10], Release(1000.millis()));
+}
+
+#[test]
+fn held_interval_limits_reports() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_held_interval(100.millis());
+    let actions = timeline(&mut button, &pin, &[(0, 450)], 10, 500);
+    // The first is prompt, the durations are counted from the press
+    assert_eq!(
+        actions,
+        [
+            (0, Press),
+            (10, Held(10.millis())),
+            (110, Held(110.millis())),
+            (210, Held(210.millis())),
+            (310, Held(310.millis())),
+            (410, Held(410.millis())),
+            (450, Click(450.millis())),
+        ]
+    );
+    // The next press starts over
+    let actions = timeline(&mut button, &pin, &[(600, 750)], 10, 800);
+    assert_eq!(actions, [(600, Press), (610, Held(10.millis())), (710, Held(110.millis())), (750, Click(150.millis()))]);
+}
+
+#[test]
+fn multi_click_counts_clicks_in_window() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_multi_click(200.millis(), 300.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (100, 150), (200, 260)], 10, 600));
+    assert_eq!(
+        actions,
+        [
+            (0, Press),
+            (50, Release(50.millis())),
+            (100, Press),
+            (150, Release(50.millis())),
+            (200, Press),
+            (260, Release(60.millis())),
+            (470, MultiClick { count: 3, last_duration: 60.millis() }),
+        ]
+    );
+    // A single click is a count of one
+    let actions = without_held(timeline(&mut button, &pin, &[(1000, 1040)], 10, 1300));
+    assert_eq!(actions, [(1000, Press), (1040, Release(40.millis())), (1250, MultiClick { count: 1, last_duration: 40.millis() })]);
+}
+
+#[test]
+fn multi_click_resets_on_long_press() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_multi_click(200.millis(), 300.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (100, 500)], 10, 900));
+    assert_eq!(actions, [(0, Press), (50, Release(50.millis())), (100, Press), (500, Click(400.millis()))]);
+}
+
+#[test]
+fn handled_press_cancels_multi_click() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_multi_click(200.millis(), 300.millis());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(sample(&mut button, &pin, 50, false), Release(50.millis()));
+    assert_eq!(sample(&mut button, &pin, 100, true), Press);
+    assert!(button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 150, false), Release(50.millis()));
+    assert_eq!(sample(&mut button, &pin, 600, false), None);
+}
+
+#[test]
+fn min_press_drops_glitches() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_min_press(20.millis());
+    assert_eq!(timeline(&mut button, &pin, &[(0, 15)], 5, 50), []);
+    // `Press` comes once the threshold is reached, durations still count from the real press
+    let actions = timeline(&mut button, &pin, &[(100, 135)], 5, 200);
+    assert_eq!(
+        actions,
+        [(120, Press), (125, Held(25.millis())), (130, Held(30.millis())), (135, Click(35.millis()))]
+    );
+}
+
+#[test]
+fn min_press_handled_while_deferred() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_min_press(20.millis());
+    assert_eq!(sample(&mut button, &pin, 0, true), None);
+    assert_eq!(sample(&mut button, &pin, 5, true), None);
+    assert!(button.handle_press());
+    assert!(!button.handle_press());
+    // The handled press reports nothing at all
+    for t in [10, 30, 50] {
+        assert_eq!(sample(&mut button, &pin, t, true), None);
+    }
+    assert_eq!(sample(&mut button, &pin, 60, false), None);
+    let actions = without_held(timeline(&mut button, &pin, &[(200, 250)], 10, 300));
+    assert_eq!(actions, [(220, Press), (250, Click(50.millis()))]);
+}
+
+#[test]
+fn click_threshold_classifies_clicks() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_click_threshold(100.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 90), (200, 300)], 10, 400));
+    assert_eq!(actions, [(0, Press), (90, ShortClick(90.millis())), (200, Press), (300, LongClick(100.millis()))]);
+    // The held stream is the same as without the threshold
+    let mut plain = TimeButton::<_, Ms>::new(&pin);
+    let held = |actions: Vec<(u32, TimeButtonAction)>| -> Vec<_> {
+        actions.into_iter().filter(|(_, act)| act.held_duration().is_some()).collect()
+    };
+    let classified = held(timeline(&mut button, &pin, &[(500, 700)], 10, 800));
+    assert_eq!(classified, held(timeline(&mut plain, &pin, &[(500, 700)], 10, 800)));
+    assert_eq!(classified.len(), 19);
+}
+
+#[test]
+fn state_accessors_do_not_disturb_updates() {
+    let levels = [false, true, false, true, true, true, true, false, true, false, false, false, false];
+    let pin = MockPin::new(false);
+    let mut plain = Button::new(&pin);
+    plain.set_debounce_samples(3);
+    let expected = run(&mut plain, &pin, &levels);
+
+    let mut button = Button::<_>::new(&pin);
+    button.set_debounce_samples(3);
+    for (&high, &action) in levels.iter().zip(&expected) {
+        pin.set(high);
+        assert_eq!(button.update().unwrap(), action);
+        // Down from `Press` through `Held`, the debounced level agrees
+        assert_eq!(button.is_down(), action.is_press() || action.is_held());
+        assert_eq!(button.last_level(), button.is_down());
+    }
+}
+
+#[test]
+fn time_button_state_accessors() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert!(!button.is_down());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert!(button.is_down() && button.last_level());
+    assert_eq!(sample(&mut button, &pin, 10, true), Held(10.millis()));
+    assert!(button.is_down());
+    assert_eq!(sample(&mut button, &pin, 20, false), Click(20.millis()));
+    assert!(!button.is_down() && !button.last_level());
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), Press);
+    assert!(button.is_down() && button.last_level());
+}
+
+#[test]
+fn press_duration_between_updates() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    // Nothing pressed yet, even though the clock is at its zero instant
+    assert!(button.press_duration(Ms::from_ticks(0)).is_none());
+    assert_eq!(sample(&mut button, &pin, 0, false), None);
+    assert!(button.press_duration(Ms::from_ticks(0)).is_none());
+    // A press at the zero instant is a known press start
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(button.press_duration(Ms::from_ticks(0)), Some(0.millis()));
+    assert_eq!(button.press_duration(Ms::from_ticks(37)), Some(37.millis()));
+    // Querying does not disturb the event stream
+    assert_eq!(sample(&mut button, &pin, 40, true), Held(40.millis()));
+    assert_eq!(sample(&mut button, &pin, 50, false), Click(50.millis()));
+    assert!(button.press_duration(Ms::from_ticks(60)).is_none());
+}
+
+#[test]
+fn press_duration_after_handle_press() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert_eq!(sample(&mut button, &pin, 10, true), Press);
+    assert!(button.handle_press());
+    assert!(button.press_duration(Ms::from_ticks(20)).is_none());
+    assert_eq!(sample(&mut button, &pin, 30, true), None);
+    assert!(button.press_duration(Ms::from_ticks(30)).is_none());
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), Press);
+    // The clock is read once more for the query
+    assert_eq!(button.press_duration(), Some(10.millis()));
+    assert!(button.handle_press());
+    assert!(button.press_duration().is_none());
+}
+
+#[test]
+fn handle_press_reports_whether_armed() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    assert!(!button.handle_press());
+    assert_eq!(run(&mut button, &pin, &[true]), [Press]);
+    assert!(button.handle_press());
+    // Already armed
+    assert!(!button.handle_press());
+    assert_eq!(run(&mut button, &pin, &[true, false]), [None, Release]);
+    // Only right after `Press`, not while held
+    assert_eq!(run(&mut button, &pin, &[true, true]), [Press, Held]);
+    assert!(!button.handle_press());
+    assert_eq!(run(&mut button, &pin, &[false]), [Click]);
+
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert!(!button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 0, true), TimeButtonAction::Press);
+    assert!(button.handle_press());
+    assert!(!button.handle_press());
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    assert!(!button.handle_press());
+    assert_eq!(button.update().unwrap(), TimeButtonAction::Press);
+    assert!(button.handle_press());
+    assert!(!button.handle_press());
+}
+
+#[test]
+fn suppression_ends_with_its_press() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    assert_eq!(run(&mut button, &pin, &[true]), [Press]);
+    assert!(button.handle_press());
+    // Released before the next update
+    assert_eq!(run(&mut button, &pin, &[false]), [Release]);
+    assert!(run(&mut button, &pin, &[false; 1000]).iter().all(ButtonAction::is_none));
+    assert_eq!(run(&mut button, &pin, &[true, true, false]), [Press, Held, Click]);
+
+    // Same with the time button over a long idle period
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert_eq!(sample(&mut button, &pin, 0, true), TimeButtonAction::Press);
+    assert!(button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 10, false), TimeButtonAction::Release(10.millis()));
+    for t in (1000..100_000).step_by(1000) {
+        assert_eq!(sample(&mut button, &pin, t, false), TimeButtonAction::None);
+    }
+    assert_eq!(sample(&mut button, &pin, 100_000, true), TimeButtonAction::Press);
+    assert_eq!(sample(&mut button, &pin, 100_050, false), TimeButtonAction::Click(50.millis()));
+}
+
+#[test]
+fn suppressed_press_has_no_known_start() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert!(button.handle_press());
+    assert!(button.press_duration(Ms::from_ticks(100)).is_none());
+    // The release is still timed from the physical press
+    assert_eq!(sample(&mut button, &pin, 5000, false), Release(5000.millis()));
+    // The next press is timed normally
+    assert_eq!(sample(&mut button, &pin, 8000, true), Press);
+    assert_eq!(sample(&mut button, &pin, 8040, false), Click(40.millis()));
+}
+
+fn seeded<const INVERTED: bool>(pin: &MockPin, pressed: bool) -> Vec<ButtonAction> {
+    let level = |pressed: bool| pressed != INVERTED;
+    pin.set(level(pressed));
+    let mut button = Button::<_, INVERTED>::new_from_pin(pin).unwrap();
+    [true, true, false, true, false]
+        .iter()
+        .map(|&p| {
+            pin.set(level(p));
+            button.update().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn new_from_pin_skips_held_press() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    // Held at creation: nothing until the release, the next press is normal
+    assert_eq!(seeded::<false>(&pin, true), [None, None, Release, Press, Click]);
+    assert_eq!(seeded::<true>(&pin, true), [None, None, Release, Press, Click]);
+    // Up at creation: same as `new`
+    assert_eq!(seeded::<false>(&pin, false), [Press, Held, Click, Press, Click]);
+    assert_eq!(seeded::<true>(&pin, false), [Press, Held, Click, Press, Click]);
+    // `new` still reports the held press
+    pin.set(true);
+    let mut button = Button::<_>::new(&pin);
+    assert_eq!(button.update().unwrap(), Press);
+}
+
+#[test]
+fn time_button_new_from_pin_skips_held_press() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(true);
+    let mut button = TimeButton::<_, Ms>::new_from_pin(&pin).unwrap();
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 50), (100, 150)], 10, 200));
+    // The release is timed from the first update that saw the key down
+    assert_eq!(actions, [(50, Release(50.millis())), (100, Press), (150, Click(50.millis()))]);
+
+    let mut button = ClockButton::<_, _>::new_from_pin(&pin, StepClock { now: 0, step: 10 }).unwrap();
+    assert_eq!(button.update().unwrap(), None);
+}
+
+#[test]
+fn runtime_polarity() {
+    use ButtonAction::*;
+    let pin = MockPin::new(true);
+    let mut button = Button::with_polarity(&pin, Polarity::ActiveLow);
+    assert_eq!(button.polarity(), Polarity::ActiveLow);
+    assert_eq!(run(&mut button, &pin, &[true, false, false, true]), [None, Press, Held, Click]);
+}
+
+#[test]
+fn polarity_flip_reports_no_edge() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    assert_eq!(run(&mut button, &pin, &[false, false]), [None, None]);
+    // The low level now reads as pressed, but nothing was pressed
+    button.set_polarity(Polarity::ActiveLow);
+    let actions = run(&mut button, &pin, &[false, false]);
+    assert!(!actions.iter().any(ButtonAction::is_press), "{actions:?}");
+    // Setting the same polarity changes nothing
+    button.set_polarity(Polarity::ActiveLow);
+    assert!(!run(&mut button, &pin, &[false]).iter().any(ButtonAction::is_press));
+    // Real edges follow the new polarity
+    run(&mut button, &pin, &[true]);
+    assert_eq!(run(&mut button, &pin, &[false, true]), [Press, Click]);
+}
+
+#[test]
+fn missed_press_edge_has_zero_duration() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    // A suppressed press long before
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert!(button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 10, false), Release(10.millis()));
+    // The level turns pressed without an edge, so the next press start is unknown
+    assert_eq!(sample(&mut button, &pin, 5000, false), None);
+    button.set_polarity(Polarity::ActiveLow);
+    assert_eq!(sample(&mut button, &pin, 6000, false), Held(0.millis()));
+    assert!(button.press_duration(Ms::from_ticks(6000)).is_none());
+    assert_eq!(sample(&mut button, &pin, 7000, true), Click(0.millis()));
+    // The next seen press is timed normally
+    assert_eq!(sample(&mut button, &pin, 8000, false), Press);
+    assert_eq!(sample(&mut button, &pin, 8040, true), Click(40.millis()));
+}
+
+#[test]
+fn edge_button_reports_edges_only() {
+    use simple_encoder::debounce::Edge;
+    let pin = MockPin::new(false);
+    let mut button = EdgeButton::<_>::new(&pin);
+    let mut edges = Vec::new();
+    for high in [false, true, true, true, false, false] {
+        pin.set(high);
+        edges.push(button.update().unwrap());
+    }
+    assert_eq!(edges, [Edge::None, Edge::Pressed, Edge::None, Edge::None, Edge::Released, Edge::None]);
+
+    // Debounced like `Button`
+    let mut button = EdgeButton::<_>::new(&pin);
+    button.set_debounce_samples(3);
+    let mut edges = Vec::new();
+    for high in bouncing(true, 5).into_iter().chain(bouncing(false, 5)) {
+        pin.set(high);
+        edges.push(button.update().unwrap());
+    }
+    assert_eq!(edges.iter().filter(|&&e| e == Edge::Pressed).count(), 1);
+    assert_eq!(edges.iter().filter(|&&e| e == Edge::Released).count(), 1);
+}
+
+#[test]
+fn edge_button_matches_button() {
+    use simple_encoder::debounce::Edge;
+    let levels = [true, false, true, true, true, false, true, false, false, false, true, true];
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    button.set_debounce_samples(2);
+    let actions = run(&mut button, &pin, &levels);
+    let mut edge = EdgeButton::<_>::new(&pin);
+    edge.set_debounce_samples(2);
+    for (&high, action) in levels.iter().zip(actions) {
+        pin.set(high);
+        let expected = match action {
+            ButtonAction::Press => Edge::Pressed,
+            ButtonAction::Click => Edge::Released,
+            _ => Edge::None,
+        };
+        assert_eq!(edge.update().unwrap(), expected);
+    }
+}
+
+fn toggle(button: &mut ToggleButton<&MockPin>, pin: &MockPin, levels: &[bool]) -> Vec<ToggleAction> {
+    levels
+        .iter()
+        .map(|&high| {
+            pin.set(high);
+            button.update().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn toggle_flips_on_clicks() {
+    use ToggleAction::*;
+    let pin = MockPin::new(false);
+    let mut button = ToggleButton::new(&pin);
+    assert!(!button.state());
+    assert_eq!(toggle(&mut button, &pin, &[true, false]), [None, On]);
+    assert!(button.state());
+    // A long hold flips only once
+    let mut hold = vec![true; 50];
+    hold.push(false);
+    let actions = toggle(&mut button, &pin, &hold);
+    assert_eq!(actions.iter().filter(|a| **a != None).collect::<Vec<_>>(), [&Off]);
+    assert!(!button.state());
+}
+
+#[test]
+fn toggle_state_is_settable_and_suppressible() {
+    use ToggleAction::*;
+    let pin = MockPin::new(false);
+    let mut button = ToggleButton::new(&pin);
+    button.set_state(true);
+    assert_eq!(toggle(&mut button, &pin, &[true]), [None]);
+    assert!(button.handle_press());
+    assert_eq!(toggle(&mut button, &pin, &[true, false]), [None, None]);
+    assert!(button.state());
+    assert_eq!(toggle(&mut button, &pin, &[true, false]), [None, Off]);
+}
+
+#[test]
+fn reset_forgets_history() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    assert_eq!(run(&mut button, &pin, &[true, true]), [Press, Held]);
+    button.reset();
+    // The stale press yields no phantom click
+    assert_eq!(run(&mut button, &pin, &[false, false]), [None, None]);
+    assert_eq!(run(&mut button, &pin, &[true, false]), [Press, Click]);
+
+    // Seeded from the pin, a held key stays silent until released
+    assert_eq!(run(&mut button, &pin, &[true]), [Press]);
+    button.reset_from_pin().unwrap();
+    assert_eq!(run(&mut button, &pin, &[true, true, false, true]), [None, None, Release, Press]);
+}
+
+#[test]
+fn time_button_reset_drops_pending_click() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_double_click_window(&pin, 300.millis());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(sample(&mut button, &pin, 50, false), Release(50.millis()));
+    button.reset();
+    assert_eq!(sample(&mut button, &pin, 100, false), None);
+    assert_eq!(sample(&mut button, &pin, 1000, false), None);
+    assert!(button.press_duration(Ms::from_ticks(1000)).is_none());
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), Press);
+    button.reset_from_pin().unwrap();
+    assert_eq!(button.update().unwrap(), None);
+    pin.set(false);
+    // Timed from the first update after the reset
+    assert_eq!(button.update().unwrap(), Release(10.millis()));
+}
+
+#[test]
+fn chord_suppresses_clicks_of_its_buttons() {
+    use ButtonAction::*;
+    let mut chord = Chord::new([0b011, 0b101]);
+    let mut step = |actions: [ButtonAction; 3]| {
+        let mut actions = actions;
+        let chord_action = chord.update(&mut actions);
+        (chord_action, actions)
+    };
+    assert_eq!(step([Press, None, None]), (ChordAction::None, [Press, None, None]));
+    assert_eq!(step([Held, Press, None]), (ChordAction::Chord(0), [Held, Press, None]));
+    // Fires once while held
+    assert_eq!(step([Held, Held, None]), (ChordAction::None, [Held, Held, None]));
+    assert_eq!(step([Click, Held, None]), (ChordAction::None, [Release, Held, None]));
+    assert_eq!(step([None, Click, None]), (ChordAction::None, [None, Release, None]));
+    // Abandoned partial chord: normal clicks
+    assert_eq!(step([None, None, Press]), (ChordAction::None, [None, None, Press]));
+    assert_eq!(step([None, None, Click]), (ChordAction::None, [None, None, Click]));
+    // The second combination
+    assert_eq!(step([Press, None, Press]), (ChordAction::Chord(1), [Press, None, Press]));
+    assert_eq!(step([Click, None, Click]), (ChordAction::None, [Release, None, Release]));
+}
+
+#[test]
+fn chord_tracks_down_buttons() {
+    use ButtonAction::*;
+    let mut chord = Chord::new([0b11]);
+    assert_eq!(chord.update(&mut [Press, None]), ChordAction::None);
+    assert_eq!(chord.down(), 0b01);
+    assert_eq!(chord.update(&mut [Release, Press]), ChordAction::None);
+    assert_eq!(chord.down(), 0b10);
+    let mut actions = [None, Click];
+    assert_eq!(chord.update(&mut actions), ChordAction::None);
+    assert_eq!(actions, [None, Click]);
+    assert_eq!(chord.down(), 0);
+}
+
+const LADDER: [(u16, u16); 3] = [(100, 200), (400, 500), (700, 800)];
+
+fn ladder_trace(buttons: &mut LadderButtons<3>, samples: &[u16]) -> Vec<[ButtonAction; 3]> {
+    samples.iter().map(|&sample| buttons.update_with_sample(sample)).collect()
+}
+
+#[test]
+fn ladder_decodes_windows() {
+    use ButtonAction::*;
+    let mut buttons = LadderButtons::new(LADDER);
+    let actions = ladder_trace(&mut buttons, &[1023, 450, 460, 1023, 150]);
+    assert_eq!(actions, [[None; 3], [None, Press, None], [None, Held, None], [None, Click, None], [Press, None, None]]);
+    assert!(buttons.is_down(0) && !buttons.is_down(1));
+    // Between the windows is no button
+    assert_eq!(ladder_trace(&mut buttons, &[300]), [[Click, None, None]]);
+    assert_eq!(ladder_trace(&mut buttons, &[600, 650]), [[None; 3]; 2]);
+}
+
+#[test]
+fn ladder_filters_noisy_trace() {
+    let mut buttons = LadderButtons::new(LADDER);
+    buttons.set_debounce_samples(3);
+    // Idle with spikes into a window, a noisy press of button 1 with a dropout, idle again
+    let trace = [
+        1023, 1020, 180, 1023, 1019, 760, 1022, 1023, 470, 455, 512, 448, 463, 451, 330, 458, 466, 1023, 1018, 1021,
+        1023,
+    ];
+    let actions = ladder_trace(&mut buttons, &trace);
+    let count = |i: usize, f: fn(&ButtonAction) -> bool| actions.iter().filter(|a| f(&a[i])).count();
+    assert_eq!(count(1, ButtonAction::is_press), 1);
+    assert_eq!(count(1, ButtonAction::is_click), 1);
+    for i in [0, 2] {
+        assert_eq!(count(i, ButtonAction::is_press), 0);
+    }
+}
+
+#[test]
+fn ladder_overlap_matches_first_window() {
+    use ButtonAction::*;
+    let mut buttons = LadderButtons::new([(100, 300), (200, 400)]);
+    assert_eq!(buttons.update_with_sample(250), [Press, None]);
+    assert_eq!(buttons.update_with_sample(350), [Click, Press]);
+}
+
+#[test]
+fn time_since_last_click() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    assert!(button.time_since_last_click(Ms::from_ticks(0)).is_none());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(sample(&mut button, &pin, 40, false), Click(40.millis()));
+    assert_eq!(button.time_since_last_click(Ms::from_ticks(100)), Some(60.millis()));
+    // A suppressed press keeps the previous click
+    assert_eq!(sample(&mut button, &pin, 200, true), Press);
+    assert!(button.handle_press());
+    assert_eq!(sample(&mut button, &pin, 250, false), Release(50.millis()));
+    assert_eq!(button.time_since_last_click(Ms::from_ticks(300)), Some(260.millis()));
+    assert_eq!(sample(&mut button, &pin, 400, true), Press);
+    assert_eq!(sample(&mut button, &pin, 420, false), Click(20.millis()));
+    assert_eq!(button.time_since_last_click(Ms::from_ticks(420)), Some(0.millis()));
+}
+
+#[test]
+fn hold_milestones_fire_once() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_hold_milestones(&[100.millis(), 250.millis()]);
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 300)], 20, 400));
+    assert_eq!(
+        actions,
+        [(0, Press), (100, HoldMilestone(0, 100.millis())), (260, HoldMilestone(1, 260.millis())), (300, Click(300.millis()))]
+    );
+    // The next press starts over
+    let actions = without_held(timeline(&mut button, &pin, &[(500, 620)], 20, 700));
+    assert_eq!(actions, [(500, Press), (600, HoldMilestone(0, 100.millis())), (620, Click(120.millis()))]);
+}
+
+#[test]
+fn hold_milestones_skip_handled_press() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_hold_milestones(&[100.millis()]);
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert!(button.handle_press());
+    for t in (20..300).step_by(20) {
+        assert_eq!(sample(&mut button, &pin, t, true), None);
+    }
+    assert_eq!(sample(&mut button, &pin, 300, false), Release(300.millis()));
+}
+
+#[test]
+fn startup_grace_hides_noisy_prefix() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::with_startup_grace(&pin, 50.millis());
+    // Garbage levels while the pull-up settles, then a clean click
+    let noise = [(0, 5), (10, 12), (20, 30), (35, 40), (45, 48)];
+    let mut presses = noise.to_vec();
+    presses.push((100, 150));
+    let actions = without_held(timeline(&mut button, &pin, &presses, 1, 200));
+    assert_eq!(actions, [(100, Press), (150, Click(50.millis()))]);
+
+    // Held when the grace ends: treated as a press already in progress at creation,
+    // its release timed from the first update after the grace
+    let mut button = TimeButton::<_, Ms>::with_startup_grace(&pin, 50.millis());
+    let actions = without_held(timeline(&mut button, &pin, &[(0, 5), (30, 80), (100, 120)], 1, 200));
+    assert_eq!(actions, [(80, Release(29.millis())), (100, Press), (120, Click(20.millis()))]);
+}
+
+#[test]
+fn clock_button_parts_round_trip() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut inner = TimeButton::<_, Ms>::new(&pin);
+    inner.set_long_press(100.millis());
+    let mut button = ClockButton::from_parts(inner, StepClock { now: 0, step: 10 });
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), Press);
+    // The press in progress and the settings survive
+    let (inner, clock) = button.into_parts();
+    assert!(inner.is_down());
+    let mut button = ClockButton::from_parts(inner, clock);
+    assert_eq!(button.button().press_duration(Ms::from_ticks(50)), Some(40.millis()));
+    let actions: Vec<_> = (0..10).map(|_| button.update().unwrap()).collect();
+    assert!(actions.contains(&LongPress(100.millis())), "{actions:?}");
+    pin.set(false);
+    assert_eq!(button.update().unwrap(), Release(110.millis()));
+    assert_eq!(button.clock().now, 120);
+}
+
+// 64-bit microsecond timer
+type Us64 = fugit::Instant<u64, 1, 1_000_000>;
+
+fn us64(ms: u64) -> Us64 {
+    Us64::from_ticks(ms * 1000)
+}
+
+#[test]
+fn wide_press_duration_past_49_days() {
+    use fugit::{MillisDurationU32, MillisDurationU64};
+    use TimeButtonAction::*;
+    const LIMIT: u64 = u32::MAX as u64;
+    let pin = MockPin::new(true);
+    let mut button = TimeButton::<_, Us64>::new(&pin);
+    assert_eq!(button.update(us64(0)).unwrap(), Press);
+    for (ms, narrow) in [(LIMIT - 1, LIMIT - 1), (LIMIT, LIMIT), (LIMIT + 1, LIMIT), (3 * LIMIT, LIMIT)] {
+        assert_eq!(button.press_duration(us64(ms)), Some(MillisDurationU32::from_ticks(narrow as u32)), "{ms} ms");
+        assert_eq!(button.wide_press_duration(us64(ms)), Some(MillisDurationU64::from_ticks(ms)), "{ms} ms");
+    }
+    // Actions of `update` carry saturated durations
+    assert_eq!(button.update(us64(LIMIT + 1)).unwrap(), Held(MillisDurationU32::from_ticks(u32::MAX)));
+    pin.set(false);
+    assert_eq!(button.update(us64(LIMIT + 2)).unwrap(), Click(MillisDurationU32::from_ticks(u32::MAX)));
+    assert!(button.wide_press_duration(us64(LIMIT + 3)).is_none());
+}
+
+#[test]
+fn wide_press_duration_of_narrow_instants() {
+    let pin = MockPin::new(true);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.update(Ms::from_ticks(10)).unwrap();
+    assert_eq!(button.wide_press_duration(Ms::from_ticks(1_000_010)), Some(fugit::MillisDurationU64::from_ticks(1_000_000)));
+}
+
+#[test]
+fn wide_actions_cross_the_u32_boundary() {
+    use fugit::{MillisDurationU32, MillisDurationU64};
+    use TimeButtonAction::*;
+    const LIMIT: u64 = u32::MAX as u64;
+    let pin = MockPin::new(true);
+    let mut button = TimeButton::<_, Us64>::new(&pin);
+    assert_eq!(button.update_wide(us64(0)).unwrap(), Press);
+    for ms in [LIMIT - 1, LIMIT, LIMIT + 1, 3 * LIMIT] {
+        assert_eq!(button.update_wide(us64(ms)).unwrap(), Held(MillisDurationU64::from_ticks(ms)), "{ms} ms");
+    }
+    pin.set(false);
+    let click = button.update_wide(us64(3 * LIMIT + 1)).unwrap();
+    assert_eq!(click, Click(MillisDurationU64::from_ticks(3 * LIMIT + 1)));
+    // Saturated only once narrowed
+    assert_eq!(click.narrow(), Click(MillisDurationU32::from_ticks(u32::MAX)));
+}
+
+#[test]
+fn wide_deferred_click_keeps_its_duration() {
+    use fugit::MillisDurationU64;
+    use TimeButtonAction::*;
+    const LIMIT: u64 = u32::MAX as u64;
+    let pin = MockPin::new(true);
+    let mut button = TimeButton::<_, Us64>::new(&pin);
+    button.set_double_click_window(100.millis());
+    button.set_long_press(1000.millis());
+    assert_eq!(button.update_wide(us64(0)).unwrap(), Press);
+    assert_eq!(button.update_wide(us64(LIMIT)).unwrap(), LongPress(MillisDurationU64::from_ticks(LIMIT)));
+    pin.set(false);
+    assert_eq!(button.update_wide(us64(LIMIT + 5)).unwrap(), Release(MillisDurationU64::from_ticks(LIMIT + 5)));
+    // A click held past the limit, reported once the double click window closes
+    pin.set(true);
+    assert_eq!(button.update_wide(us64(LIMIT + 1000)).unwrap(), Press);
+    pin.set(false);
+    assert_eq!(button.update_wide(us64(2 * LIMIT + 2000)).unwrap(), Release(MillisDurationU64::from_ticks(LIMIT + 1000)));
+    assert_eq!(button.update_wide(us64(2 * LIMIT + 2200)).unwrap(), Click(MillisDurationU64::from_ticks(LIMIT + 1000)));
+}
+
+#[test]
+fn stamped_updates_carry_their_instant() {
+    use simple_encoder::Stamped;
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut stamped = TimeButton::<_, Ms>::new(&pin);
+    let mut plain = TimeButton::<_, Ms>::new(&pin);
+    for (t, high) in [(0, false), (10, true), (25, true), (40, false)] {
+        pin.set(high);
+        let at = Ms::from_ticks(t);
+        assert_eq!(stamped.update_stamped(at).unwrap(), Stamped { at, action: plain.update(at).unwrap() });
+    }
+
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 10 });
+    pin.set(true);
+    assert_eq!(button.update_stamped().unwrap(), Stamped { at: Ms::from_ticks(10), action: Press });
+    pin.set(false);
+    assert_eq!(button.update_stamped().unwrap(), Stamped { at: Ms::from_ticks(20), action: Click(10.millis()) });
+}
+
+#[test]
+fn hold_delay_in_samples() {
+    use ButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = Button::new(&pin);
+    button.set_hold_delay(3);
+    // A short click never sees `Held`
+    assert_eq!(run(&mut button, &pin, &[true, true, true, false]), [Press, None, None, Click]);
+    // A long press sees it once the delay has passed
+    assert_eq!(run(&mut button, &pin, &[true, true, true, true, true, false]), [Press, None, None, None, Held, Click]);
+}
+
+#[test]
+fn hold_delay_in_time() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_hold_delay(250.millis());
+    assert_eq!(timeline(&mut button, &pin, &[(0, 200)], 50, 300), [(0, Press), (200, Click(200.millis()))]);
+    let actions = timeline(&mut button, &pin, &[(400, 750)], 50, 800);
+    assert_eq!(
+        actions,
+        [(400, Press), (650, Held(250.millis())), (700, Held(300.millis())), (750, Click(350.millis()))]
+    );
+}
+
+#[test]
+fn button_action_predicates() {
+    use ButtonAction::*;
+    let all = [None, Press, Held, Click, Release];
+    let pick = |f: fn(&ButtonAction) -> bool| all.iter().copied().filter(f).collect::<Vec<_>>();
+    assert_eq!(pick(ButtonAction::is_none), [None]);
+    assert_eq!(pick(ButtonAction::is_press), [Press]);
+    assert_eq!(pick(ButtonAction::is_held), [Held]);
+    assert_eq!(pick(ButtonAction::is_click), [Click]);
+}
+
+#[test]
+fn time_button_action_predicates() {
+    use TimeButtonAction::*;
+    let t = 30.millis();
+    let all = [
+        None,
+        Press,
+        Held(t),
+        Click(t),
+        Release(t),
+        DoubleClick(t),
+        LongPress(t),
+        Repeat(2),
+        MultiClick { count: 2, last_duration: t },
+        ShortClick(t),
+        LongClick(t),
+        HoldMilestone(0, t),
+        Stuck(t),
+    ];
+    let pick = |f: fn(&TimeButtonAction) -> bool| all.iter().copied().filter(f).collect::<Vec<_>>();
+    assert_eq!(pick(TimeButtonAction::is_none), [None]);
+    assert_eq!(pick(TimeButtonAction::is_press), [Press]);
+    assert_eq!(pick(TimeButtonAction::is_click), [Click(t), ShortClick(t), LongClick(t)]);
+    assert_eq!(all.iter().filter_map(TimeButtonAction::held_duration).collect::<Vec<_>>(), [t]);
+    assert_eq!(all.iter().filter_map(TimeButtonAction::click_duration).count(), 3);
+}
+
+#[test]
+fn stuck_press_is_reported_once() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = TimeButton::<_, Ms>::new(&pin);
+    button.set_stuck_timeout(30_000.millis());
+    assert_eq!(sample(&mut button, &pin, 0, true), Press);
+    assert_eq!(sample(&mut button, &pin, 29_999, true), Held(29_999.millis()));
+    assert!(!button.is_stuck());
+    assert_eq!(sample(&mut button, &pin, 30_000, true), Stuck(30_000.millis()));
+    assert!(button.is_stuck());
+    // Silent afterwards, no `Held`
+    for t in (31_000..40_000).step_by(1000) {
+        assert_eq!(sample(&mut button, &pin, t, true), None);
+    }
+    // Recovery: the release clears the flag, the next press is normal
+    assert_eq!(sample(&mut button, &pin, 40_000, false), Release(40_000.millis()));
+    assert!(!button.is_stuck());
+    assert_eq!(sample(&mut button, &pin, 41_000, true), Press);
+    assert_eq!(sample(&mut button, &pin, 41_100, false), Click(100.millis()));
+}
+
+#[test]
+fn stuck_counts_handled_presses() {
+    use TimeButtonAction::*;
+    let pin = MockPin::new(false);
+    let mut button = ClockButton::<_, _>::new(&pin, StepClock { now: 0, step: 100 });
+    button.set_stuck_timeout(500.millis());
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), Press);
+    assert!(button.handle_press());
+    let actions: Vec<_> = (0..6).map(|_| button.update().unwrap()).collect();
+    assert_eq!(actions, [None, None, None, None, Stuck(500.millis()), None]);
+    assert!(button.is_stuck());
+    pin.set(false);
+    assert_eq!(button.update().unwrap(), Release(700.millis()));
+}
+
+#[test]
+fn update_with_level_matches_pin_updates() {
+    let levels = [false, true, true, false, true, false, false, true, true, true, false];
+    let pin = MockPin::new(false);
+    let mut by_pin = Button::new(&pin);
+    by_pin.set_debounce_samples(2);
+    let expected = run(&mut by_pin, &pin, &levels);
+    // The pin is never read
+    pin.set_failing(true);
+    let mut by_level = Button::<_>::new(&pin);
+    by_level.set_debounce_samples(2);
+    let actions: Vec<_> = levels.iter().map(|&pressed| by_level.update_with_level(pressed)).collect();
+    assert_eq!(actions, expected);
+}
+
+#[test]
+fn time_update_with_level_matches_pin_updates() {
+    let presses = [(10, 40), (90, 130), (400, 700)];
+    let pin = MockPin::new(false);
+    let mut by_pin = TimeButton::<_, Ms>::with_double_click_window(&pin, 100.millis());
+    let expected = timeline(&mut by_pin, &pin, &presses, 10, 800);
+    pin.set_failing(true);
+    let mut by_level = TimeButton::<_, Ms>::with_double_click_window(&pin, 100.millis());
+    let actions: Vec<_> = (0..=800)
+        .step_by(10)
+        .map(|t| {
+            let pressed = presses.iter().any(|&(start, end)| (start..end).contains(&t));
+            (t, by_level.update_with_level(Ms::from_ticks(t), pressed))
+        })
+        .filter(|(_, act)| !act.is_none())
+        .collect();
+    assert_eq!(actions, expected);
+}
+
+#[test]
+fn pin_round_trips_through_into_pin() {
+    let pin = MockPin::new(false);
+    let mut button = Button::<_>::new(&pin);
+    pin.set(true);
+    assert_eq!(button.update().unwrap(), ButtonAction::Press);
+    assert!(embedded_hal::digital::v2::InputPin::is_high(button.k_pin()).unwrap());
+    let mut button = Button::<_>::new(button.into_pin());
+    assert_eq!(button.update().unwrap(), ButtonAction::Press);
+    let mut button = TimeButton::<_, Ms>::new(button.into_pin());
+    pin.set(false);
+    assert!(button.update(Ms::from_ticks(0)).unwrap().is_none());
+    let mut button = TimeButton::<_, Ms>::new(button.into_pin());
+    pin.set(true);
+    assert_eq!(button.update(Ms::from_ticks(10)).unwrap(), TimeButtonAction::Press);
+}
@@ -0,0 +1,53 @@
+use simple_encoder::calibrate::*;
+use simple_encoder::rotary::StepMode;
+
+// One clockwise cycle from rest as (a_low, b_low), with contact bounce on every transition
+fn bouncy_cycle() -> Vec<(bool, bool)> {
+    let cycle = [(false, false), (false, true), (true, true), (true, false), (false, false)];
+    let mut trace = Vec::new();
+    for pair in cycle.windows(2) {
+        trace.extend([pair[1], pair[0], pair[1], pair[1]]);
+    }
+    trace
+}
+
+fn calibrate(trace: &[(bool, bool)], detents_per_cycle: usize, cycles: usize) -> DividerCalibrator {
+    let mut calibrator = DividerCalibrator::new();
+    calibrator.sample(false, false);
+    let per_detent = trace.len() / detents_per_cycle;
+    for _ in 0..cycles {
+        for detent in trace.chunks(per_detent) {
+            for &(a_low, b_low) in detent {
+                calibrator.sample(a_low, b_low);
+            }
+            calibrator.mark_detent();
+        }
+    }
+    calibrator
+}
+
+#[test]
+fn one_detent_per_cycle() {
+    let calibrator = calibrate(&bouncy_cycle(), 1, 4);
+    assert_eq!(calibrator.recommendation(), Some(Calibration { step_mode: StepMode::Full, confidence: 100 }));
+}
+
+#[test]
+fn two_detents_per_cycle() {
+    let calibrator = calibrate(&bouncy_cycle(), 2, 3);
+    assert_eq!(calibrator.detents(), 6);
+    assert_eq!(calibrator.recommendation(), Some(Calibration { step_mode: StepMode::Half, confidence: 100 }));
+}
+
+#[test]
+fn miscounted_detent_lowers_confidence() {
+    let mut calibrator = calibrate(&bouncy_cycle(), 2, 3);
+    assert_eq!(calibrator.mark_detent(), None);
+    // Half a cycle back, bouncing on the first transition
+    for (a_low, b_low) in [(true, false), (false, false), (true, false), (true, true)] {
+        calibrator.sample(a_low, b_low);
+    }
+    assert_eq!(calibrator.mark_detent(), Some(StepMode::Half));
+    assert_eq!(calibrator.recommendation(), Some(Calibration { step_mode: StepMode::Half, confidence: 87 }));
+    assert_eq!(DividerCalibrator::new().recommendation(), None);
+}
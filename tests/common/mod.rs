@@ -0,0 +1,64 @@
+#![allow(dead_code)]
+
+use core::cell::Cell;
+use embedded_hal::digital::v2::InputPin;
+
+pub type Ms = fugit::Instant<u32, 1, 1000>;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MockError;
+
+/// Pin with a level set from the test, read by the device through `&MockPin`.
+/// While failing every read returns `MockError`.
+pub struct MockPin {
+    level: Cell<bool>,
+    failing: Cell<bool>,
+}
+
+impl MockPin {
+    pub fn new(high: bool) -> Self {
+        Self { level: Cell::new(high), failing: Cell::new(false) }
+    }
+
+    pub fn set(&self, high: bool) {
+        self.level.set(high);
+    }
+
+    pub fn set_failing(&self, failing: bool) {
+        self.failing.set(failing);
+    }
+}
+
+impl InputPin for &MockPin {
+    type Error = MockError;
+
+    fn is_high(&self) -> Result<bool, MockError> {
+        match self.failing.get() {
+            true => Err(MockError),
+            false => Ok(self.level.get()),
+        }
+    }
+
+    fn is_low(&self) -> Result<bool, MockError> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+/// A and B levels of one clockwise detent of a pulled-up encoder, ending at rest.
+pub const CW: [(bool, bool); 4] = [(true, false), (false, false), (false, true), (true, true)];
+pub const CCW: [(bool, bool); 4] = [(false, true), (false, false), (true, false), (true, true)];
+
+/// Clock advancing by `step` ms on every read.
+pub struct StepClock {
+    pub now: u32,
+    pub step: u32,
+}
+
+impl simple_encoder::Clock for StepClock {
+    type Instant = Ms;
+
+    fn now(&mut self) -> Ms {
+        self.now += self.step;
+        Ms::from_ticks(self.now)
+    }
+}
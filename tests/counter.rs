@@ -0,0 +1,110 @@
+mod common;
+
+use common::*;
+use simple_encoder::counter::*;
+
+#[test]
+fn soft_counter_wraps() {
+    let mut counter = SoftCounter::<16>::new(0xffff);
+    counter.add(1);
+    assert_eq!(counter.count(), 0);
+    counter.add(-1);
+    assert_eq!(counter.count(), 0xffff);
+    counter.add(0x1_0003);
+    assert_eq!(counter.count(), 2);
+    assert_eq!(SoftCounter::<16>::new(0x1_0005).count(), 5);
+    let mut counter = SoftCounter::<8>::new(250);
+    counter.add(10);
+    assert_eq!(counter.count(), 4);
+    let mut counter = SoftCounter::<32>::new(u32::MAX);
+    counter.add(2);
+    assert_eq!(counter.count(), 1);
+}
+
+fn rotary(count: u32) -> CounterRotary<SoftCounter> {
+    let mut rotary = CounterRotary::new(SoftCounter::new(count), 4);
+    // The first read only takes the starting point
+    assert_eq!(rotary.update().unwrap().angle(), 0);
+    rotary
+}
+
+fn turn(rotary: &mut CounterRotary<SoftCounter>, counts: i32) -> i32 {
+    rotary.source().add(counts);
+    rotary.update().unwrap().angle()
+}
+
+#[test]
+fn counts_across_the_wrap() {
+    let mut rotary = rotary(65530);
+    assert_eq!(turn(&mut rotary, 12), 3);
+    assert_eq!(rotary.source().count(), 6);
+    assert_eq!(turn(&mut rotary, -8), -2);
+    assert_eq!(turn(&mut rotary, -8), -2);
+    assert_eq!(rotary.source().count(), 65526);
+    // The largest step a 16 bit counter can tell apart
+    assert_eq!(turn(&mut rotary, 32764), 8191);
+    assert_eq!(turn(&mut rotary, -32764), -8191);
+}
+
+#[test]
+fn remainder_waits_for_next_update() {
+    let mut rotary = rotary(0);
+    assert_eq!(turn(&mut rotary, 6), 1);
+    assert_eq!(rotary.sub_steps(), 2);
+    assert_eq!(turn(&mut rotary, 2), 1);
+    assert_eq!(turn(&mut rotary, -3), 0);
+    assert_eq!(rotary.sub_steps(), -3);
+    rotary.reset();
+    assert_eq!(turn(&mut rotary, 100), 0);
+    assert_eq!(turn(&mut rotary, 1), 0);
+    rotary.set_counts_per_detent(0);
+    assert_eq!(turn(&mut rotary, 3), 3);
+    rotary.set_reversed(true);
+    assert_eq!(turn(&mut rotary, 3), -3);
+}
+
+// Twelve bit hardware counter that can fail to read
+struct Peripheral {
+    count: u32,
+    failing: bool,
+}
+
+impl CountSource for Peripheral {
+    type Error = MockError;
+
+    const BITS: u32 = 12;
+
+    fn read(&mut self) -> Result<u32, MockError> {
+        match self.failing {
+            true => Err(MockError),
+            false => Ok(self.count),
+        }
+    }
+}
+
+#[test]
+fn narrow_hardware_counter() {
+    let mut rotary = CounterRotary::new(Peripheral { count: 4094, failing: false }, 2);
+    rotary.update().unwrap();
+    // Bits above the width are ignored
+    rotary.source().count = 0xf000 | 4;
+    assert_eq!(rotary.update().unwrap().angle(), 3);
+    rotary.source().count = 4090;
+    assert_eq!(rotary.update().unwrap().angle(), -5);
+    rotary.source().failing = true;
+    assert_eq!(rotary.update(), Err(MockError));
+}
+
+#[test]
+fn time_counter_rotary_accelerates() {
+    let mut rotary = TimeCounterRotary::<_, Ms>::with_acceleration(rotary(0), 8);
+    let mut turn = |counts: i32, t: u32| {
+        rotary.rotary().source().add(counts);
+        rotary.update(Ms::from_ticks(t)).unwrap().angle()
+    };
+    assert_eq!(turn(4, 0), 1);
+    assert_eq!(turn(4, 5), 8);
+    // Several detents in one update are accelerated as one
+    assert_eq!(turn(8, 10), 16);
+    assert_eq!(turn(-4, 500), -1);
+}
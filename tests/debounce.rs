@@ -0,0 +1,72 @@
+mod common;
+
+use common::*;
+use simple_encoder::debounce::{Debounced, Edge};
+use simple_encoder::rotary::Rotary;
+
+fn edges<const SAMPLES: u8>(signal: &mut Debounced<SAMPLES>, levels: &[bool]) -> Vec<Edge> {
+    levels.iter().map(|&level| signal.update(level)).collect()
+}
+
+#[test]
+fn single_sample_follows_input() {
+    use Edge::*;
+    let mut signal = Debounced::<1>::new();
+    assert_eq!(edges(&mut signal, &[false, true, true, false, true]), [None, Pressed, None, Released, Pressed]);
+    // Zero acts as one
+    let mut signal = Debounced::<0>::new();
+    assert_eq!(edges(&mut signal, &[true, false]), [Pressed, Released]);
+}
+
+#[test]
+fn bounces_are_filtered() {
+    use Edge::*;
+    let mut signal = Debounced::<3>::new();
+    // Contact bounce on press, then a clean hold
+    let press = [true, false, true, true, false, true, true, true, true];
+    assert_eq!(edges(&mut signal, &press), [None, None, None, None, None, None, None, Pressed, None]);
+    assert!(signal.level());
+    // A glitch shorter than the depth is ignored
+    assert_eq!(edges(&mut signal, &[false, false, true]), [None, None, None]);
+    let release = [false, true, false, false, false];
+    assert_eq!(edges(&mut signal, &release), [None, None, None, None, Released]);
+    assert!(!signal.level());
+}
+
+#[test]
+fn depth_changes_at_runtime() {
+    use Edge::*;
+    let mut signal = Debounced::<4>::new();
+    signal.set_samples(2);
+    assert_eq!(edges(&mut signal, &[true, true]), [None, Pressed]);
+    signal.set_samples(0);
+    assert_eq!(edges(&mut signal, &[false]), [Released]);
+}
+
+#[test]
+fn settled_level_reports_no_edge() {
+    use Edge::*;
+    let mut signal = Debounced::<2>::with_level(true);
+    assert_eq!(edges(&mut signal, &[true, false, false]), [None, None, Released]);
+    signal.reset(true);
+    assert!(signal.level());
+    assert_eq!(edges(&mut signal, &[true, false, true]), [None, None, None]);
+}
+
+#[test]
+fn rotary_lines_are_debounced() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_>::new(&a, &b);
+    rotary.set_debounce_samples(2);
+    let mut angle = 0;
+    for &(a_high, b_high) in &CW {
+        // Every state is held for two samples, with a one sample bounce back on entry
+        for (a_high, b_high) in [(a_high, b_high), (!a_high, b_high), (a_high, b_high), (a_high, b_high)] {
+            a.set(a_high);
+            b.set(b_high);
+            angle += rotary.update().unwrap().angle();
+        }
+    }
+    assert_eq!(angle, 1);
+    assert_eq!(rotary.error_count(), 0);
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::*;
+use simple_encoder::rotary::*;
+
+const MODES: [StepMode; 3] = [StepMode::Full, StepMode::Half, StepMode::Quarter];
+const RESTS: [RestState; 3] = [RestState::Open, RestState::Closed, RestState::Either];
+
+// The per-state match the transition table replaced, copied as it was. Double transitions
+// counted one step either way there, they now follow `DoubleTransition::Ignore`.
+struct Baseline {
+    state: u8,
+    switches: i8,
+    step_mode: StepMode,
+    rest: RestState,
+}
+
+impl Baseline {
+    fn rests_at(&self, closed: bool) -> bool {
+        match (self.step_mode, self.rest) {
+            (StepMode::Quarter, _) => false,
+            (StepMode::Half, _) | (_, RestState::Either) => true,
+            (_, RestState::Open) => !closed,
+            (_, RestState::Closed) => closed,
+        }
+    }
+
+    fn step(&mut self, a_low: bool, b_low: bool) -> i32 {
+        let state = self.state >> 2 | match (a_low, b_low) {
+            (false, false) => 0b0000,
+            (false, true) => 0b0100,
+            (true, false) => 0b1000,
+            (true, true) => 0b1100,
+        };
+        self.state = state;
+
+        let divider = self.step_mode.transitions();
+        let overflow_switches = |switch_origin: &mut i8, switches: i8| {
+            if switches.abs() >= divider {
+                *switch_origin = 0;
+                switches.signum() as i32
+            } else {
+                *switch_origin = switches;
+                0
+            }
+        };
+
+        match state {
+            0b0001 | 0b0111 | 0b1110 | 0b1000 => {
+                let switches = self.switches - 1;
+                overflow_switches(&mut self.switches, switches)
+            },
+            0b0010 | 0b1011 | 0b1101 | 0b0100 => {
+                let switches = self.switches + 1;
+                overflow_switches(&mut self.switches, switches)
+            },
+            0b0000 | 0b0011 if self.rests_at(false) => {
+                let s = self.switches;
+                self.switches = 0;
+                s.signum() as i32
+            }
+            0b1111 | 0b1100 if self.rests_at(true) => {
+                let s = self.switches;
+                self.switches = 0;
+                s.signum() as i32
+            }
+            _ => 0,
+        }
+    }
+}
+
+fn levels(sample: usize) -> (bool, bool) {
+    (sample & 0b10 != 0, sample & 0b01 != 0)
+}
+
+#[test]
+fn table_matches_baseline_match() {
+    // Every sequence of 8 samples, so every pair of states is reached with every
+    // partial count the step modes allow
+    const LEN: u32 = 8;
+    for step_mode in MODES {
+        for rest in RESTS {
+            for sequence in 0..4usize.pow(LEN) {
+                let mut decoder = QuadratureDecoder::<FullStep>::with_rest_state(rest);
+                decoder.set_step_mode(step_mode);
+                let mut baseline = Baseline { state: 0, switches: 0, step_mode, rest };
+                for i in 0..LEN {
+                    let (a_low, b_low) = levels(sequence >> (2 * i) & 0b11);
+                    let expected = baseline.step(a_low, b_low);
+                    assert_eq!(
+                        decoder.step(a_low, b_low).angle(),
+                        expected,
+                        "{step_mode:?} {rest:?} sequence {sequence:#018b} sample {i}"
+                    );
+                    assert_eq!(decoder.sub_steps(), baseline.switches);
+                }
+            }
+        }
+    }
+}
+
+// Feeds pin levels, `true` meaning high as in `CW`
+fn feed<M: Steps>(decoder: &mut QuadratureDecoder<M>, highs: &[(bool, bool)]) -> Vec<i32> {
+    highs.iter().map(|&(a_high, b_high)| decoder.step(!a_high, !b_high).angle()).collect()
+}
+
+fn turns<M: Steps>(cycle: &[(bool, bool)], times: usize) -> Vec<i32> {
+    let mut decoder = QuadratureDecoder::<M>::new();
+    let highs: Vec<_> = cycle.iter().cycle().take(cycle.len() * times).copied().collect();
+    feed(&mut decoder, &highs)
+}
+
+#[test]
+fn valid_sequences() {
+    assert_eq!(QuadratureDecoder::<FullStep>::new().divider(), 4);
+    assert_eq!(QuadratureDecoder::<HalfStep>::new().divider(), 2);
+    assert_eq!(QuadratureDecoder::<QuarterStep>::new().divider(), 1);
+
+    assert_eq!(turns::<FullStep>(&CW, 2), [0, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(turns::<FullStep>(&CCW, 2), [0, 0, 0, -1, 0, 0, 0, -1]);
+    assert_eq!(turns::<HalfStep>(&CW, 2), [0, 1, 0, 1, 0, 1, 0, 1]);
+    assert_eq!(turns::<HalfStep>(&CCW, 2), [0, -1, 0, -1, 0, -1, 0, -1]);
+    assert_eq!(turns::<QuarterStep>(&CW, 2), [1; 8]);
+    assert_eq!(turns::<QuarterStep>(&CCW, 2), [-1; 8]);
+
+    // Turning back within a detent reports nothing and leaves no partial count
+    let mut decoder = QuadratureDecoder::<FullStep>::new();
+    let back = [CW[0], CW[1], CW[0], CW[3]];
+    assert_eq!(feed(&mut decoder, &back), [0, 0, 0, 0]);
+    assert_eq!(decoder.sub_steps(), 0);
+    assert_eq!(decoder.error_count(), 0);
+    assert_eq!(decoder.odometer(), (0, 0));
+}
+
+#[test]
+fn valid_sequences_from_any_phase() {
+    // Starting between detents, each count is reported once the next rest state is reached
+    for start in 0..4 {
+        let cycle: Vec<_> = CW.iter().cycle().skip(start).take(4 * 3).copied().collect();
+        let mut decoder = QuadratureDecoder::<QuarterStep>::new();
+        decoder.seed(!CW[(start + 3) % 4].0, !CW[(start + 3) % 4].1);
+        assert_eq!(feed(&mut decoder, &cycle).iter().sum::<i32>(), 12, "start {start}");
+        let mut decoder = QuadratureDecoder::<HalfStep>::new();
+        decoder.seed(!CW[(start + 3) % 4].0, !CW[(start + 3) % 4].1);
+        assert_eq!(feed(&mut decoder, &cycle).iter().sum::<i32>(), 6, "start {start}");
+        assert_eq!(decoder.error_count(), 0);
+    }
+}
+
+#[test]
+fn invalid_jumps() {
+    // Every sample changing both lines at once counts one error and reports nothing
+    for mode in MODES {
+        for from in 0..4usize {
+            let to = from ^ 0b11;
+            let mut decoder = QuadratureDecoder::<FullStep>::with_step_mode(mode);
+            let (a_low, b_low) = levels(from);
+            decoder.seed(a_low, b_low);
+            let (a_low, b_low) = levels(to);
+            assert_eq!(decoder.step(a_low, b_low).angle(), 0, "{mode:?} {from:02b} -> {to:02b}");
+            assert_eq!(decoder.sub_steps(), 0);
+            assert_eq!(decoder.error_count(), 1);
+            // Valid transitions afterwards are not errors
+            let (a_low, b_low) = levels(to ^ 0b01);
+            decoder.step(a_low, b_low);
+            assert_eq!(decoder.error_count(), 1);
+        }
+    }
+
+    // Arriving at rest by a jump completes the detent counted so far, as any arrival does
+    let mut decoder = QuadratureDecoder::<FullStep>::new();
+    assert_eq!(feed(&mut decoder, &[CW[0], CW[1], CW[3], CW[3]]), [0, 0, 1, 0]);
+    assert_eq!(decoder.sub_steps(), 0);
+    assert_eq!(decoder.error_count(), 1);
+    assert_eq!(feed(&mut decoder, &CW), [0, 0, 0, 1]);
+}
+
+#[test]
+fn repeated_samples_change_nothing() {
+    for mode in MODES {
+        let mut decoder = QuadratureDecoder::<FullStep>::with_step_mode(mode);
+        let mut total = 0;
+        for &(a_high, b_high) in &CW {
+            for _ in 0..3 {
+                total += decoder.step(!a_high, !b_high).angle();
+            }
+        }
+        assert_eq!(total, 4 / mode.transitions() as i32, "{mode:?}");
+        assert_eq!(decoder.error_count(), 0);
+    }
+}
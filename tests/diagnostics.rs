@@ -0,0 +1,54 @@
+#![cfg(feature = "diagnostics")]
+
+mod common;
+
+use common::*;
+use fugit::ExtU32;
+use simple_encoder::rotary::*;
+
+// Detents at the given instants, clockwise unless negative
+fn stats(detents: &[i32]) -> RotaryStats {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    for &at in detents {
+        let cycle = if at < 0 { &CCW } else { &CW };
+        for &(a_high, b_high) in cycle {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(at.unsigned_abs())).unwrap();
+        }
+    }
+    rotary.stats()
+}
+
+#[test]
+fn histogram_and_extrema() {
+    let stats = stats(&[0, 3, 11, 26, 66, 400, 403, 413, 1000]);
+    assert_eq!(stats.min_interval, Some(3.millis()));
+    assert_eq!(stats.histogram, [2, 2, 1, 1, 0, 0, 0, 2]);
+    assert_eq!(stats.max_burst, 5);
+}
+
+#[test]
+fn reversal_ends_burst() {
+    let stats = stats(&[0, 10, 20, -30, -40, 50]);
+    assert_eq!(stats.max_burst, 3);
+    assert_eq!(stats.histogram[1], 5);
+}
+
+#[test]
+fn reset_stats_clears() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    for t in [0, 10] {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(t)).unwrap();
+        }
+    }
+    rotary.reset();
+    assert_eq!(rotary.stats().max_burst, 2);
+    rotary.reset_stats();
+    assert_eq!(rotary.stats(), RotaryStats::default());
+}
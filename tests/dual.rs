@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use simple_encoder::dual::*;
+use simple_encoder::input::PinKind;
+use simple_encoder::rotary::{FullStep, HalfStep, Rotary, Rotation, TimeRotary};
+
+struct Pins([MockPin; 4]);
+
+impl Pins {
+    fn new() -> Self {
+        Self(core::array::from_fn(|_| MockPin::new(true)))
+    }
+
+    fn set(&self, outer: (bool, bool), inner: (bool, bool)) {
+        self.0[0].set(outer.0);
+        self.0[1].set(outer.1);
+        self.0[2].set(inner.0);
+        self.0[3].set(inner.1);
+    }
+}
+
+#[test]
+fn decodes_independently() {
+    let pins = Pins::new();
+    let [a1, b1, a2, b2] = &pins.0;
+    let mut dual = DualRotary::<_, _, _, _, FullStep, HalfStep>::new(Rotary::new(a1, b1), Rotary::new(a2, b2));
+    dual.inner().set_reversed(true);
+    let mut total = DualRotation::default();
+    for &cw in &CW {
+        pins.set(cw, cw);
+        let rot = dual.update().unwrap();
+        total.outer += rot.outer;
+        total.inner += rot.inner;
+    }
+    for &ccw in &CCW {
+        pins.set((true, true), ccw);
+        let rot = dual.update().unwrap();
+        assert!(rot.outer.is_zero());
+        total.inner += rot.inner;
+    }
+    assert_eq!(total, DualRotation { outer: Rotation::CW_STEP, inner: Rotation::ZERO });
+}
+
+#[test]
+fn errors_name_the_encoder() {
+    let pins = Pins::new();
+    let [a1, b1, a2, b2] = &pins.0;
+    let mut dual = TimeDualRotary::<_, _, _, _, Ms>::new(TimeRotary::new(a1, b1), TimeRotary::new(a2, b2));
+    pins.0[3].set_failing(true);
+    let err = dual.update(Ms::from_ticks(0)).unwrap_err();
+    assert_eq!(err, DualRotaryError::InnerB(MockError));
+    assert!(!err.is_outer());
+    pins.0[0].set_failing(true);
+    let err = dual.update(Ms::from_ticks(1)).unwrap_err();
+    assert!(err.is_outer());
+    assert_eq!(err.into_kind(), PinKind::A);
+}
@@ -0,0 +1,607 @@
+mod common;
+
+use common::*;
+use simple_encoder::encoder::*;
+use simple_encoder::button::Polarity;
+use simple_encoder::rotary::{FullStep, HalfStep, QuarterStep, Rotation, Steps};
+
+struct Pins {
+    a: MockPin,
+    b: MockPin,
+    k: MockPin,
+}
+
+impl Pins {
+    fn new() -> Self {
+        Self { a: MockPin::new(true), b: MockPin::new(true), k: MockPin::new(true) }
+    }
+}
+
+// Runs `(a, b, key pressed)` samples, the key is active low
+fn run(encoder: &mut Encoder<&MockPin, &MockPin, &MockPin, FullStep>, pins: &Pins, samples: &[(bool, bool, bool)]) -> Vec<EncoderAction> {
+    samples
+        .iter()
+        .map(|&(a, b, pressed)| {
+            pins.a.set(a);
+            pins.b.set(b);
+            pins.k.set(!pressed);
+            encoder.update().unwrap()
+        })
+        .filter(|act| !act.is_none())
+        .collect()
+}
+
+fn detent(pressed: bool) -> Vec<(bool, bool, bool)> {
+    CW.iter().map(|&(a, b)| (a, b, pressed)).collect()
+}
+
+#[test]
+fn rotate_and_click() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    assert_eq!(run(&mut encoder, &pins, &detent(false)), [Rotate(Rotation::new(1))]);
+    let click = [(true, true, true), (true, true, false)];
+    assert_eq!(run(&mut encoder, &pins, &click), [Press, Click]);
+}
+
+#[test]
+fn rotation_while_held_suppresses_click() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    let mut samples = vec![(true, true, true), (true, true, true)];
+    samples.extend(detent(true));
+    samples.extend(detent(true));
+    samples.push((true, true, false));
+    assert_eq!(
+        run(&mut encoder, &pins, &samples),
+        [Press, Held, Held, Held, Held, RotatePressed(Rotation::new(1)), RotatePressed(Rotation::new(1)), Release],
+    );
+    let click = [(true, true, true), (true, true, false)];
+    assert_eq!(run(&mut encoder, &pins, &click), [Press, Click]);
+}
+
+#[test]
+fn pin_errors_map_to_channel() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::<_, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    pins.b.set_failing(true);
+    assert_eq!(encoder.update(), Err(EncoderError::BPin(MockError)));
+    pins.b.set_failing(false);
+    pins.k.set_failing(true);
+    assert_eq!(encoder.update(), Err(EncoderError::KPin(MockError)));
+}
+
+#[test]
+fn clock_encoder_accessors() {
+    let pins = Pins::new();
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 1 });
+    encoder.set_acceleration(3);
+    assert_eq!(encoder.acceleration(), 3);
+    assert!(!encoder.set_accel_window(fugit::MillisDurationU32::from_ticks(20), fugit::MillisDurationU32::from_ticks(100)));
+    assert!(encoder.set_accel_window(fugit::MillisDurationU32::from_ticks(200), fugit::MillisDurationU32::from_ticks(50)));
+    assert_eq!(encoder.inner().acceleration(), 3);
+    encoder.clock().now = 20;
+    encoder.update().unwrap();
+    let (_, clock) = encoder.into_parts();
+    assert_eq!(clock.now, 21);
+}
+
+#[test]
+fn idle_time_counts_presses() {
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    let idle = |encoder: &TimeEncoder<_, _, _, Ms, FullStep>, t| encoder.idle_for(Ms::from_ticks(t)).map(|d| d.ticks());
+    encoder.update(Ms::from_ticks(0)).unwrap();
+    assert_eq!(idle(&encoder, 100), None);
+    pins.k.set(false);
+    encoder.update(Ms::from_ticks(100)).unwrap();
+    assert_eq!(idle(&encoder, 300), Some(0));
+    pins.k.set(true);
+    encoder.update(Ms::from_ticks(300)).unwrap();
+    assert_eq!(idle(&encoder, 400), Some(300));
+}
+
+fn steps<M: Steps>() -> i32 {
+    let pins = Pins::new();
+    let mut encoder = Encoder::<_, _, _, M>::new(&pins.a, &pins.b, &pins.k);
+    let mut angle = 0;
+    for &(a, b) in CW.iter().chain(&CW) {
+        pins.a.set(a);
+        pins.b.set(b);
+        if let EncoderAction::Rotate(rot) = encoder.update().unwrap() {
+            angle += rot.angle();
+        }
+    }
+    angle
+}
+
+#[test]
+fn divider_reaches_rotary() {
+    assert_eq!(steps::<QuarterStep>(), 8);
+    assert_eq!(steps::<HalfStep>(), 4);
+    assert_eq!(steps::<FullStep>(), 2);
+}
+
+// Key levels `[released, pressed]` through an encoder with the given polarity
+fn key_actions(polarity: Polarity, levels: [bool; 2]) -> Vec<EncoderAction> {
+    let pins = Pins::new();
+    pins.k.set(levels[0]);
+    let mut encoder = Encoder::<_, _, _, FullStep>::with_key_polarity(&pins.a, &pins.b, &pins.k, polarity);
+    assert_eq!(encoder.key_polarity(), polarity);
+    [levels[0], levels[1], levels[0]]
+        .iter()
+        .map(|&level| {
+            pins.k.set(level);
+            encoder.update().unwrap()
+        })
+        .filter(|act| !act.is_none())
+        .collect()
+}
+
+#[test]
+fn key_polarity_both_levels() {
+    use EncoderAction::*;
+    assert_eq!(key_actions(Polarity::ActiveLow, [true, false]), [Press, Click]);
+    assert_eq!(key_actions(Polarity::ActiveHigh, [false, true]), [Press, Click]);
+    // The wrong polarity swaps presses and releases
+    assert_eq!(key_actions(Polarity::ActiveLow, [false, true]), [Press, Click, Press]);
+    let pins = Pins::new();
+    let encoder = ClockEncoder::<_, _, _, _, FullStep>::with_key_polarity(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 1 }, Polarity::ActiveHigh);
+    assert_eq!(encoder.key_polarity(), Polarity::ActiveHigh);
+}
+
+#[test]
+fn handled_press_release_is_forwarded() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, true)]), [Press]);
+    assert!(encoder.handle_press());
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, true), (true, true, false)]), [Release]);
+    // The next press clicks again
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, true), (true, true, false)]), [Press, Click]);
+}
+
+#[test]
+fn time_encoder_forwards_release() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    let at = |encoder: &mut TimeEncoder<_, _, _, Ms, FullStep>, t, pressed: bool| {
+        pins.k.set(!pressed);
+        encoder.update(Ms::from_ticks(t)).unwrap()
+    };
+    assert_eq!(at(&mut encoder, 0, true), Press);
+    assert!(encoder.handle_press());
+    assert_eq!(at(&mut encoder, 40, true), None);
+    assert_eq!(at(&mut encoder, 50, false), Release(fugit::MillisDurationU32::from_ticks(50)));
+    // Rotating while held turns the click into a release too
+    assert_eq!(at(&mut encoder, 100, true), Press);
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        pins.a.set(a);
+        pins.b.set(b);
+        let act = encoder.update(Ms::from_ticks(110 + i as u32)).unwrap();
+        assert!(matches!(act, Held(_) | RotatePressed(_)), "{act:?}");
+    }
+    assert_eq!(at(&mut encoder, 200, false), Release(fugit::MillisDurationU32::from_ticks(100)));
+}
+
+#[test]
+fn time_encoder_held_interval() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_held_interval(fugit::MillisDurationU32::from_ticks(100));
+    pins.k.set(false);
+    let actions: Vec<_> = (0..=250)
+        .step_by(10)
+        .map(|t| (t, encoder.update(Ms::from_ticks(t)).unwrap()))
+        .filter(|(_, act)| !act.is_none())
+        .collect();
+    let ms = fugit::MillisDurationU32::from_ticks;
+    assert_eq!(actions, [(0, Press), (10, Held(ms(10))), (110, Held(ms(110))), (210, Held(ms(210)))]);
+}
+
+#[test]
+fn time_encoder_classifies_clicks() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_click_threshold(fugit::MillisDurationU32::from_ticks(100));
+    let mut at = |t, pressed: bool| {
+        pins.k.set(!pressed);
+        encoder.update(Ms::from_ticks(t)).unwrap()
+    };
+    let ms = fugit::MillisDurationU32::from_ticks;
+    assert_eq!([at(0, true), at(40, false)], [Press, ShortClick(ms(40))]);
+    assert_eq!([at(100, true), at(250, false)], [Press, LongClick(ms(150))]);
+}
+
+#[test]
+fn encoder_handle_press_reports_whether_armed() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    assert!(!encoder.handle_press());
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, true)]), [EncoderAction::Press]);
+    assert!(encoder.handle_press());
+    assert!(!encoder.handle_press());
+
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    pins.k.set(true);
+    encoder.update(Ms::from_ticks(0)).unwrap();
+    assert!(!encoder.handle_press());
+    pins.k.set(false);
+    assert_eq!(encoder.update(Ms::from_ticks(10)).unwrap(), TimeEncoderAction::Press);
+    assert!(encoder.handle_press());
+    assert!(!encoder.handle_press());
+
+    pins.k.set(true);
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 10 });
+    encoder.update().unwrap();
+    assert!(!encoder.handle_press());
+    pins.k.set(false);
+    assert_eq!(encoder.update().unwrap(), TimeEncoderAction::Press);
+    assert!(encoder.handle_press());
+    assert!(!encoder.handle_press());
+}
+
+#[test]
+fn encoder_new_from_pin_skips_held_press() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    pins.k.set(false);
+    let mut encoder = Encoder::new_from_pin(&pins.a, &pins.b, &pins.k).unwrap();
+    let samples = [(true, true, true), (true, true, false), (true, true, true), (true, true, false)];
+    assert_eq!(run(&mut encoder, &pins, &samples), [Release, Press, Click]);
+}
+
+#[test]
+fn key_polarity_flip_reports_no_edge() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, false)]), []);
+    encoder.set_key_polarity(Polarity::ActiveHigh);
+    // The idle high key now reads as pressed without an edge
+    let actions = run(&mut encoder, &pins, &[(true, true, false), (true, true, false)]);
+    assert!(!actions.contains(&EncoderAction::Press), "{actions:?}");
+}
+
+#[test]
+fn encoder_reset_from_pins() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    let held: Vec<_> = CW[..2].iter().map(|&(a, b)| (a, b, true)).collect();
+    assert_eq!(run(&mut encoder, &pins, &held), [Press, Held]);
+    encoder.reset_from_pins().unwrap();
+    let rest: Vec<_> = CW[2..].iter().map(|&(a, b)| (a, b, true)).collect();
+    assert_eq!(run(&mut encoder, &pins, &rest), []);
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, false)]), [Release]);
+    assert_eq!(run(&mut encoder, &pins, &detent(false)), [Rotate(Rotation::new(1))]);
+}
+
+#[test]
+fn time_encoder_hold_milestones() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    let ms = fugit::MillisDurationU32::from_ticks;
+    encoder.set_hold_milestones(&[ms(50)]);
+    pins.k.set(false);
+    let milestones: Vec<_> = (0..=200)
+        .step_by(10)
+        .map(|t| encoder.update(Ms::from_ticks(t)).unwrap())
+        .filter(|act| matches!(act, HoldMilestone(..)))
+        .collect();
+    assert_eq!(milestones, [HoldMilestone(0, ms(50))]);
+}
+
+#[test]
+fn time_encoder_startup_grace() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let ms = fugit::MillisDurationU32::from_ticks;
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::with_startup_grace(&pins.a, &pins.b, &pins.k, ms(50));
+    let mut actions = Vec::new();
+    let mut at = |t, (a, b, pressed): (bool, bool, bool)| {
+        pins.a.set(a);
+        pins.b.set(b);
+        pins.k.set(!pressed);
+        let act = encoder.update(Ms::from_ticks(t)).unwrap();
+        if !act.is_none() {
+            actions.push((t, act));
+        }
+    };
+    // Key and lines bounce during the grace period
+    for (t, &(a, b)) in (0..50).step_by(5).zip(CW.iter().cycle()) {
+        at(t, (a, b, t % 10 == 0));
+    }
+    at(50, (true, true, false));
+    at(100, (true, true, true));
+    at(140, (true, true, false));
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        at(200 + i as u32, (a, b, false));
+    }
+    assert_eq!(actions, [(100, Press), (140, Click(ms(40))), (203, Rotate(Rotation::new(1)))]);
+}
+
+#[test]
+fn clock_encoder_parts_round_trip() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 10 });
+    pins.k.set(false);
+    assert_eq!(encoder.update().unwrap(), Press);
+    let (inner, clock) = encoder.into_parts();
+    let mut encoder = ClockEncoder::from_parts(inner, clock);
+    pins.k.set(true);
+    assert_eq!(encoder.update().unwrap(), Click(fugit::MillisDurationU32::from_ticks(10)));
+}
+
+#[test]
+fn stamped_encoder_updates() {
+    use simple_encoder::Stamped;
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    let mut stamped = Vec::new();
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        pins.a.set(a);
+        pins.b.set(b);
+        stamped.push(encoder.update_stamped(Ms::from_ticks(5 * i as u32)).unwrap());
+    }
+    assert_eq!(stamped[3], Stamped { at: Ms::from_ticks(15), action: Rotate(Rotation::new(1)) });
+
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 7 });
+    pins.k.set(false);
+    assert_eq!(encoder.update_stamped().unwrap(), Stamped { at: Ms::from_ticks(7), action: Press });
+}
+
+#[test]
+fn encoder_action_predicates() {
+    use EncoderAction::*;
+    let r = Rotation::new(-2);
+    let all = [None, Press, Held, Click, Release, Rotate(r), RotatePressed(r)];
+    let pick = |f: fn(&EncoderAction) -> bool| all.iter().copied().filter(f).collect::<Vec<_>>();
+    assert_eq!(pick(EncoderAction::is_none), [None]);
+    assert_eq!(pick(EncoderAction::is_press), [Press]);
+    assert_eq!(pick(EncoderAction::is_click), [Click]);
+    assert_eq!(pick(EncoderAction::is_rotate_pressed), [RotatePressed(r)]);
+    assert_eq!(all.iter().filter_map(EncoderAction::rotation).collect::<Vec<_>>(), [r, r]);
+}
+
+#[test]
+fn time_encoder_action_predicates() {
+    use TimeEncoderAction::*;
+    let t = fugit::MillisDurationU32::from_ticks(30);
+    let r = Rotation::new(3);
+    let all = [
+        None,
+        Press,
+        Held(t),
+        Click(t),
+        Release(t),
+        DoubleClick(t),
+        LongPress(t),
+        Repeat(1),
+        MultiClick { count: 3, last_duration: t },
+        ShortClick(t),
+        LongClick(t),
+        HoldMilestone(1, t),
+        Stuck(t),
+        Rotate(r),
+        RotatePressed(r),
+    ];
+    let pick = |f: fn(&TimeEncoderAction) -> bool| all.iter().copied().filter(f).collect::<Vec<_>>();
+    assert_eq!(pick(TimeEncoderAction::is_none), [None]);
+    assert_eq!(pick(TimeEncoderAction::is_press), [Press]);
+    assert_eq!(pick(TimeEncoderAction::is_click), [Click(t), ShortClick(t), LongClick(t)]);
+    assert_eq!(pick(TimeEncoderAction::is_rotate_pressed), [RotatePressed(r)]);
+    assert_eq!(all.iter().filter_map(TimeEncoderAction::held_duration).collect::<Vec<_>>(), [t]);
+    assert_eq!(all.iter().filter_map(TimeEncoderAction::click_duration).count(), 3);
+    assert_eq!(all.iter().filter_map(TimeEncoderAction::rotation).collect::<Vec<_>>(), [r, r]);
+}
+
+#[test]
+fn stuck_key_rotates_unpressed() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_stuck_timeout(fugit::MillisDurationU32::from_ticks(1000));
+    pins.k.set(false);
+    assert_eq!(encoder.update(Ms::from_ticks(0)).unwrap(), Press);
+    assert_eq!(encoder.update(Ms::from_ticks(1000)).unwrap(), Stuck(fugit::MillisDurationU32::from_ticks(1000)));
+    assert!(encoder.is_stuck());
+    let mut actions = Vec::new();
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        pins.a.set(a);
+        pins.b.set(b);
+        actions.push(encoder.update(Ms::from_ticks(1100 + i as u32)).unwrap());
+    }
+    assert_eq!(actions, [None, None, None, Rotate(Rotation::new(1))]);
+    pins.k.set(true);
+    assert_eq!(encoder.update(Ms::from_ticks(2000)).unwrap(), Release(fugit::MillisDurationU32::from_ticks(2000)));
+    assert!(!encoder.is_stuck());
+}
+
+#[test]
+fn rotation_in_stuck_update_is_reported_next() {
+    use TimeEncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_stuck_timeout(fugit::MillisDurationU32::from_ticks(1000));
+    pins.k.set(false);
+    assert_eq!(encoder.update(Ms::from_ticks(0)).unwrap(), Press);
+    let mut actions = Vec::new();
+    // The detent completes in the update the key turns stuck
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        pins.a.set(a);
+        pins.b.set(b);
+        actions.push(encoder.update(Ms::from_ticks(997 + i as u32)).unwrap());
+    }
+    let held: Vec<_> = (997..1000).map(|t| Held(fugit::MillisDurationU32::from_ticks(t))).collect();
+    assert_eq!(actions[..3], held);
+    assert_eq!(actions[3], Stuck(fugit::MillisDurationU32::from_ticks(1000)));
+    assert_eq!(encoder.update(Ms::from_ticks(1001)).unwrap(), Rotate(Rotation::new(1)));
+    assert_eq!(encoder.update(Ms::from_ticks(1002)).unwrap(), None);
+}
+
+#[test]
+fn encoders_respect_max_step() {
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_acceleration(20);
+    encoder.set_max_step(4);
+    let mut rotations = Vec::new();
+    for t in [0, 5, 10] {
+        for &(a, b) in &CW {
+            pins.a.set(a);
+            pins.b.set(b);
+            rotations.extend(encoder.update(Ms::from_ticks(t)).unwrap().rotation());
+        }
+    }
+    assert_eq!(rotations, [1, 4, 4].map(Rotation::new));
+    // One millisecond per sample keeps the detents fast
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 1 });
+    encoder.set_acceleration(20);
+    encoder.set_max_step(4);
+    let mut rotations = Vec::new();
+    for _ in 0..3 {
+        for &(a, b) in &CCW {
+            pins.a.set(a);
+            pins.b.set(b);
+            rotations.extend(encoder.update().unwrap().rotation());
+        }
+    }
+    assert_eq!(rotations, [-1, -4, -4].map(Rotation::new));
+}
+
+#[test]
+fn reversed_rotation_while_held() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_reversed(true);
+    assert_eq!(run(&mut encoder, &pins, &detent(false)), [Rotate(Rotation::new(-1))]);
+    let mut samples = vec![(true, true, true)];
+    samples.extend(detent(true));
+    samples.push((true, true, false));
+    let actions: Vec<_> = run(&mut encoder, &pins, &samples).into_iter().filter(|act| *act != Held).collect();
+    // Still counts as rotated on hold, so no click
+    assert_eq!(actions, [Press, RotatePressed(Rotation::new(-1)), Release]);
+    encoder.set_reversed(false);
+    assert_eq!(run(&mut encoder, &pins, &detent(false)), [Rotate(Rotation::new(1))]);
+}
+
+#[test]
+fn encoder_rotary_polarity() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    pins.a.set(false);
+    pins.b.set(false);
+    let mut encoder = Encoder::with_rotary_polarity(&pins.a, &pins.b, &pins.k, Polarity::ActiveHigh, Polarity::ActiveHigh);
+    let samples: Vec<_> = CW.iter().map(|&(a, b)| (!a, !b, false)).collect();
+    assert_eq!(run(&mut encoder, &pins, &samples), [Rotate(Rotation::new(1))]);
+    // The key keeps its own polarity
+    assert_eq!(run(&mut encoder, &pins, &[(false, false, true)]), [Press]);
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::with_rotary_polarity(&pins.a, &pins.b, &pins.k, Polarity::ActiveHigh, Polarity::ActiveLow);
+    pins.b.set(true);
+    let mut rotations = Vec::new();
+    for (i, &(a, b)) in CCW.iter().enumerate() {
+        pins.a.set(!a);
+        pins.b.set(b);
+        rotations.extend(encoder.update(Ms::from_ticks(i as u32)).unwrap().rotation());
+    }
+    assert_eq!(rotations, [Rotation::new(-1)]);
+}
+
+#[test]
+fn encoder_rest_state() {
+    use EncoderAction::*;
+    use simple_encoder::rotary::RestState;
+    let pins = Pins::new();
+    pins.a.set(false);
+    pins.b.set(false);
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_rest_state(RestState::Closed);
+    encoder.reset_from_pins().unwrap();
+    let cycle = [(false, true, false), (true, true, false), (true, false, false), (false, false, false)];
+    assert_eq!(run(&mut encoder, &pins, &[cycle, cycle].concat()), [Rotate(Rotation::new(1)), Rotate(Rotation::new(1))]);
+}
+
+#[test]
+fn encoder_counts_impossible_transitions() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    run(&mut encoder, &pins, &[(true, true, false), (false, false, false), (true, true, true), (true, true, false)]);
+    assert_eq!(encoder.error_count(), 2);
+    encoder.clear_error_count();
+    assert_eq!(encoder.error_count(), 0);
+    let mut encoder = ClockEncoder::<_, _, _, _, FullStep>::new(&pins.a, &pins.b, &pins.k, StepClock { now: 0, step: 1 });
+    encoder.update().unwrap();
+    pins.a.set(false);
+    pins.b.set(false);
+    encoder.update().unwrap();
+    assert_eq!(encoder.error_count(), 1);
+}
+
+#[test]
+fn encoder_reports_stuck_channel() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    encoder.set_health_window(2);
+    run(&mut encoder, &pins, &[(true, true, false), (true, false, false), (true, true, false)]);
+    assert_eq!(encoder.health(), simple_encoder::rotary::RotaryHealth::AStuck);
+    run(&mut encoder, &pins, &detent(false));
+    assert_eq!(encoder.health(), simple_encoder::rotary::RotaryHealth::Ok);
+}
+
+#[test]
+fn time_encoder_velocity() {
+    let pins = Pins::new();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    for t in [0, 100] {
+        for &(a, b) in &CW {
+            pins.a.set(a);
+            pins.b.set(b);
+            encoder.update(Ms::from_ticks(t)).unwrap();
+        }
+    }
+    assert_eq!(encoder.velocity(Ms::from_ticks(100)), 10);
+    assert_eq!(encoder.velocity(Ms::from_ticks(600)), 2);
+    assert_eq!(encoder.velocity(Ms::from_ticks(2000)), 0);
+}
+
+#[test]
+fn encoder_odometer() {
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    run(&mut encoder, &pins, &[detent(false), detent(true), detent(false)].concat());
+    let ccw: Vec<_> = CCW.iter().map(|&(a, b)| (a, b, false)).collect();
+    run(&mut encoder, &pins, &ccw);
+    assert_eq!(encoder.odometer(), (3, 1));
+    encoder.reset_odometer();
+    assert_eq!(encoder.odometer(), (0, 0));
+}
+
+#[test]
+fn encoder_pins_round_trip() {
+    use EncoderAction::*;
+    let pins = Pins::new();
+    let mut encoder = Encoder::new(&pins.a, &pins.b, &pins.k);
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, true)]), [Press]);
+    // The key pin can be read while the encoder keeps it
+    assert!(embedded_hal::digital::v2::InputPin::is_low(encoder.k_pin()).unwrap());
+    let (a, b, k) = encoder.into_parts();
+    let mut encoder = Encoder::new(a, b, k);
+    assert_eq!(run(&mut encoder, &pins, &[(true, true, false), (true, true, true), (true, true, false)]), [Press, Click]);
+    assert_eq!(run(&mut encoder, &pins, &detent(false)), [Rotate(Rotation::new(1))]);
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(&pins.a, &pins.b, &pins.k);
+    encoder.update(Ms::from_ticks(0)).unwrap();
+    let (a, b, k) = encoder.into_parts();
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(a, b, k);
+    pins.k.set(false);
+    assert_eq!(encoder.update(Ms::from_ticks(10)).unwrap(), TimeEncoderAction::Press);
+}
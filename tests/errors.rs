@@ -0,0 +1,68 @@
+use simple_encoder::encoder::EncoderError;
+use simple_encoder::input::PinKind;
+use simple_encoder::rotary::RotaryError;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct PortError(u8);
+
+#[test]
+fn rotary_error_map_and_unify() {
+    let err: RotaryError<PortError, u8> = RotaryError::BPin(3);
+    let err = err.map_b(PortError).map_a(|e| e);
+    assert_eq!(err, RotaryError::BPin(PortError(3)));
+    assert_eq!(err.into_inner(), PortError(3));
+    let err: RotaryError<u8, PortError> = RotaryError::APin(1);
+    assert_eq!(err.map_b(|e| e.0).into_inner(), 1);
+    assert_eq!(RotaryError::<_, PortError>::APin(PortError(2)).map_a(|e| e.0 + 1), RotaryError::APin(3));
+}
+
+#[test]
+fn encoder_error_map_and_unify() {
+    let err: EncoderError<PortError, PortError, u8> = EncoderError::KPin(7);
+    let err = err.map_k(PortError);
+    assert_eq!(err.into_kind(), PinKind::Key);
+    assert_eq!(err.into_inner(), PortError(7));
+    let err: EncoderError<u8, u16, PortError> = EncoderError::BPin(300);
+    let err = err.map_a(PortError).map_b(|e| PortError(e as u8)).map_k(|e| e);
+    assert_eq!(err, EncoderError::BPin(PortError(44)));
+    assert_eq!(EncoderError::<u8, u8, u8>::APin(5).into_inner(), 5);
+}
+
+// Implements only `Debug`, so the error traits need no more than that from the pin
+#[derive(Debug)]
+struct BusFault;
+
+fn boxed<E: core::error::Error + 'static>(err: E) -> Box<dyn core::error::Error> {
+    Box::new(err)
+}
+
+#[test]
+fn errors_need_only_debug_pin_errors() {
+    let err = boxed(simple_encoder::button::Error::KPin(BusFault));
+    assert_eq!(err.to_string(), "key pin error");
+    let err = boxed(RotaryError::<BusFault, BusFault>::BPin(BusFault));
+    assert_eq!(err.to_string(), "rotary B pin error");
+    let err = boxed(EncoderError::<BusFault, BusFault, BusFault>::APin(BusFault));
+    assert_eq!(err.to_string(), "encoder A pin error");
+    assert_eq!(format!("{:?}", simple_encoder::button::Error::KPin(BusFault)), "KPin(BusFault)");
+}
+
+#[test]
+fn errors_compare_and_copy_with_their_pin_errors() {
+    let err = simple_encoder::button::Error::KPin(PortError(1));
+    let copy = err;
+    assert_eq!(err, copy);
+    assert_ne!(RotaryError::<_, PortError>::APin(PortError(1)), RotaryError::BPin(PortError(1)));
+    assert_eq!(err.into_kind(), PinKind::Key);
+    assert_eq!(RotaryError::<u8, u8>::BPin(0).into_kind(), PinKind::B);
+    assert_eq!(EncoderError::<u8, u8, u8>::APin(0).into_kind(), PinKind::A);
+}
+
+#[test]
+fn errors_propagate_with_question_mark() {
+    fn read() -> Result<(), Box<dyn core::error::Error>> {
+        Err(EncoderError::<BusFault, BusFault, BusFault>::KPin(BusFault))?;
+        Ok(())
+    }
+    assert_eq!(read().unwrap_err().to_string(), "encoder key pin error");
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use core::cell::{Cell, RefCell};
+use embedded_hal::digital::v2::OutputPin;
+use fugit::ExtU32;
+use simple_encoder::button::{Button, ButtonAction, TimeButton, TimeButtonAction};
+use simple_encoder::encoder::{Encoder, EncoderAction};
+use simple_encoder::feedback::{FeedbackButton, FeedbackEncoder, FeedbackError, TimeFeedbackButton};
+use simple_encoder::rotary::{FullStep, Rotation};
+
+/// Output recording every write, failing all of them while `failing`.
+#[derive(Default)]
+struct MockOut {
+    writes: RefCell<Vec<bool>>,
+    failing: Cell<bool>,
+}
+
+impl MockOut {
+    fn high(&self) -> bool {
+        self.writes.borrow().last().copied().unwrap_or(false)
+    }
+
+    fn write(&self, high: bool) -> Result<(), MockError> {
+        if self.failing.get() {
+            return Err(MockError);
+        }
+        self.writes.borrow_mut().push(high);
+        Ok(())
+    }
+}
+
+impl OutputPin for &MockOut {
+    type Error = MockError;
+
+    fn set_low(&mut self) -> Result<(), MockError> {
+        self.write(false)
+    }
+
+    fn set_high(&mut self) -> Result<(), MockError> {
+        self.write(true)
+    }
+}
+
+#[test]
+fn click_pulses_for_updates() {
+    use ButtonAction::*;
+    let (pin, out) = (MockPin::new(false), MockOut::default());
+    let mut button = FeedbackButton::new(Button::<_>::new(&pin), &out, 3);
+    let mut trace = Vec::new();
+    for high in [true, false, false, false, false, false] {
+        pin.set(high);
+        trace.push((button.update().unwrap(), out.high()));
+    }
+    assert_eq!(trace, [(Press, false), (Click, true), (None, true), (None, true), (None, false), (None, false)]);
+    // One rising and one falling write
+    assert_eq!(*out.writes.borrow(), [true, false]);
+}
+
+#[test]
+fn idle_updates_never_touch_the_pin() {
+    let (pin, out) = (MockPin::new(false), MockOut::default());
+    let mut button = FeedbackButton::new(Button::<_>::new(&pin), &out, 3);
+    for _ in 0..10 {
+        assert_eq!(button.update().unwrap(), ButtonAction::None);
+    }
+    // Held presses neither
+    pin.set(true);
+    for _ in 0..10 {
+        button.update().unwrap();
+    }
+    assert!(out.writes.borrow().is_empty());
+    // Zero disables the pulse
+    let mut button = FeedbackButton::new(Button::<_>::new(&pin), &out, 0);
+    for high in [true, false, false] {
+        pin.set(high);
+        button.update().unwrap();
+    }
+    assert!(out.writes.borrow().is_empty());
+}
+
+#[test]
+fn out_pin_errors_surface() {
+    let (pin, out) = (MockPin::new(false), MockOut::default());
+    let mut button = FeedbackButton::new(Button::<_>::new(&pin), &out, 3);
+    out.failing.set(true);
+    pin.set(true);
+    button.update().unwrap();
+    pin.set(false);
+    assert_eq!(button.update(), Err(FeedbackError::OutPin(MockError)));
+    pin.set_failing(true);
+    assert!(matches!(button.update(), Err(FeedbackError::Device(_))));
+}
+
+#[test]
+fn time_pulse_lasts_its_duration() {
+    use TimeButtonAction::*;
+    let (pin, out) = (MockPin::new(false), MockOut::default());
+    let mut button = TimeFeedbackButton::new(TimeButton::<_, Ms>::new(&pin), &out, 50.millis());
+    let mut trace = Vec::new();
+    for (t, high) in [(0, true), (20, false), (40, false), (69, false), (70, false), (100, false)] {
+        pin.set(high);
+        trace.push((button.update(Ms::from_ticks(t)).unwrap(), out.high()));
+    }
+    assert_eq!(
+        trace,
+        [(Press, false), (Click(20.millis()), true), (None, true), (None, true), (None, false), (None, false)]
+    );
+}
+
+#[test]
+fn encoder_pulses_on_detents_and_clicks() {
+    let (a, b, k) = (MockPin::new(true), MockPin::new(true), MockPin::new(true));
+    let out = MockOut::default();
+    let mut encoder = FeedbackEncoder::new(Encoder::<_, _, _, FullStep>::new(&a, &b, &k), &out, 1);
+    let mut trace = Vec::new();
+    for &(a_high, b_high) in &CW {
+        a.set(a_high);
+        b.set(b_high);
+        trace.push((encoder.update().unwrap(), out.high()));
+    }
+    trace.push((encoder.update().unwrap(), out.high()));
+    use EncoderAction::*;
+    assert_eq!(trace, [(None, false), (None, false), (None, false), (Rotate(Rotation::new(1)), true), (None, false)]);
+    k.set(false);
+    encoder.update().unwrap();
+    k.set(true);
+    assert_eq!(encoder.update().unwrap(), Click);
+    assert!(out.high());
+}
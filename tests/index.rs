@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use simple_encoder::button::EdgeButton;
+use simple_encoder::index::*;
+use simple_encoder::input::PinKind;
+use simple_encoder::position::IndexPositionRotary;
+use simple_encoder::rotary::{Rotary, Rotation};
+
+struct Pins {
+    a: MockPin,
+    b: MockPin,
+    z: MockPin,
+}
+
+impl Pins {
+    fn new() -> Self {
+        Self { a: MockPin::new(true), b: MockPin::new(true), z: MockPin::new(false) }
+    }
+
+    fn rotary(&self) -> RotaryWithIndex<&MockPin, &MockPin, &MockPin> {
+        RotaryWithIndex::new(Rotary::new(&self.a, &self.b), EdgeButton::new(&self.z))
+    }
+}
+
+// Turns one clockwise detent with the index high on its last `pulse` samples
+fn detent(rotary: &mut IndexPositionRotary<&MockPin, &MockPin, &MockPin>, pins: &Pins, pulse: usize) -> Vec<IndexedRotation> {
+    CW.iter()
+        .enumerate()
+        .map(|(i, &(a, b))| {
+            pins.a.set(a);
+            pins.b.set(b);
+            pins.z.set(i + pulse >= CW.len());
+            rotary.update().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn index_pulse_is_reported_once() {
+    let pins = Pins::new();
+    let mut rotary = pins.rotary();
+    let mut steps = Vec::new();
+    for (i, &(a, b)) in CW.iter().enumerate() {
+        pins.a.set(a);
+        pins.b.set(b);
+        pins.z.set(i >= 1);
+        steps.push(rotary.update().unwrap());
+    }
+    let index: Vec<_> = steps.iter().map(|step| step.index).collect();
+    assert_eq!(index, [false, true, false, false]);
+    assert_eq!(steps[3].rotation, Rotation::new(1));
+}
+
+#[test]
+fn index_corrects_drift() {
+    let pins = Pins::new();
+    let mut rotary = IndexPositionRotary::new(pins.rotary(), 0);
+    for _ in 0..3 {
+        detent(&mut rotary, &pins, 0);
+    }
+    assert_eq!(rotary.position(), 3);
+    // Missed detents put the count off by some
+    rotary.set_position(41);
+    let steps = detent(&mut rotary, &pins, 1);
+    assert!(steps[3].index);
+    assert_eq!(rotary.position(), 0);
+    // The rotations count for the delta, the correction does not
+    assert_eq!(rotary.take_delta(), 4);
+    detent(&mut rotary, &pins, 0);
+    assert_eq!(rotary.position(), 1);
+    // Zeroed where the pulse starts, rotations after it still count
+    rotary.set_index_position(100);
+    detent(&mut rotary, &pins, 2);
+    assert_eq!(rotary.position(), 101);
+}
+
+#[test]
+fn index_pin_errors() {
+    let pins = Pins::new();
+    let mut rotary = pins.rotary();
+    pins.z.set_failing(true);
+    let err = rotary.update().unwrap_err();
+    assert_eq!(err, RotaryIndexError::ZPin(MockError));
+    assert_eq!(err.into_kind(), PinKind::Z);
+    pins.z.set_failing(false);
+    pins.a.set_failing(true);
+    assert_eq!(rotary.update().unwrap_err().into_kind(), PinKind::A);
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::*;
+use core::cell::RefCell;
+use simple_encoder::button::{Button, ButtonAction, Error};
+use simple_encoder::encoder::{Encoder, EncoderAction};
+use simple_encoder::input::{ButtonInput, PinRef};
+use simple_encoder::rotary::{FullStep, Rotary, RotaryError};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct BusError;
+
+/// I/O expander with its port latched in a register, each line read is a bus transaction.
+#[derive(Default)]
+struct Expander {
+    port: u8,
+    reads: u32,
+    failing: bool,
+}
+
+/// Line `bit` of a shared expander.
+struct Line<'a>(&'a RefCell<Expander>, u8);
+
+impl ButtonInput for Line<'_> {
+    type Error = BusError;
+
+    fn is_high(&mut self) -> Result<bool, BusError> {
+        let mut expander = self.0.borrow_mut();
+        if expander.failing {
+            return Err(BusError);
+        }
+        expander.reads += 1;
+        Ok(expander.port & 1 << self.1 != 0)
+    }
+}
+
+fn set(expander: &RefCell<Expander>, bit: u8, high: bool) {
+    let mut expander = expander.borrow_mut();
+    expander.port = (expander.port & !(1 << bit)) | (high as u8) << bit;
+}
+
+#[test]
+fn button_behind_expander() {
+    use ButtonAction::*;
+    let expander = RefCell::new(Expander::default());
+    let mut button = Button::<_>::new(Line(&expander, 3));
+    let mut actions = Vec::new();
+    for high in [false, true, true, false] {
+        set(&expander, 3, high);
+        actions.push(button.update().unwrap());
+    }
+    assert_eq!(actions, [None, Press, Held, Click]);
+    assert_eq!(expander.borrow().reads, 4);
+    expander.borrow_mut().failing = true;
+    assert_eq!(button.update(), Err(Error::KPin(BusError)));
+}
+
+#[test]
+fn rotary_behind_expander() {
+    let expander = RefCell::new(Expander { port: 0b11, ..Default::default() });
+    let mut rotary = Rotary::<_>::new(Line(&expander, 0), Line(&expander, 1));
+    let mut angle = 0;
+    for &(a, b) in CW.iter().chain(&CW) {
+        set(&expander, 0, a);
+        set(&expander, 1, b);
+        angle += rotary.update().unwrap().angle();
+    }
+    assert_eq!(angle, 2);
+    expander.borrow_mut().failing = true;
+    assert_eq!(rotary.update(), Err(RotaryError::APin(BusError)));
+}
+
+#[test]
+fn encoder_mixes_pins_and_expander_lines() {
+    use EncoderAction::*;
+    let expander = RefCell::new(Expander { port: 0b100, ..Default::default() });
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut encoder = Encoder::<_, _, _, FullStep>::new(&a, &b, Line(&expander, 2));
+    let mut actions = Vec::new();
+    for high in [true, false, true] {
+        set(&expander, 2, high);
+        actions.push(encoder.update().unwrap());
+    }
+    assert_eq!(actions, [None, Press, Click]);
+}
+
+/// Pin owned by the test.
+struct Wire {
+    high: bool,
+    reads: u32,
+}
+
+impl Wire {
+    fn new() -> Self {
+        Self { high: true, reads: 0 }
+    }
+}
+
+impl ButtonInput for Wire {
+    type Error = BusError;
+
+    fn is_high(&mut self) -> Result<bool, BusError> {
+        self.reads += 1;
+        Ok(self.high)
+    }
+}
+
+#[test]
+fn borrowed_pins_are_given_back() {
+    let (mut a, mut b, mut k) = (Wire::new(), Wire::new(), Wire::new());
+    k.high = false;
+    {
+        let mut rotary = Rotary::<_, FullStep>::new(PinRef(&mut a), PinRef(&mut b));
+        let mut angle = 0;
+        for &(a_high, b_high) in &CW {
+            rotary.a_pin().0.high = a_high;
+            rotary.b_pin().0.high = b_high;
+            angle += rotary.update().unwrap().angle();
+        }
+        assert_eq!(angle, 1);
+    }
+    // Usable directly once the rotary is gone
+    assert_eq!((a.reads, b.reads), (4, 4));
+    a.high = false;
+    assert!(!a.is_high().unwrap());
+    a.high = true;
+    {
+        let mut button = Button::<_>::new(PinRef(&mut k));
+        button.k_pin().0.high = true;
+        assert_eq!(button.update().unwrap(), ButtonAction::Press);
+    }
+    // The encoder key is active low
+    k.high = true;
+    {
+        let mut encoder = Encoder::<_, _, _, FullStep>::new(PinRef(&mut a), PinRef(&mut b), PinRef(&mut k));
+        encoder.k_pin().0.high = false;
+        assert_eq!(encoder.update().unwrap(), EncoderAction::Press);
+    }
+    assert_eq!(k.reads, 2);
+}
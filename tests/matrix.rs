@@ -0,0 +1,96 @@
+use core::cell::Cell;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use simple_encoder::button::{ButtonAction, ButtonMatrix};
+
+/// 2×2 matrix with the key state set from the test. A column reads low while the
+/// driven row has its key pressed.
+#[derive(Default)]
+struct Board {
+    keys: [[Cell<bool>; 2]; 2],
+    driven: Cell<Option<usize>>,
+}
+
+struct Row<'a>(&'a Board, usize);
+struct Col<'a>(&'a Board, usize);
+
+impl OutputPin for Row<'_> {
+    type Error = ();
+
+    fn set_low(&mut self) -> Result<(), ()> {
+        self.0.driven.set(Some(self.1));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), ()> {
+        self.0.driven.set(None);
+        Ok(())
+    }
+}
+
+impl InputPin for Col<'_> {
+    type Error = ();
+
+    fn is_high(&self) -> Result<bool, ()> {
+        self.is_low().map(|low| !low)
+    }
+
+    fn is_low(&self) -> Result<bool, ()> {
+        Ok(self.0.driven.get().is_some_and(|row| self.0.keys[row][self.1].get()))
+    }
+}
+
+fn matrix(board: &Board) -> ButtonMatrix<Row<'_>, Col<'_>, 2, 2> {
+    ButtonMatrix::new([Row(board, 0), Row(board, 1)], [Col(board, 0), Col(board, 1)])
+}
+
+fn events<'a>(matrix: &mut ButtonMatrix<Row<'a>, Col<'a>, 2, 2>) -> Vec<(usize, usize, ButtonAction)> {
+    matrix.update().unwrap().collect()
+}
+
+#[test]
+fn keys_act_like_buttons() {
+    use ButtonAction::*;
+    let board = Board::default();
+    let mut matrix = matrix(&board);
+    assert_eq!(events(&mut matrix), []);
+    board.keys[1][0].set(true);
+    assert_eq!(events(&mut matrix), [(1, 0, Press)]);
+    board.keys[0][1].set(true);
+    assert_eq!(events(&mut matrix), [(0, 1, Press), (1, 0, Held)]);
+    assert!(matrix.is_down(1, 0) && !matrix.is_down(1, 1));
+    board.keys[1][0].set(false);
+    assert_eq!(events(&mut matrix), [(0, 1, Held), (1, 0, Click)]);
+    // Rows are released after the scan
+    assert!(board.driven.get().is_none());
+}
+
+#[test]
+fn single_row_scan() {
+    use ButtonAction::*;
+    let board = Board::default();
+    let mut matrix = matrix(&board);
+    matrix.set_scan_all(false);
+    board.keys[0][0].set(true);
+    board.keys[1][1].set(true);
+    assert_eq!(events(&mut matrix), [(0, 0, Press)]);
+    assert_eq!(events(&mut matrix), [(1, 1, Press)]);
+    assert_eq!(events(&mut matrix), [(0, 0, Held)]);
+}
+
+#[test]
+fn rectangle_is_ghosting() {
+    let board = Board::default();
+    let mut matrix = matrix(&board);
+    for (row, col) in [(0, 0), (0, 1), (1, 0)] {
+        board.keys[row][col].set(true);
+    }
+    events(&mut matrix);
+    assert!(!matrix.is_ghosting());
+    // A diode-less matrix reads the fourth corner as pressed too
+    board.keys[1][1].set(true);
+    events(&mut matrix);
+    assert!(matrix.is_ghosting());
+    board.keys[0][0].set(false);
+    events(&mut matrix);
+    assert!(!matrix.is_ghosting());
+}
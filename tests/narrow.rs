@@ -0,0 +1,113 @@
+#![cfg(feature = "narrow")]
+
+mod common;
+
+use common::*;
+use fugit::ExtU32;
+use simple_encoder::rotary::*;
+use simple_encoder::scale::StepMapper;
+
+// The 32 bit interpolation of `Linear` without `narrow`
+fn wide_gain(slow: u32, fast: u32, dt: u32, acceleration: u16) -> u16 {
+    match dt {
+        dt if dt <= fast => acceleration,
+        dt if dt >= slow => 1,
+        dt => {
+            let (acc, elapsed, size) = (acceleration as u64, (dt - fast) as u64, (slow - fast) as u64);
+            (acc - acc * elapsed / size) as u16
+        }
+    }
+}
+
+#[test]
+fn linear_matches_wide_for_small_accelerations() {
+    for (slow, fast) in [(100, 20), (255, 0), (300, 45)] {
+        let curve = Linear::new(slow.millis(), fast.millis()).unwrap();
+        for acceleration in 0..=255 {
+            for dt in 0..=slow + 1 {
+                let expected = wide_gain(slow, fast, dt, acceleration);
+                assert_eq!(curve.gain(dt.millis(), acceleration), expected, "{slow}/{fast} ms, {acceleration} at {dt} ms");
+            }
+        }
+    }
+}
+
+#[test]
+fn linear_stays_close_on_long_windows() {
+    for (slow, fast) in [(1000, 20), (200_000, 0), (u32::MAX, 0)] {
+        let curve = Linear::new(slow.millis(), fast.millis()).unwrap();
+        for acceleration in [256, 1000, 4097, 32768, u16::MAX] {
+            let mut last = acceleration;
+            for i in 0..=1000u64 {
+                let dt = (fast as u64 + (slow - fast) as u64 * i / 1000) as u32;
+                let gain = curve.gain(dt.millis(), acceleration);
+                let expected = wide_gain(slow, fast, dt, acceleration);
+                assert!(gain.abs_diff(expected) <= acceleration / 64 + 1, "{slow}/{fast} ms, {acceleration} at {dt} ms");
+                assert!(gain <= last);
+                last = gain;
+            }
+        }
+    }
+}
+
+#[test]
+fn rotation_saturates_at_i16() {
+    let max = Rotation::new(i32::MAX);
+    assert_eq!(max.angle(), i16::MAX as i32);
+    assert_eq!(Rotation::new(i32::MIN).angle(), i16::MIN as i32);
+    assert_eq!(Rotation::new(-40_000), Rotation::new(i16::MIN as i32));
+    assert_eq!(Rotation::cw(40_000), max);
+    assert_eq!(Rotation::ccw(32_768).angle(), -32_768);
+    assert_eq!(Rotation::ccw(40_000).angle(), -32_768);
+    assert_eq!(Rotation::new(300) * 200, max);
+    assert_eq!(Rotation::new(-3) * i32::MAX, -max - Rotation::CW_STEP);
+    assert_eq!(max + Rotation::new(2), max);
+}
+
+#[test]
+fn largest_acceleration_does_not_overflow() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, u16::MAX);
+    let mut angles = Vec::new();
+    for t in 0..4 {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            angles.push(rotary.update(Ms::from_ticks(t)).unwrap().angle());
+        }
+    }
+    // The first detent is a single step, the following ones saturate
+    assert_eq!(angles.iter().filter(|&&angle| angle != 0).collect::<Vec<_>>(), [&1, &32767, &32767, &32767]);
+    rotary.set_max_step(1000);
+    for &(a_high, b_high) in &CCW {
+        a.set(a_high);
+        b.set(b_high);
+        let angle = rotary.update(Ms::from_ticks(4)).unwrap().angle();
+        assert!(angle == 0 || angle == -1000);
+    }
+}
+
+#[test]
+fn accumulated_rotation_saturates() {
+    let mut decoder = QuadratureDecoder::<QuarterStep>::new();
+    for _ in 0..10_000 {
+        for &(a_high, b_high) in &CW {
+            decoder.step(!a_high, !b_high);
+        }
+    }
+    assert_eq!(decoder.take_accumulated().angle(), i16::MAX as i32);
+    assert_eq!(decoder.take_accumulated(), Rotation::ZERO);
+}
+
+#[test]
+fn step_mapper_output_exceeds_i16() {
+    let mut mapper = StepMapper::new(12500, 1).unwrap();
+    mapper.set_multiplier(10);
+    assert_eq!(mapper.apply(Rotation::CW_STEP), 125_000);
+    assert_eq!(mapper.apply(Rotation::new(-2)), -250_000);
+    // The remainder is carried at full width too
+    let mut mapper = StepMapper::new(100_000, 3).unwrap();
+    assert_eq!(mapper.apply(Rotation::CW_STEP), 33_333);
+    assert_eq!(mapper.apply(Rotation::new(2)), 66_667);
+    assert_eq!(mapper.apply(Rotation::CCW_STEP), -33_333);
+}
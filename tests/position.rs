@@ -0,0 +1,153 @@
+mod common;
+
+use common::*;
+use simple_encoder::position::*;
+use simple_encoder::rotary::{Rotary, Rotation, TimeRotary};
+
+fn turn(rotary: &mut PositionRotary<&MockPin, &MockPin>, a: &MockPin, b: &MockPin, cycle: &[(bool, bool)], times: usize) {
+    for _ in 0..times {
+        for &(a_high, b_high) in cycle {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update().unwrap();
+        }
+    }
+}
+
+#[test]
+fn position_follows_both_directions() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = PositionRotary::new(Rotary::new(&a, &b));
+    turn(&mut rotary, &a, &b, &CW, 5);
+    assert_eq!(rotary.position(), 5);
+    turn(&mut rotary, &a, &b, &CCW, 8);
+    assert_eq!(rotary.position(), -3);
+    assert_eq!(rotary.take_delta(), -3);
+    assert_eq!(rotary.take_delta(), 0);
+    // Moving the position is not a change
+    rotary.set_position(100);
+    assert_eq!((rotary.position(), rotary.take_delta()), (100, 0));
+    turn(&mut rotary, &a, &b, &CW, 2);
+    assert_eq!((rotary.position(), rotary.take_delta()), (102, 2));
+    rotary.set_position(0);
+    turn(&mut rotary, &a, &b, &CCW, 1);
+    assert_eq!(rotary.position(), -1);
+}
+
+#[test]
+fn position_includes_acceleration() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimePositionRotary::new(TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8));
+    for (t, cycle) in [(0, CW), (5, CW), (200, CCW), (205, CCW)] {
+        for &(a_high, b_high) in &cycle {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(t)).unwrap();
+        }
+    }
+    // 1 + 8, then -1 - 8
+    assert_eq!(rotary.position(), 0);
+    assert_eq!(rotary.take_delta(), 0);
+    rotary.set_position(-4);
+    for &(a_high, b_high) in &CW {
+        a.set(a_high);
+        b.set(b_high);
+        rotary.update(Ms::from_ticks(210)).unwrap();
+    }
+    assert_eq!((rotary.position(), rotary.take_delta()), (4, 8));
+}
+
+#[test]
+fn position_saturates() {
+    let mut position = Position::new(i32::MAX - 1);
+    assert_eq!(position.update(Rotation::new(5)), (1, EndStop::None));
+    assert_eq!(position.value(), i32::MAX);
+    assert_eq!(position.take_delta(), 1);
+}
+
+#[test]
+fn wrapping_position() {
+    let mut position = Position::new(10);
+    position.set_wrap(12);
+    position.update(Rotation::new(15));
+    assert_eq!((position.value(), position.wraps()), (1, 2));
+    position.set(0);
+    position.update(Rotation::new(-1));
+    assert_eq!((position.value(), position.wraps()), (11, 1));
+    // Jumps larger than the ring wrap as often as needed, either way
+    position.update(Rotation::new(-30));
+    assert_eq!((position.value(), position.wraps()), (5, -1));
+    position.update(Rotation::new(40));
+    assert_eq!((position.value(), position.wraps()), (9, 2));
+    assert_eq!(position.take_delta(), -1 - 30 + 15 + 40);
+    // A new modulus re-maps the position and clears the wraps
+    position.set_wrap(4);
+    assert_eq!((position.value(), position.wraps()), (1, 0));
+    position.set(-1);
+    assert_eq!(position.value(), 3);
+    position.set_wrap(0);
+    position.update(Rotation::new(10));
+    assert_eq!(position.value(), 13);
+}
+
+#[test]
+fn wrapping_accelerated_rotary() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimePositionRotary::new(TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 20));
+    rotary.set_wrap(12);
+    rotary.set_position(10);
+    for t in [0, 5] {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(t)).unwrap();
+        }
+    }
+    // 1 + 20 from 10
+    assert_eq!((rotary.position(), rotary.wraps()), (7, 2));
+}
+
+#[test]
+fn clamped_position_reports_end_stops() {
+    let mut position = Position::new(98);
+    position.set_range(0, 100);
+    assert_eq!(position.update(Rotation::new(1)), (1, EndStop::None));
+    // An accelerated step is applied up to the stop
+    assert_eq!(position.update(Rotation::new(8)), (1, EndStop::Max));
+    assert_eq!((position.value(), position.end_stop()), (100, EndStop::Max));
+    assert_eq!(position.update(Rotation::new(1)), (0, EndStop::Max));
+    assert_eq!(position.take_delta(), 2);
+    assert_eq!(position.update(Rotation::new(-1)), (-1, EndStop::None));
+    assert_eq!(position.end_stop(), EndStop::None);
+    position.set(2);
+    assert_eq!(position.update(Rotation::new(-1)), (-1, EndStop::None));
+    assert_eq!(position.update(Rotation::new(-8)), (-1, EndStop::Min));
+    assert_eq!(position.update(Rotation::new(-1)), (0, EndStop::Min));
+    // A new range clamps the position, swapped bounds are ordered
+    position.set(50);
+    position.set_range(20, 10);
+    assert_eq!(position.value(), 20);
+    position.set(-5);
+    assert_eq!(position.value(), 10);
+    position.clear_range();
+    assert_eq!(position.update(Rotation::new(-20)), (-20, EndStop::None));
+    assert_eq!(position.value(), -10);
+}
+
+#[test]
+fn clamped_accelerated_rotary() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimePositionRotary::new(TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8));
+    rotary.set_range(0, 5);
+    let mut stops = Vec::new();
+    for t in [0, 5, 10] {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(t)).unwrap();
+        }
+        stops.push((rotary.position(), rotary.end_stop()));
+    }
+    assert_eq!(stops, [(1, EndStop::None), (5, EndStop::Max), (5, EndStop::Max)]);
+    assert_eq!(rotary.take_delta(), 5);
+}
@@ -0,0 +1,1119 @@
+mod common;
+
+use common::*;
+use fugit::ExtU32;
+use embedded_hal::digital::v2::InputPin;
+use simple_encoder::rotary::*;
+
+fn spin<M: Steps>(rotary: &mut Rotary<(&MockPin, &MockPin), M>, a: &MockPin, b: &MockPin, cycle: &[(bool, bool)], times: usize) -> Vec<i32> {
+    let mut out = Vec::new();
+    for _ in 0..times {
+        for &(a_high, b_high) in cycle {
+            a.set(a_high);
+            b.set(b_high);
+            out.push(rotary.update().unwrap().angle());
+        }
+    }
+    out
+}
+
+#[test]
+fn full_step_detents() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 2), [0, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [0, 0, 0, -1]);
+}
+
+#[test]
+fn half_step_detents() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, HalfStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [0, 1, 0, 1]);
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [0, -1, 0, -1]);
+}
+
+#[test]
+fn quarter_step_detents() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, QuarterStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [1, 1, 1, 1]);
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [-1, -1, -1, -1]);
+}
+
+#[test]
+fn partial_detent_completes_at_rest() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &[(true, false), (false, false), (true, false), (true, true)], 1), [0, 0, 0, 0]);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW[..2], 1), [0, 0]);
+    assert_eq!(rotary.sub_steps(), 2);
+}
+
+#[test]
+fn pin_errors_map_to_channel() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_>::new(&a, &b);
+    a.set_failing(true);
+    assert_eq!(rotary.update(), Err(RotaryError::APin(MockError)));
+    a.set_failing(false);
+    b.set_failing(true);
+    assert_eq!(rotary.update(), Err(RotaryError::BPin(MockError)));
+}
+
+// Three clockwise detents sampled at every other transition, so most samples skip a state.
+// The rest is sampled twice, a partial detent completes on the second resting sample.
+fn decimated(double: DoubleTransition) -> (i32, u16) {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    rotary.set_double_transition(double);
+    let full: Vec<_> = CW.iter().cycle().take(12).copied().collect();
+    let samples = full.iter().step_by(2).chain(full.last()).chain(full.last());
+    let mut total = 0;
+    for &(a_high, b_high) in samples {
+        a.set(a_high);
+        b.set(b_high);
+        total += rotary.update().unwrap().angle();
+    }
+    (total, rotary.error_count())
+}
+
+#[test]
+fn double_transitions() {
+    assert_eq!(decimated(DoubleTransition::Ignore), (1, 5));
+    assert_eq!(decimated(DoubleTransition::Infer), (3, 5));
+}
+
+fn accelerated(acceleration: u16, gap: u32) -> i32 {
+    accelerated_in(None, acceleration, gap)
+}
+
+// Last of three detents `gap` ms apart, with the acceleration window `(slow, fast)` in ms
+fn accelerated_in(window: Option<(u32, u32)>, acceleration: u16, gap: u32) -> i32 {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, acceleration);
+    if let Some((slow, fast)) = window {
+        assert!(rotary.set_accel_window(slow.millis(), fast.millis()));
+    }
+    let mut t = 0;
+    let mut last = 0;
+    for _ in 0..3 {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            last = rotary.update(Ms::from_ticks(t)).unwrap().angle();
+        }
+        t += gap;
+    }
+    last
+}
+
+#[test]
+fn time_rotary_acceleration() {
+    assert_eq!(accelerated(8, 200), 1);
+    assert_eq!(accelerated(8, 100), 1);
+    assert_eq!(accelerated(8, 20), 8);
+    assert_eq!(accelerated(8, 5), 8);
+    let mid = accelerated(8, 60);
+    assert!(1 < mid && mid < 8, "{}", mid);
+    assert_eq!(accelerated(0, 5), 1);
+}
+
+#[test]
+fn time_rotary_window() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 4);
+    assert!(!rotary.set_accel_window(20.millis(), 100.millis()));
+    assert!(!rotary.set_accel_window(50.millis(), 50.millis()));
+    assert_eq!(*rotary.curve(), Linear::default());
+    assert!(rotary.set_accel_window(200.millis(), 50.millis()));
+    // Gain is full at `dt == fast` and one at `dt == slow`
+    let window = Some((200, 50));
+    assert_eq!(accelerated_in(window, 8, 49), 8);
+    assert_eq!(accelerated_in(window, 8, 50), 8);
+    assert_eq!(accelerated_in(window, 8, 51), 8);
+    assert_eq!(accelerated_in(window, 8, 69), 7);
+    assert_eq!(accelerated_in(window, 8, 125), 4);
+    assert_eq!(accelerated_in(window, 8, 199), 1);
+    assert_eq!(accelerated_in(window, 8, 200), 1);
+    // Defaults stay 100 and 20 ms
+    assert_eq!(accelerated(8, 20), 8);
+    assert_eq!(accelerated(8, 29), 8);
+    assert_eq!(accelerated(8, 30), 7);
+    assert_eq!(accelerated(8, 99), 1);
+}
+
+#[test]
+fn linear_curve_boundaries() {
+    let curve = Linear::new(100.millis(), 20.millis()).unwrap();
+    assert_eq!(curve, Linear::default());
+    assert_eq!(curve.gain(0.millis(), 10), 10);
+    assert_eq!(curve.gain(20.millis(), 10), 10);
+    assert_eq!(curve.gain(60.millis(), 10), 5);
+    assert_eq!(curve.gain(100.millis(), 10), 1);
+    assert_eq!(curve.gain(u32::MAX.millis(), 10), 1);
+    assert!(Linear::new(20.millis(), 20.millis()).is_none());
+}
+
+// The `narrow` curve cuts long windows to 8 bits, see tests/narrow.rs
+#[test]
+#[cfg(not(feature = "narrow"))]
+fn linear_curve_long_windows() {
+    // Windows past a minute with the largest acceleration do not overflow
+    let curve = Linear::new(200_000.millis(), 0.millis()).unwrap();
+    assert_eq!(curve.gain(0.millis(), u16::MAX), u16::MAX);
+    assert_eq!(curve.gain(100_000.millis(), u16::MAX), 32768);
+    assert_eq!(curve.gain(199_990.millis(), u16::MAX), 4);
+    let curve = Linear::new(u32::MAX.millis(), 0.millis()).unwrap();
+    assert_eq!(curve.gain((u32::MAX / 2).millis(), u16::MAX), 32768);
+}
+
+#[test]
+fn rotation_arithmetic_saturates() {
+    let max = Rotation::new(i32::MAX);
+    let min = Rotation::new(i32::MIN);
+    assert_eq!(max + Rotation::CW_STEP, max);
+    assert_eq!(min - Rotation::CW_STEP, min);
+    assert_eq!(-min, max);
+    assert_eq!(min.abs(), max);
+    assert_eq!(max * 2, max);
+    assert_eq!(Rotation::new(-3) * 2, Rotation::new(-6));
+    let mut rot = Rotation::ZERO;
+    rot += Rotation::new(5);
+    rot -= Rotation::new(7);
+    assert_eq!(rot, Rotation::new(-2));
+    assert!(Rotation::CCW_STEP < Rotation::ZERO);
+}
+
+#[test]
+fn rotation_sum() {
+    let rots = [1, -4, 7, 0, -2].map(Rotation::new);
+    assert_eq!(rots.iter().sum::<Rotation>(), Rotation::new(2));
+    let max = Rotation::new(i32::MAX);
+    assert_eq!([max, Rotation::CW_STEP, Rotation::CCW_STEP].into_iter().sum::<Rotation>(), Rotation::new(max.angle() - 1));
+}
+
+#[test]
+fn exponential_gain() {
+    let curve = Exponential { fast: 20.millis(), half_life: 10.millis() };
+    let gains: Vec<_> = [0, 20, 29, 30, 45, 60, 1000, u32::MAX].iter().map(|&dt| curve.gain(dt.millis(), 64)).collect();
+    assert_eq!(gains, [64, 64, 64, 32, 16, 4, 0, 0]);
+    assert_eq!(curve.gain(25.millis(), u16::MAX), u16::MAX);
+}
+
+// Detents at the given times and directions, polled every 10 ms until 500 ms past the last
+fn flicks(detents: &[(u32, bool)]) -> Vec<Flick> {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    rotary.set_flick(4, 100.millis(), 150.millis());
+    let mut found = Vec::new();
+    let end = detents.last().map_or(0, |&(t, _)| t) + 500;
+    let mut pending = detents.iter().peekable();
+    for t in (0..=end).step_by(10) {
+        if let Some(&&(at, cw)) = pending.peek() {
+            if at == t {
+                pending.next();
+                for &(a_high, b_high) in if cw { &CW } else { &CCW } {
+                    a.set(a_high);
+                    b.set(b_high);
+                    rotary.update(Ms::from_ticks(t)).unwrap();
+                }
+                continue;
+            }
+        }
+        rotary.update(Ms::from_ticks(t)).unwrap();
+        found.extend(rotary.take_flick());
+    }
+    found
+}
+
+#[test]
+fn flick_after_fast_burst() {
+    let burst: Vec<_> = (0..6).map(|i| (i * 10, true)).collect();
+    assert_eq!(flicks(&burst), [Flick { direction: Direction::Cw, detents: 6 }]);
+}
+
+#[test]
+fn slow_rotation_does_not_flick() {
+    let slow: Vec<_> = (0..8).map(|i| (i * 70, true)).collect();
+    assert_eq!(flicks(&slow), []);
+}
+
+#[test]
+fn reversal_restarts_flick_burst() {
+    let interrupted = [(0, true), (10, true), (20, true), (30, false), (40, false), (50, false)];
+    assert_eq!(flicks(&interrupted), []);
+    let reversed = [(0, true), (10, true), (20, false), (30, false), (40, false), (50, false)];
+    assert_eq!(flicks(&reversed), [Flick { direction: Direction::Ccw, detents: 4 }]);
+}
+
+#[test]
+fn smoothed_velocity_converges() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    rotary.set_velocity_smoothing(2);
+    let mut t = 0;
+    let mut history = Vec::new();
+    for gap in [100; 10].into_iter().chain([20; 20]) {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(t)).unwrap();
+        }
+        history.push(rotary.smoothed_velocity());
+        t += gap;
+    }
+    assert!((9..=10).contains(&history[9]), "{:?}", history);
+    assert!(history[9..].windows(2).all(|w| w[0] <= w[1]), "{:?}", history);
+    assert!((48..=50).contains(&history[29]), "{:?}", history);
+    assert!(history[11] < 40, "{:?}", history);
+
+    rotary.update(Ms::from_ticks(t + 2000)).unwrap();
+    assert_eq!(rotary.smoothed_velocity(), 0);
+}
+
+#[test]
+fn clock_rotary_accessors() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = ClockRotary::<_, _, _>::with_acceleration(&a, &b, StepClock { now: 0, step: 5 }, 6);
+    assert_eq!(rotary.acceleration(), 6);
+    rotary.set_acceleration(2);
+    assert_eq!(rotary.inner().acceleration(), 2);
+    rotary.clock().now = 100;
+    rotary.update().unwrap();
+    let (inner, clock) = rotary.into_parts();
+    assert_eq!(clock.now, 105);
+    assert_eq!(inner.acceleration(), 2);
+}
+
+#[test]
+fn idle_time_grows_and_resets_on_detent() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = ClockRotary::<_, _, _>::new(&a, &b, StepClock { now: 0, step: 10 });
+    assert_eq!(rotary.idle_for(), None);
+    for &(a_high, b_high) in &CW {
+        a.set(a_high);
+        b.set(b_high);
+        rotary.update().unwrap();
+    }
+    assert_eq!(rotary.idle_for().map(|d| d.ticks()), Some(10));
+    for _ in 0..3 {
+        assert!(rotary.update().unwrap().is_zero());
+    }
+    assert_eq!(rotary.idle_for().map(|d| d.ticks()), Some(50));
+    for &(a_high, b_high) in &CW {
+        a.set(a_high);
+        b.set(b_high);
+        rotary.update().unwrap();
+    }
+    assert_eq!(rotary.idle_for().map(|d| d.ticks()), Some(10));
+}
+
+// Multipliers of detents at the given gaps with acceleration 8
+fn gains(decay: u8, gaps: &[u32]) -> Vec<i32> {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+    rotary.set_gain_decay(decay);
+    let mut t = 0;
+    let mut gains = Vec::new();
+    for &gap in gaps {
+        t += gap;
+        let mut rot = 0;
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rot += rotary.update(Ms::from_ticks(t)).unwrap().angle();
+        }
+        gains.push(rot);
+    }
+    gains
+}
+
+#[test]
+fn gain_decay_ramps_down() {
+    let gaps = [0, 5, 5, 5, 200, 200, 200, 200, 200];
+    assert_eq!(gains(0, &gaps), [1, 8, 8, 8, 1, 1, 1, 1, 1]);
+    let decayed = gains(1, &gaps);
+    assert_eq!(decayed, [1, 8, 8, 8, 4, 2, 1, 1, 1]);
+    assert!(decayed[3..].windows(2).all(|w| w[1] <= w[0] && w[1] >= w[0] >> 1));
+    let quarter = gains(2, &gaps);
+    assert_eq!(&quarter[3..6], [8, 2, 1]);
+    // A pause of a second drops the gain at once
+    assert_eq!(gains(1, &[0, 5, 5, 1500]), [1, 8, 8, 1]);
+    assert_eq!(gains(1, &[0, 5, 5, 999]), [1, 8, 8, 4]);
+    assert_eq!(gains(1, &[0, 5, 5, 1000]), [1, 8, 8, 1]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn legacy_divider_maps_to_step_modes() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    assert_eq!(Rotary::<_, Divider<4>>::new(&a, &b).step_mode(), StepMode::Full);
+    assert_eq!(Rotary::<_, Divider<2>>::new(&a, &b).step_mode(), StepMode::Half);
+    assert_eq!(Rotary::<_, Divider<1>>::new(&a, &b).step_mode(), StepMode::Quarter);
+    assert_eq!(Rotary::<_, HalfStep>::new(&a, &b).step_mode(), StepMode::Half);
+    let mut rotary = Rotary::<_, Divider<2>>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [0, 1, 0, 1]);
+}
+
+// Both lines of one GPIO port register, A at bit 0 and B at bit 1
+struct Port<'a> {
+    register: &'a core::cell::Cell<u8>,
+}
+
+impl simple_encoder::input::QuadratureInput for Port<'_> {
+    type Error = MockError;
+
+    fn read(&mut self) -> Result<(bool, bool), MockError> {
+        match self.register.get() {
+            0xff => Err(MockError),
+            bits => Ok((bits & 1 != 0, bits & 2 != 0)),
+        }
+    }
+}
+
+#[test]
+fn port_decodes_like_pins() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let register = core::cell::Cell::new(0b11);
+    for mode in [StepMode::Full, StepMode::Half, StepMode::Quarter] {
+        // Every sequence of six samples, including invalid jumps
+        for seq in 0..1u32 << 12 {
+            let mut pins = Rotary::<_>::with_step_mode(&a, &b, mode);
+            let mut port = Rotary::<_>::from_port(Port { register: &register });
+            port.set_step_mode(mode);
+            for k in 0..6 {
+                let bits = (seq >> (2 * k) & 0b11) as u8;
+                a.set(bits & 1 != 0);
+                b.set(bits & 2 != 0);
+                register.set(bits);
+                assert_eq!(pins.update().unwrap(), port.update().unwrap());
+                assert_eq!(pins.sub_steps(), port.sub_steps());
+            }
+            assert_eq!(pins.error_count(), port.error_count());
+        }
+    }
+    let mut port = Rotary::<_>::from_port(Port { register: &register });
+    register.set(0xff);
+    assert_eq!(port.update(), Err(MockError));
+}
+
+#[test]
+fn reset_from_pins_mid_detent() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW[..2], 1), [0, 0]);
+    // Pins left mid-detent, e.g. across sleep: seeding reports nothing, the rest of
+    // that detent completes once the encoder rests
+    rotary.reset_from_pins().unwrap();
+    assert_eq!(spin(&mut rotary, &a, &b, &CW[2..], 1), [0, 0]);
+    assert_eq!(spin(&mut rotary, &a, &b, &[(true, true)], 2), [1, 0]);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [0, 0, 0, 1]);
+    rotary.reset();
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [0, 0, 0, -1]);
+}
+
+#[test]
+fn startup_grace_hides_noisy_prefix() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_startup_grace(&a, &b, 50.millis());
+    let mut angles = Vec::new();
+    // Random-looking levels for the first 50 ms, a partial detent among them
+    let noise = [(false, true), (true, true), (true, false), (false, false), (true, true), (false, false)];
+    for (t, &(a_high, b_high)) in (0..50).step_by(10).zip(noise.iter().cycle()) {
+        a.set(a_high);
+        b.set(b_high);
+        angles.push(rotary.update(Ms::from_ticks(t)).unwrap().angle());
+    }
+    a.set(true);
+    b.set(true);
+    angles.push(rotary.update(Ms::from_ticks(50)).unwrap().angle());
+    assert!(angles.iter().all(|&angle| angle == 0), "{angles:?}");
+    // Then only the clean detent
+    let mut angle = 0;
+    for (i, &(a_high, b_high)) in CW.iter().enumerate() {
+        a.set(a_high);
+        b.set(b_high);
+        angle += rotary.update(Ms::from_ticks(100 + i as u32)).unwrap().angle();
+    }
+    assert_eq!(angle, 1);
+}
+
+#[test]
+fn clock_rotary_parts_round_trip() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = ClockRotary::<_, _, _>::new(&a, &b, StepClock { now: 0, step: 1 });
+    for &(a_high, b_high) in &CW[..2] {
+        a.set(a_high);
+        b.set(b_high);
+        assert_eq!(rotary.update().unwrap().angle(), 0);
+    }
+    // The partial detent survives
+    let (inner, clock) = rotary.into_parts();
+    let mut rotary = ClockRotary::from_parts(inner, clock);
+    let mut angle = 0;
+    for &(a_high, b_high) in &CW[2..] {
+        a.set(a_high);
+        b.set(b_high);
+        angle += rotary.update().unwrap().angle();
+    }
+    assert_eq!(angle, 1);
+    assert_eq!(rotary.clock().now, 4);
+}
+
+#[test]
+fn direction_rotation_round_trip() {
+    for dir in [Direction::None, Direction::Cw, Direction::Ccw] {
+        assert_eq!(dir.to_rotation().direction(), dir);
+        assert_eq!(Rotation::from(dir).direction(), dir);
+        assert_eq!(Direction::from_sign(i32::from(dir)), dir);
+    }
+    // Positive is clockwise
+    assert_eq!(Rotation::CW_STEP, Rotation::new(1));
+    assert_eq!(Rotation::CCW_STEP, Rotation::new(-1));
+    assert_eq!(Rotation::new(5).direction(), Direction::Cw);
+    assert_eq!(Rotation::new(-5).direction(), Direction::Ccw);
+    assert_eq!(Rotation::new(0).direction(), Direction::None);
+}
+
+#[test]
+fn clockwise_detent_is_positive() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1)[3], Rotation::CW_STEP.angle());
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1)[3], Rotation::CCW_STEP.angle());
+}
+
+#[test]
+fn stepped_tiers() {
+    let curve = Stepped { tiers: [(10.millis(), 8), (40.millis(), 4), (80.millis(), 2)] };
+    let gains: Vec<_> = [0, 10, 11, 40, 41, 80, 81, u32::MAX].iter().map(|&dt| curve.gain(dt.millis(), 100)).collect();
+    assert_eq!(gains, [8, 8, 4, 4, 2, 2, 1, 1]);
+    assert_eq!(Stepped::<0> { tiers: [] }.gain(0.millis(), 100), 1);
+}
+
+// Angle of each detent turned clockwise after the given gaps since the previous one
+fn detent_gains<G: AccelCurve>(
+    rotary: &mut TimeRotary<&MockPin, &MockPin, Ms, FullStep, G>,
+    a: &MockPin,
+    b: &MockPin,
+    start: u32,
+    gaps: &[u32],
+) -> Vec<i32> {
+    let mut t = start;
+    gaps.iter()
+        .map(|&gap| {
+            t += gap;
+            let mut angle = 0;
+            for &(a_high, b_high) in &CW {
+                a.set(a_high);
+                b.set(b_high);
+                angle += rotary.update(Ms::from_ticks(t)).unwrap().angle();
+            }
+            angle
+        })
+        .collect()
+}
+
+// Gain by dt in 10 ms units, capped at the acceleration
+struct Table([u16; 4]);
+
+impl AccelCurve for Table {
+    fn gain(&self, dt: fugit::MillisDurationU32, acceleration: u16) -> u16 {
+        let slot = (dt.to_millis() / 10) as usize;
+        self.0.get(slot).copied().unwrap_or(1).min(acceleration)
+    }
+}
+
+#[test]
+fn custom_curve_maps_gaps() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms, FullStep, _>::with_curve(&a, &b, 6, Table([9, 5, 3, 2]));
+    // The first detent has no previous one to measure against
+    let gains = detent_gains(&mut rotary, &a, &b, 0, &[0, 5, 15, 25, 35, 45, 1000, 5]);
+    assert_eq!(gains[1..], [6, 5, 3, 2, 1, 1, 6]);
+}
+
+#[test]
+fn curve_swaps_at_runtime() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms, FullStep, _>::with_curve(
+        &a,
+        &b,
+        8,
+        Stepped { tiers: [(30.millis(), 8), (60.millis(), 3)] },
+    );
+    let gaps = [0, 20, 50, 70];
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &gaps)[1..], [8, 3, 1]);
+    rotary.set_curve(Stepped { tiers: [(30.millis(), 2), (60.millis(), 6)] });
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 1000, &gaps)[1..], [2, 6, 1]);
+}
+
+#[test]
+fn moderate_speed_never_swallows_detent() {
+    for acceleration in [1, 2, 3, 8, 100] {
+        let (a, b) = (MockPin::new(true), MockPin::new(true));
+        let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, acceleration);
+        let gaps: Vec<_> = core::iter::once(0).chain(20..=100).collect();
+        let gains = detent_gains(&mut rotary, &a, &b, 0, &gaps);
+        assert!(gains.iter().all(|&gain| gain >= 1), "{}: {:?}", acceleration, gains);
+    }
+}
+
+#[test]
+fn zero_curve_gain_is_one_step() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    // Exponential decays to zero past a few half lives
+    let curve = Exponential { fast: 20.millis(), half_life: 5.millis() };
+    let mut rotary = TimeRotary::<_, _, Ms, FullStep, _>::with_curve(&a, &b, 4, curve);
+    let gains = detent_gains(&mut rotary, &a, &b, 0, &[0, 20, 60, 100, 1000]);
+    assert_eq!(gains[1..], [4, 1, 1, 1]);
+    // Counter-clockwise detents keep their sign
+    let mut t = 2000;
+    for gap in [0, 100] {
+        t += gap;
+        let mut angle = 0;
+        for &(a_high, b_high) in &CCW {
+            a.set(a_high);
+            b.set(b_high);
+            angle += rotary.update(Ms::from_ticks(t)).unwrap().angle();
+        }
+        assert_eq!(angle, -1);
+    }
+}
+
+#[test]
+fn acceleration_extremes() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let fast = [0, 5, 0, 20];
+    // Zero disables acceleration at any speed
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 0);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &fast), [1, 1, 1, 1]);
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 1);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &fast), [1, 1, 1, 1]);
+    // Rotations saturate at the `i16` range with `narrow`
+    let max = if cfg!(feature = "narrow") { i16::MAX as i32 } else { u16::MAX as i32 };
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, u16::MAX);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &fast), [1, max, max, max]);
+    rotary.set_acceleration(0);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 100, &fast), [1, 1, 1, 1]);
+    rotary.set_acceleration(u16::MAX);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 200, &fast)[1..], [max, max, max]);
+}
+
+const RATE_TABLE: &[(u8, u16)] = &[(2, 2), (3, 4), (5, 8)];
+
+#[test]
+fn rate_acceleration_is_stable_under_jitter() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let jitter: Vec<_> = (0..12).map(|i| if i % 2 == 0 { 110 } else { 10 }).collect();
+    // The per gap mode jumps between the extremes
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+    let gains = detent_gains(&mut rotary, &a, &b, 0, &jitter);
+    assert_eq!(gains[2..6], [1, 8, 1, 8]);
+    // Three or four detents are always within the window
+    let mut rotary = TimeRotary::<_, _, Ms>::with_rate_acceleration(&a, &b, 200.millis(), RATE_TABLE);
+    let gains = detent_gains(&mut rotary, &a, &b, 2000, &jitter);
+    assert_eq!(gains[..4], [1, 2, 4, 4]);
+    assert!(gains[2..].iter().all(|&gain| gain == 4), "{:?}", gains);
+}
+
+#[test]
+fn rate_window_counts_recent_detents() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_rate_acceleration(&a, &b, 100.millis(), RATE_TABLE);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &[0, 10, 10, 10, 10, 10]), [1, 2, 4, 4, 8, 8]);
+    // Older detents fall out of the window
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 50, &[101, 10]), [1, 2]);
+    rotary.reset();
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 300, &[0, 10]), [1, 2]);
+    // A zero window goes back to the curve, which has no acceleration here
+    rotary.set_rate_acceleration(0.millis(), RATE_TABLE);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 400, &[0, 10, 10]), [1, 1, 1]);
+}
+
+#[test]
+fn max_step_clamps_accelerated_detents() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 20);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &[0, 5]), [1, 20]);
+    rotary.set_max_step(6);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 5, &[5, 5, 200]), [6, 6, 1]);
+    // The clamp keeps the sign
+    let mut t = 500;
+    for expected in [-1, -6] {
+        t += 5;
+        let mut angle = 0;
+        for &(a_high, b_high) in &CCW {
+            a.set(a_high);
+            b.set(b_high);
+            angle += rotary.update(Ms::from_ticks(t)).unwrap().angle();
+        }
+        assert_eq!(angle, expected);
+    }
+    rotary.set_max_step(0);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 700, &[5, 5]), [1, 20]);
+}
+
+#[test]
+fn reversal_mirrors_rotation() {
+    let trace = |reversed: bool| {
+        let (a, b) = (MockPin::new(true), MockPin::new(true));
+        let mut rotary = Rotary::<_, HalfStep>::new(&a, &b);
+        rotary.set_reversed(reversed);
+        assert_eq!(rotary.is_reversed(), reversed);
+        let mut out = spin(&mut rotary, &a, &b, &CW, 2);
+        out.extend(spin(&mut rotary, &a, &b, &CCW, 1));
+        out
+    };
+    let mirrored: Vec<_> = trace(false).iter().map(|angle| -angle).collect();
+    assert_eq!(trace(true), mirrored);
+    assert!(mirrored.contains(&-1) && mirrored.contains(&1));
+}
+
+#[test]
+fn reversal_keeps_acceleration() {
+    let gaps = [0, 5, 60, 200];
+    let gains = |reversed: bool| {
+        let (a, b) = (MockPin::new(true), MockPin::new(true));
+        let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+        rotary.set_reversed(reversed);
+        detent_gains(&mut rotary, &a, &b, 0, &gaps)
+    };
+    let forward = gains(false);
+    assert_eq!(forward[..2], [1, 8]);
+    assert_eq!(gains(true), forward.iter().map(|gain| -gain).collect::<Vec<_>>());
+}
+
+// The levels of `cycle` with A and/or B inverted
+fn inverted(cycle: &[(bool, bool)], a: bool, b: bool) -> Vec<(bool, bool)> {
+    cycle.iter().map(|&(a_high, b_high)| (a_high != a, b_high != b)).collect()
+}
+
+#[test]
+fn active_high_lines_decode_alike() {
+    use simple_encoder::button::Polarity::*;
+    for (a_pol, b_pol) in [(ActiveHigh, ActiveHigh), (ActiveHigh, ActiveLow), (ActiveLow, ActiveHigh)] {
+        let (flip_a, flip_b) = (a_pol == ActiveHigh, b_pol == ActiveHigh);
+        let (a, b) = (MockPin::new(!flip_a), MockPin::new(!flip_b));
+        let mut rotary = Rotary::<_, FullStep>::with_polarity(&a, &b, a_pol, b_pol);
+        assert_eq!(rotary.polarity(), (a_pol, b_pol));
+        assert_eq!(spin(&mut rotary, &a, &b, &inverted(&CW, flip_a, flip_b), 2), [0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(spin(&mut rotary, &a, &b, &inverted(&CCW, flip_a, flip_b), 1), [0, 0, 0, -1]);
+        // Polarity and direction compose
+        rotary.set_reversed(true);
+        assert_eq!(spin(&mut rotary, &a, &b, &inverted(&CW, flip_a, flip_b), 1), [0, 0, 0, -1]);
+        assert_eq!(rotary.error_count(), 0);
+    }
+}
+
+#[test]
+fn polarity_flip_reports_no_rotation() {
+    use simple_encoder::button::Polarity::*;
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [0, 0, 0, 1]);
+    // The lines now rest low
+    a.set(false);
+    b.set(false);
+    rotary.set_polarity(ActiveHigh, ActiveHigh);
+    assert_eq!(rotary.update().unwrap().angle(), 0);
+    assert_eq!(spin(&mut rotary, &a, &b, &inverted(&CW, true, true), 1), [0, 0, 0, 1]);
+}
+
+// Low line flags of a waveform given as high levels
+fn lows(cycle: &[(bool, bool)]) -> impl Iterator<Item = (bool, bool)> + '_ {
+    cycle.iter().map(|&(a_high, b_high)| (!a_high, !b_high))
+}
+
+#[test]
+fn levels_decode_like_pins() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut pins = Rotary::<_, HalfStep>::new(&a, &b);
+    let mut levels = Rotary::<_, HalfStep>::new(&a, &b);
+    let waveform = [CW, CW, CCW, [(true, false), (true, true), (false, true), (true, true)]].concat();
+    for (&(a_high, b_high), (a_low, b_low)) in waveform.iter().zip(lows(&waveform)) {
+        a.set(a_high);
+        b.set(b_high);
+        assert_eq!(levels.update_from_levels(a_low, b_low), pins.update().unwrap());
+        assert_eq!(levels.sub_steps(), pins.sub_steps());
+    }
+}
+
+#[test]
+fn time_rotary_levels_accelerate() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+    let mut angles = Vec::new();
+    for t in [0, 5, 200] {
+        let angle: i32 = lows(&CW).map(|(a_low, b_low)| rotary.update_from_levels(Ms::from_ticks(t), a_low, b_low).angle()).sum();
+        angles.push(angle);
+    }
+    assert_eq!(angles, [1, 8, 1]);
+}
+
+#[test]
+fn burst_between_slow_updates_accumulates() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(rotary.divider(), 4);
+    // An interrupt feeds twelve quarter steps while the main loop sleeps
+    for (a_low, b_low) in lows(&[CW, CW, CW].concat()) {
+        rotary.update_from_levels(a_low, b_low);
+    }
+    assert_eq!(rotary.take_accumulated(), Rotation::new(3));
+    assert_eq!(rotary.take_accumulated(), Rotation::ZERO);
+    // Opposite detents cancel out and partial ones wait for completion
+    for (a_low, b_low) in lows(&[&CW[..], &CCW, &CCW, &CW[..2]].concat()) {
+        rotary.update_from_levels(a_low, b_low);
+    }
+    assert_eq!(rotary.take_accumulated(), Rotation::new(-1));
+    assert_eq!(rotary.sub_steps(), 2);
+    rotary.reset();
+    assert_eq!(rotary.take_accumulated(), Rotation::ZERO);
+}
+
+fn progress<M: Steps>(rotary: &mut Rotary<(&MockPin, &MockPin), M>, a: &MockPin, b: &MockPin, cycle: &[(bool, bool)]) -> Vec<i8> {
+    cycle
+        .iter()
+        .map(|&(a_high, b_high)| {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update().unwrap();
+            rotary.sub_steps()
+        })
+        .collect()
+}
+
+#[test]
+fn sub_steps_track_detent_progress() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!((rotary.sub_steps(), rotary.divider()), (0, 4));
+    assert_eq!(progress(&mut rotary, &a, &b, &CW), [1, 2, 3, 0]);
+    assert_eq!(progress(&mut rotary, &a, &b, &CCW), [-1, -2, -3, 0]);
+    rotary.set_reversed(true);
+    assert_eq!(progress(&mut rotary, &a, &b, &CW), [-1, -2, -3, 0]);
+    let mut rotary = Rotary::<_, HalfStep>::new(&a, &b);
+    assert_eq!(rotary.divider(), 2);
+    assert_eq!(progress(&mut rotary, &a, &b, &CW), [1, 0, 1, 0]);
+    // Reading does not change decoding
+    let clock = StepClock { now: 0, step: 1 };
+    let mut rotary = ClockRotary::<_, _, _, FullStep>::new(&a, &b, clock);
+    let mut steps = Vec::new();
+    let mut angle = 0;
+    for &(a_high, b_high) in &CW {
+        a.set(a_high);
+        b.set(b_high);
+        angle += rotary.update().unwrap().angle();
+        steps.push(rotary.sub_steps());
+    }
+    assert_eq!((steps, angle, rotary.divider()), (vec![1, 2, 3, 0], 1, 4));
+}
+
+#[test]
+fn quarter_steps_report_every_transition() {
+    // Jog half way into a detent and back, then turn two detents
+    let jog = [(true, false), (false, false), (true, false), (true, true)];
+    let waveform = [&jog[..], &CW, &CW].concat();
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut raw = Rotary::<_, QuarterStep>::new(&a, &b);
+    let quarter = spin(&mut raw, &a, &b, &waveform, 1);
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    let full = spin(&mut rotary, &a, &b, &waveform, 1);
+    assert_eq!(quarter[..4], [1, 1, -1, -1]);
+    assert_eq!(full[..4], [0, 0, 0, 0]);
+    // No extra steps at rest, four transitions per detent
+    assert!(quarter.iter().all(|angle| angle.abs() == 1));
+    assert_eq!(quarter.iter().sum::<i32>(), 4 * full.iter().sum::<i32>());
+    assert_eq!(full.iter().sum::<i32>(), 2);
+}
+
+// EC11 clockwise cycles resting with both contacts closed, the open ones are `CW`
+const CW_CLOSED: [(bool, bool); 4] = [(false, true), (true, true), (true, false), (false, false)];
+
+// Wiggle off the closed rest and back
+const JIGGLE_CLOSED: [(bool, bool); 4] = [(false, true), (false, false), (true, false), (false, false)];
+
+#[test]
+fn rest_state_counts_one_step_per_detent() {
+    let (a, b) = (MockPin::new(false), MockPin::new(false));
+    let mut rotary = Rotary::<_, FullStep>::with_rest_state(&a, &b, RestState::Closed);
+    // A fresh history is open, seed it from the closed rest
+    rotary.reset_from_pins().unwrap();
+    assert_eq!(spin(&mut rotary, &a, &b, &CW_CLOSED, 2), [0, 0, 0, 1, 0, 0, 0, 1]);
+    assert_eq!(spin(&mut rotary, &a, &b, &JIGGLE_CLOSED, 2), [0; 8]);
+    // Pausing half way and turning back is no detent
+    let half_way = [(false, true), (true, true), (true, true), (false, true), (false, false)];
+    assert_eq!(spin(&mut rotary, &a, &b, &half_way, 1), [0; 5]);
+    // The open setting on a closed part takes the pause for a detent
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    rotary.reset_from_pins().unwrap();
+    assert_eq!(spin(&mut rotary, &a, &b, &half_way, 1), [0, 0, 1, 0, 0]);
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::with_rest_state(&a, &b, RestState::Open);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 2), [0, 0, 0, 1, 0, 0, 0, 1]);
+    let half_way = [(true, false), (false, false), (false, false), (true, false), (true, true)];
+    assert_eq!(spin(&mut rotary, &a, &b, &half_way, 1), [0; 5]);
+    // Either completes a pause at both rests
+    rotary.set_rest_state(RestState::Either);
+    assert_eq!(spin(&mut rotary, &a, &b, &half_way, 1), [0, 0, 1, 0, 0]);
+}
+
+#[test]
+fn impossible_transitions_are_counted() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    // A detent with one sample missed, then a glitch on both lines. The first sample only
+    // primes the history.
+    let corrupted = [(true, true), (false, false), (false, true), (true, true), (false, false), (true, true)];
+    spin(&mut rotary, &a, &b, &corrupted, 1);
+    assert_eq!(rotary.error_count(), 3);
+    spin(&mut rotary, &a, &b, &CW, 2);
+    assert_eq!(rotary.error_count(), 3);
+    rotary.clear_error_count();
+    assert_eq!(rotary.error_count(), 0);
+    for i in 0..70_000 {
+        rotary.update_from_levels(i % 2 == 0, i % 2 == 0);
+    }
+    assert_eq!(rotary.error_count(), u16::MAX);
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    for (t, &(a_high, b_high)) in corrupted.iter().enumerate() {
+        a.set(a_high);
+        b.set(b_high);
+        rotary.update(Ms::from_ticks(t as u32)).unwrap();
+    }
+    assert_eq!(rotary.error_count(), 3);
+    rotary.clear_error_count();
+    assert_eq!(rotary.error_count(), 0);
+}
+
+#[test]
+fn stuck_channel_is_reported() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    rotary.set_health_window(4);
+    rotary.update().unwrap();
+    // B shorts low, then only A keeps toggling
+    let b_low = [(true, false), (false, false), (true, false), (false, false), (true, false)];
+    let mut health = Vec::new();
+    for &(a_high, b_high) in &b_low {
+        a.set(a_high);
+        b.set(b_high);
+        rotary.update().unwrap();
+        health.push(rotary.health());
+    }
+    use RotaryHealth::*;
+    assert_eq!(health, [Ok, Ok, Ok, Ok, BStuck]);
+    // Clears once B toggles again
+    b.set(true);
+    rotary.update().unwrap();
+    assert_eq!(rotary.health(), Ok);
+    // A broken A trace
+    for b_high in [false, true, false, true] {
+        b.set(b_high);
+        rotary.update().unwrap();
+    }
+    assert_eq!(rotary.health(), AStuck);
+    rotary.set_health_window(0);
+    assert_eq!(rotary.health(), Ok);
+}
+
+// Clockwise quarter steps with a blip back after the first and the third
+const CHATTERY: [(bool, bool); 8] =
+    [(true, false), (true, true), (true, false), (false, false), (false, true), (false, false), (false, true), (true, true)];
+
+#[test]
+fn chatter_filter_drops_single_blips() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, QuarterStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CHATTERY, 1), [1, -1, 1, 1, 1, -1, 1, 1]);
+    rotary.set_chatter_filter(true);
+    let filtered = spin(&mut rotary, &a, &b, &CHATTERY, 2);
+    assert!(filtered.iter().all(|&angle| angle >= 0), "{:?}", filtered);
+    assert_eq!(filtered.iter().sum::<i32>(), 8);
+    // A reversal confirmed by a second step gets through a step late
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [0, -2, -1, -1]);
+}
+
+#[test]
+fn time_rotary_chatter_filter() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms, QuarterStep>::new(&a, &b);
+    rotary.set_chatter_filter(true);
+    let mut angles = Vec::new();
+    for (t, &(a_high, b_high)) in CHATTERY.iter().chain(&CCW).enumerate() {
+        a.set(a_high);
+        b.set(b_high);
+        angles.push(rotary.update(Ms::from_ticks(100 * t as u32)).unwrap().angle());
+    }
+    assert_eq!(angles, [1, 0, 0, 1, 1, 0, 0, 1, 0, -2, -1, -1]);
+}
+
+#[test]
+fn reversal_lockout_drops_quick_reversals() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+    rotary.set_reversal_lockout(80.millis());
+    let mut turn = |cycle: &[(bool, bool)], t: u32| -> i32 {
+        cycle
+            .iter()
+            .map(|&(a_high, b_high)| {
+                a.set(a_high);
+                b.set(b_high);
+                rotary.update(Ms::from_ticks(t)).unwrap().angle()
+            })
+            .sum()
+    };
+    assert_eq!(turn(&CW, 0), 1);
+    // Same direction passes within the window
+    assert_eq!(turn(&CW, 10), 8);
+    assert_eq!(turn(&CCW, 50), 0);
+    // The dropped detent does not restart the acceleration gap
+    assert_eq!(turn(&CW, 110), 1);
+    assert_eq!(turn(&CCW, 200), -1);
+    assert_eq!(turn(&CCW, 210), -8);
+}
+
+#[test]
+fn min_detent_interval_drops_glitches() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    rotary.set_min_detent_interval(2.millis());
+    let mut angles = Vec::new();
+    for t in [0, 1, 3, 4, 5, 10, 15, 20] {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            angles.extend(Some(rotary.update(Ms::from_ticks(t)).unwrap().angle()).filter(|&angle| angle != 0));
+        }
+    }
+    // Detents 1 ms apart vanish, a fast hand at 5 ms still passes. The glitch at 4 ms
+    // does not restart the interval, so the detent at 5 ms passes too.
+    assert_eq!(angles, [1, 1, 1, 1, 1, 1]);
+    assert_eq!(rotary.idle_for(Ms::from_ticks(22)), Some(2.millis()));
+}
+
+#[test]
+fn velocity_follows_detent_gap_and_decays() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    assert_eq!(rotary.velocity(Ms::from_ticks(0)), 0);
+    detent_gains(&mut rotary, &a, &b, 0, &[0]);
+    // One detent has no gap yet
+    assert_eq!(rotary.velocity(Ms::from_ticks(10)), 0);
+    detent_gains(&mut rotary, &a, &b, 0, &[50, 50]);
+    let velocity: Vec<_> = [100, 150, 200, 1100, 1101, 5000].iter().map(|&t| rotary.velocity(Ms::from_ticks(t))).collect();
+    assert_eq!(velocity, [20, 20, 10, 1, 0, 0]);
+    rotary.reset();
+    assert_eq!(rotary.velocity(Ms::from_ticks(5000)), 0);
+    // The clock wrapper reads the clock, 4 ms per detent here
+    let mut rotary = ClockRotary::<_, _, _>::new(&a, &b, StepClock { now: 0, step: 1 });
+    for _ in 0..2 {
+        for &(a_high, b_high) in &CW {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update().unwrap();
+        }
+    }
+    assert_eq!(rotary.velocity(), 250);
+}
+
+#[test]
+fn odometer_counts_physical_detents() {
+    // Three fast clockwise detents, two slow counter-clockwise ones, then a partial one
+    let script = [(0, CW), (5, CW), (10, CW), (500, CCW), (1000, CCW)];
+    for acceleration in [0, 1, 8, u16::MAX] {
+        let (a, b) = (MockPin::new(true), MockPin::new(true));
+        let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, acceleration);
+        for (t, cycle) in script {
+            for &(a_high, b_high) in &cycle {
+                a.set(a_high);
+                b.set(b_high);
+                rotary.update(Ms::from_ticks(t)).unwrap();
+            }
+        }
+        for &(a_high, b_high) in &CW[..2] {
+            a.set(a_high);
+            b.set(b_high);
+            rotary.update(Ms::from_ticks(2000)).unwrap();
+        }
+        assert_eq!(rotary.odometer(), (3, 2), "{}", acceleration);
+        // Kept over a reset
+        rotary.reset();
+        assert_eq!(rotary.odometer(), (3, 2));
+        rotary.reset_odometer();
+        assert_eq!(rotary.odometer(), (0, 0));
+    }
+}
+
+// Oversampled capture, bit 0 = A and bit 1 = B high: three clockwise detents, the first
+// with contact bounce on every edge, and one counter-clockwise detent
+const CAPTURE: [u8; 40] = [
+    0b11, 0b11, 0b01, 0b11, 0b01, 0b01, 0b00, 0b01, 0b00, 0b00, 0b10, 0b00, 0b10, 0b10, 0b11, 0b10, 0b11, 0b11,
+    0b01, 0b01, 0b00, 0b00, 0b10, 0b10, 0b11, 0b11,
+    0b01, 0b00, 0b10, 0b11, 0b11,
+    0b11, 0b10, 0b10, 0b00, 0b00, 0b01, 0b01, 0b11, 0b11,
+];
+
+#[test]
+fn captured_buffer_counts_every_detent() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(rotary.decode_packed(&CAPTURE, 0, 1), Rotation::new(2));
+    assert_eq!(rotary.odometer(), (3, 1));
+    assert_eq!(rotary.error_count(), 0);
+    // Buffers split mid detent join seamlessly
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    let split: i32 = CAPTURE.chunks(7).map(|chunk| rotary.decode_packed(chunk, 0, 1).angle()).sum();
+    assert_eq!(split, 2);
+    // Other bit positions, unrelated bits set
+    let moved: Vec<u8> = CAPTURE.iter().map(|&s| 0b1010_0101 | (s & 1) << 6 | (s >> 1) << 4).collect();
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(rotary.decode_packed(&moved, 6, 4), Rotation::new(2));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    let samples = CAPTURE.iter().map(|&s| (s & 1 == 0, s & 2 == 0));
+    assert_eq!(rotary.decode_samples(samples), Rotation::new(2));
+}
+
+// Detent angles across a sleep where the reconfigured A line read low once
+fn across_sleep(reset_on_wake: bool) -> (Vec<i32>, Option<fugit::MillisDurationU32>) {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = TimeRotary::<_, _, Ms>::with_acceleration(&a, &b, 8);
+    let mut angles = detent_gains(&mut rotary, &a, &b, 0, &[0]);
+    a.set(false);
+    rotary.update(Ms::from_ticks(10)).unwrap();
+    // Woken a moment later, back at rest
+    a.set(true);
+    if reset_on_wake {
+        rotary.reset_from_pins().unwrap();
+    }
+    let idle = rotary.idle_for(Ms::from_ticks(20));
+    angles.extend(detent_gains(&mut rotary, &a, &b, 20, &[0, 5]));
+    (angles, idle)
+}
+
+#[test]
+fn wake_from_sleep_reseeds_history() {
+    // The stale level swallows the first detent after waking
+    let (stale, _) = across_sleep(false);
+    assert_eq!(stale, [1, 0, 8]);
+    // Seeding drops the stale level and the acceleration history
+    assert_eq!(across_sleep(true), (vec![1, 1, 8], None));
+}
+
+#[test]
+fn pins_round_trip_through_into_parts() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut rotary = Rotary::<_, FullStep>::new(&a, &b);
+    assert_eq!(spin(&mut rotary, &a, &b, &CW, 1), [0, 0, 0, 1]);
+    assert!(rotary.a_pin().is_high().unwrap() && rotary.b_pin().is_high().unwrap());
+    let (a_pin, b_pin) = rotary.into_parts();
+    let mut rotary = Rotary::<_, FullStep>::new(a_pin, b_pin);
+    assert_eq!(spin(&mut rotary, &a, &b, &CCW, 1), [0, 0, 0, -1]);
+    let mut rotary = TimeRotary::<_, _, Ms>::new(&a, &b);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 0, &[0]), [1]);
+    let (a_pin, b_pin) = rotary.into_parts();
+    let mut rotary = TimeRotary::<_, _, Ms>::new(a_pin, b_pin);
+    assert_eq!(detent_gains(&mut rotary, &a, &b, 10, &[0]), [1]);
+}
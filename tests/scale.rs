@@ -0,0 +1,55 @@
+use simple_encoder::rotary::Rotation;
+use simple_encoder::scale::{Scale, StepMapper};
+
+fn total(scale: &mut Scale, rots: impl IntoIterator<Item = i32>) -> i32 {
+    rots.into_iter().map(|r| scale.apply(Rotation::new(r)).angle()).sum()
+}
+
+#[test]
+fn thirds_add_up() {
+    let mut scale = Scale::new(1, 3).unwrap();
+    assert_eq!([1, 1, 1].map(|r| scale.apply(Rotation::new(r)).angle()), [0, 0, 1]);
+    assert_eq!(total(&mut scale, [-1, -1, -1]), -1);
+    assert_eq!(total(&mut scale, [1, -1, 1, -1]), 0);
+    assert_eq!(scale.carry(), 0);
+}
+
+#[test]
+fn awkward_ratio_is_exact_over_time() {
+    let mut scale = Scale::new(2, 7).unwrap();
+    assert_eq!(total(&mut scale, core::iter::repeat_n(1, 7000)), 2000);
+    assert_eq!(total(&mut scale, [5, -3, 8, 1, -11].into_iter().cycle().take(500)), 0);
+    let mut scale = Scale::new(-5, 3).unwrap();
+    assert_eq!(total(&mut scale, [4, 4, 1]), -15);
+}
+
+#[test]
+fn accelerated_steps_saturate() {
+    let mut scale = Scale::new(i32::MAX, 1).unwrap();
+    assert_eq!(scale.apply(Rotation::new(3)), Rotation::new(i32::MAX));
+    assert!(Scale::new(1, 0).is_none());
+}
+
+#[test]
+fn step_mapper_stays_within_one_unit() {
+    let mut seed = 0x2545_f491u32;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    };
+    for (num, den) in [(1, 2), (25, 2), (2, 7), (-3, 11), (1000, 999)] {
+        let mut mapper = StepMapper::new(num, den).unwrap();
+        let (mut out, mut exact) = (0i64, 0i64);
+        for _ in 0..2000 {
+            if next() % 50 == 0 {
+                mapper.set_multiplier([1, 10, -4][next() as usize % 3]);
+            }
+            let rot = (next() % 9) as i32 - 4;
+            exact += rot as i64 * num as i64 * mapper.multiplier() as i64;
+            out += mapper.apply(Rotation::new(rot)) as i64;
+            assert!((out * den as i64 - exact).abs() < den as i64, "{}/{}", num, den);
+        }
+    }
+}
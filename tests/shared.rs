@@ -0,0 +1,65 @@
+mod common;
+
+use common::*;
+use simple_encoder::encoder::EncoderAction;
+use simple_encoder::rotary::Rotation;
+use simple_encoder::shared::SharedPinEncoder;
+
+fn feed(encoder: &mut SharedPinEncoder<&MockPin, &MockPin>, a: &MockPin, b: &MockPin, levels: &[(bool, bool)]) -> Vec<EncoderAction> {
+    levels
+        .iter()
+        .map(|&(a_high, b_high)| {
+            a.set(a_high);
+            b.set(b_high);
+            encoder.update().unwrap()
+        })
+        .filter(|act| !act.is_none() && *act != EncoderAction::Held)
+        .collect()
+}
+
+#[test]
+fn press_during_rotation() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut encoder = SharedPinEncoder::new(&a, &b, 5);
+    let mut levels = CW.to_vec();
+    levels.extend_from_slice(&CW[..1]);
+    levels.extend([(false, false); 20]);
+    levels.extend_from_slice(&CW);
+    levels.push((true, true));
+    assert_eq!(
+        feed(&mut encoder, &a, &b, &levels),
+        [
+            EncoderAction::Rotate(Rotation::CW_STEP),
+            EncoderAction::Press,
+            EncoderAction::Click,
+            EncoderAction::Rotate(Rotation::CW_STEP),
+        ],
+    );
+}
+
+#[test]
+fn rotation_right_after_release() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut encoder = SharedPinEncoder::new(&a, &b, 5);
+    let mut levels = vec![(false, true); 10];
+    levels.push((true, true));
+    levels.extend_from_slice(&CCW);
+    levels.extend_from_slice(&CCW);
+    assert_eq!(
+        feed(&mut encoder, &a, &b, &levels),
+        [
+            EncoderAction::Press,
+            EncoderAction::Click,
+            EncoderAction::Rotate(Rotation::CCW_STEP),
+            EncoderAction::Rotate(Rotation::CCW_STEP),
+        ],
+    );
+}
+
+#[test]
+fn short_low_pulse_is_not_a_press() {
+    let (a, b) = (MockPin::new(true), MockPin::new(true));
+    let mut encoder = SharedPinEncoder::new(&a, &b, 5);
+    let levels = [(false, true), (false, true), (true, true), (true, true)];
+    assert_eq!(feed(&mut encoder, &a, &b, &levels), []);
+}
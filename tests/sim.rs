@@ -0,0 +1,82 @@
+#![cfg(feature = "sim")]
+
+use simple_encoder::encoder::*;
+use simple_encoder::rotary::{FullStep, Rotation};
+use simple_encoder::sim::SimEncoder;
+
+type Ms = fugit::Instant<u32, 1, 1000>;
+
+#[test]
+fn time_encoder_follows_script() {
+    use TimeEncoderAction::*;
+    let mut sim = SimEncoder::<64>::new();
+    sim.push_idle(2).push_rotation(2).push_press().push_rotation(-1).push_release().push_idle(2);
+    let mut encoder = TimeEncoder::<_, _, _, Ms, FullStep>::new(sim.a(), sim.b(), sim.k());
+    let mut t = 0;
+    let mut actions = Vec::new();
+    while sim.advance() {
+        t += 10;
+        let act = encoder.update(Ms::from_ticks(t)).unwrap();
+        if act.rotation().is_some() || matches!(act, Press | Release(_)) {
+            actions.push(act);
+        }
+    }
+    assert_eq!(actions.len(), 5);
+    assert_eq!(actions[0], Rotate(Rotation::new(1)));
+    assert_eq!(actions[1], Rotate(Rotation::new(1)));
+    assert_eq!(actions[2], Press);
+    assert_eq!(actions[3], RotatePressed(Rotation::new(-1)));
+    assert!(matches!(actions[4], Release(_)));
+}
+
+#[test]
+fn script_levels() {
+    use embedded_hal::digital::v2::InputPin;
+    let mut sim = SimEncoder::<16>::new();
+    sim.push_rotation(1).push_idle(1).push_press();
+    assert_eq!(sim.len(), 6);
+    // Rest levels before the first sample
+    assert!(sim.a().is_high().unwrap() && sim.b().is_high().unwrap() && sim.k().is_high().unwrap());
+    let mut levels = Vec::new();
+    while sim.advance() {
+        levels.push((sim.a().is_high().unwrap(), sim.b().is_high().unwrap(), sim.k().is_low().unwrap()));
+    }
+    assert_eq!(
+        levels,
+        [(true, false, false), (false, false, false), (false, true, false), (true, true, false), (true, true, false), (true, true, true)],
+    );
+    // The last sample holds
+    assert_eq!(sim.position(), Some(5));
+    assert!(sim.k().is_low().unwrap());
+    sim.rewind();
+    assert_eq!(sim.position(), None);
+    assert!(sim.k().is_high().unwrap());
+}
+
+#[test]
+fn encoder_replays_rewound_script() {
+    use EncoderAction::*;
+    let mut sim = SimEncoder::<32>::new();
+    sim.push_click(3).push_rotation(-2);
+    let mut encoder = Encoder::<_, _, _, FullStep>::new(sim.a(), sim.b(), sim.k());
+    let mut script = || {
+        let mut actions = Vec::new();
+        while sim.advance() {
+            let act = encoder.update().unwrap();
+            if !act.is_none() && act != Held {
+                actions.push(act);
+            }
+        }
+        actions
+    };
+    let actions = script();
+    assert_eq!(actions, [Press, Click, Rotate(Rotation::new(-1)), Rotate(Rotation::new(-1))]);
+    sim.rewind();
+    assert_eq!(script(), actions);
+}
+
+#[test]
+#[should_panic(expected = "sim timeline full")]
+fn full_script_panics() {
+    SimEncoder::<7>::new().push_rotation(2);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+use simple_encoder::encoder::EncoderAction;
+use simple_encoder::rotary::Rotation;
+use simple_encoder::stepdir::*;
+
+// Step and direction levels fed one update each, returns the nonzero rotations
+fn run(rotary: &mut StepDirRotary<&MockPin, &MockPin>, step: &MockPin, dir: &MockPin, levels: &[(bool, bool)]) -> Vec<i32> {
+    levels
+        .iter()
+        .map(|&(step_high, dir_high)| {
+            step.set(step_high);
+            dir.set(dir_high);
+            rotary.update().unwrap().angle()
+        })
+        .filter(|&angle| angle != 0)
+        .collect()
+}
+
+#[test]
+fn direction_sampled_at_edge() {
+    let (step, dir) = (MockPin::new(false), MockPin::new(true));
+    let mut rotary = StepDirRotary::new(&step, &dir);
+    let levels = [(true, true), (false, false), (true, false), (true, true), (false, true), (true, true)];
+    assert_eq!(run(&mut rotary, &step, &dir, &levels), [1, -1, 1]);
+}
+
+#[test]
+fn bouncy_step_counts_once() {
+    let (step, dir) = (MockPin::new(false), MockPin::new(false));
+    let mut rotary = StepDirRotary::new(&step, &dir);
+    rotary.set_debounce_samples(3);
+    let bounce = [false, true, false, true, true, false, true, true, true, true, false, true, false, false, false];
+    let levels: Vec<_> = bounce.iter().chain(&bounce).map(|&high| (high, false)).collect();
+    assert_eq!(run(&mut rotary, &step, &dir, &levels), [-1, -1]);
+}
+
+#[test]
+fn step_dir_encoder_reports_rotate_pressed() {
+    let (step, dir, k) = (MockPin::new(false), MockPin::new(true), MockPin::new(false));
+    let mut encoder = StepDirEncoder::new(&step, &dir, &k);
+    assert_eq!(encoder.update().unwrap(), EncoderAction::None);
+    k.set(true);
+    assert_eq!(encoder.update().unwrap(), EncoderAction::Press);
+    step.set(true);
+    assert_eq!(encoder.update().unwrap(), EncoderAction::RotatePressed(Rotation::CW_STEP));
+    k.set(false);
+    assert_eq!(encoder.update().unwrap(), EncoderAction::Release);
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "switch-hal")]
+
+use simple_encoder::button::{Button, ButtonAction, Error};
+use simple_encoder::encoder::{Encoder, EncoderAction};
+use simple_encoder::rotary::FullStep;
+use switch_hal::mock::{Pin, State};
+use switch_hal::IntoSwitch;
+
+#[test]
+fn active_level_means_pressed() {
+    let mut button = Button::<_>::from_switch(Pin::with_state(State::High).into_active_high_switch());
+    assert_eq!(button.update(), Ok(ButtonAction::Press));
+    let mut button = Button::<_>::from_switch(Pin::with_state(State::High).into_active_low_switch());
+    assert_eq!(button.update(), Ok(ButtonAction::None));
+}
+
+#[test]
+fn switch_errors_surface_as_key_errors() {
+    let mut button = Button::<_>::from_switch(Pin::new().into_active_low_switch());
+    assert_eq!(button.update(), Err(Error::KPin("state not set")));
+}
+
+#[test]
+fn encoder_key_switch() {
+    let a = Pin::with_state(State::High);
+    let b = Pin::with_state(State::High);
+    let key = Pin::with_state(State::Low).into_active_low_switch();
+    let mut encoder = Encoder::<_, _, _, FullStep>::with_key_switch(a, b, key);
+    assert_eq!(encoder.update().unwrap(), EncoderAction::Press);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "critical-section")]
+
+mod common;
+
+use common::{Ms, CW};
+use core::convert::Infallible;
+use core::sync::atomic::{AtomicBool, Ordering};
+use embedded_hal::digital::v2::InputPin;
+use simple_encoder::button::{Button, ButtonAction, TimeButton, TimeButtonAction};
+use simple_encoder::encoder::{Encoder, EncoderAction};
+use simple_encoder::rotary::{FullStep, Rotation};
+use simple_encoder::sync::{SharedButton, SharedEncoder};
+use std::thread;
+
+/// Line shared with other threads, unlike the `Cell` based mock pin.
+struct Line(AtomicBool);
+
+impl Line {
+    const fn new(high: bool) -> Self {
+        Self(AtomicBool::new(high))
+    }
+
+    fn set(&self, high: bool) {
+        self.0.store(high, Ordering::SeqCst);
+    }
+}
+
+impl InputPin for &Line {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Infallible> {
+        Ok(self.0.load(Ordering::SeqCst))
+    }
+
+    fn is_low(&self) -> Result<bool, Infallible> {
+        Ok(!self.0.load(Ordering::SeqCst))
+    }
+}
+
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn shared_devices_are_sync_with_send_pins() {
+    assert_sync::<SharedEncoder<Encoder<&'static Line, &'static Line, &'static Line, FullStep>>>();
+    assert_sync::<SharedButton<TimeButton<&'static Line, Ms>>>();
+}
+
+#[test]
+fn empty_until_init() {
+    static BUTTON: SharedButton<Button<&'static Line>> = SharedButton::new();
+    static K: Line = Line::new(true);
+    assert_eq!(BUTTON.update(), Ok(ButtonAction::None));
+    assert!(!BUTTON.handle_press());
+    assert_eq!(BUTTON.with(|button| button.is_down()), None);
+    assert!(BUTTON.init(Button::new(&K)).is_none());
+    assert_eq!(BUTTON.update(), Ok(ButtonAction::Press));
+    assert!(BUTTON.take().is_some());
+    assert_eq!(BUTTON.update(), Ok(ButtonAction::None));
+}
+
+#[test]
+fn interrupt_thread_updates_while_main_handles_press() {
+    static A: Line = Line::new(true);
+    static B: Line = Line::new(true);
+    static K: Line = Line::new(true);
+    static ENCODER: SharedEncoder<Encoder<&'static Line, &'static Line, &'static Line, FullStep>> = SharedEncoder::new();
+    ENCODER.init(Encoder::new(&A, &B, &K));
+    // Stands in for the timer interrupt, sampling a detent and then the pressed key
+    let isr = thread::spawn(|| {
+        let mut actions = Vec::new();
+        for (a, b) in CW {
+            A.set(a);
+            B.set(b);
+            actions.push(ENCODER.update().unwrap());
+        }
+        K.set(false);
+        actions.push(ENCODER.update().unwrap());
+        actions
+    });
+    let actions: Vec<_> = isr.join().unwrap().into_iter().filter(|act| !act.is_none()).collect();
+    assert_eq!(actions, [EncoderAction::Rotate(Rotation::new(1)), EncoderAction::Press]);
+    // The main loop takes the press over, its release reports no click
+    assert!(ENCODER.handle_press());
+    K.set(true);
+    let release = thread::spawn(|| ENCODER.update().unwrap()).join().unwrap();
+    assert_eq!(release, EncoderAction::Release);
+}
+
+#[test]
+fn time_button_updates_from_another_thread() {
+    static K: Line = Line::new(false);
+    static BUTTON: SharedButton<TimeButton<&'static Line, Ms>> = SharedButton::new();
+    BUTTON.init(TimeButton::new(&K));
+    BUTTON.with(|button| button.set_held_interval(fugit::MillisDurationU32::from_ticks(1000)));
+    K.set(true);
+    let press = thread::spawn(|| BUTTON.update(Ms::from_ticks(10)).unwrap()).join().unwrap();
+    assert_eq!(press, TimeButtonAction::Press);
+    K.set(false);
+    let click = thread::spawn(|| BUTTON.update(Ms::from_ticks(60)).unwrap()).join().unwrap();
+    assert_eq!(click, TimeButtonAction::Click(fugit::MillisDurationU32::from_ticks(50)));
+}